@@ -0,0 +1,285 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+use crate::config::CustomField;
+use crate::db::{ItemChanges, JiraDatabase};
+use crate::models::{Epic, EpicId, Status, Story, StoryId};
+
+/// An operation of an `apply` file, e.g.
+/// `{"op": "create_story", "epic": "checkout", "name": "Pay", "points": 3}`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Operation {
+    CreateEpic {
+        /// Name later operations refer to the new epic by.
+        #[serde(rename = "ref")]
+        reference: Option<String>,
+        name: String,
+        #[serde(default)]
+        description: String,
+        status: Option<String>,
+    },
+    CreateStory {
+        #[serde(rename = "ref")]
+        reference: Option<String>,
+        epic: ItemRef,
+        name: String,
+        #[serde(default)]
+        description: String,
+        status: Option<String>,
+        points: Option<u32>,
+        #[serde(default)]
+        fields: BTreeMap<String, String>,
+    },
+    UpdateEpic {
+        id: ItemRef,
+        name: Option<String>,
+        description: Option<String>,
+        status: Option<String>,
+    },
+    UpdateStory {
+        id: ItemRef,
+        name: Option<String>,
+        description: Option<String>,
+        status: Option<String>,
+        points: Option<u32>,
+        #[serde(default)]
+        fields: BTreeMap<String, String>,
+    },
+    DeleteEpic {
+        id: ItemRef,
+    },
+    DeleteStory {
+        id: ItemRef,
+    },
+}
+
+/// An existing item by its id or one created earlier in the file by its `ref`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum ItemRef {
+    Id(u32),
+    Ref(String),
+}
+
+pub fn parse(contents: &str) -> Result<Vec<Operation>> {
+    Ok(serde_json::from_str(contents)?)
+}
+
+/// Runs the operations in order and saves the board once all of them
+/// succeeded, or not at all. Returns the ids of the created items by their ref.
+pub fn apply(
+    db: &JiraDatabase,
+    operations: &[Operation],
+    custom_fields: &[CustomField],
+) -> Result<BTreeMap<String, u32>> {
+    db.transaction(|db| {
+        let mut refs = HashMap::new();
+        for (i, operation) in operations.iter().enumerate() {
+            run(db, operation, custom_fields, &mut refs)
+                .with_context(|| format!("operation {} failed, nothing was applied", i + 1))?;
+        }
+        Ok(refs.into_iter().collect())
+    })
+}
+
+fn run(
+    db: &JiraDatabase,
+    operation: &Operation,
+    custom_fields: &[CustomField],
+    refs: &mut HashMap<String, u32>,
+) -> Result<()> {
+    let resolve = |item: &ItemRef, refs: &HashMap<String, u32>| match item {
+        ItemRef::Id(id) => Ok(*id),
+        ItemRef::Ref(name) => refs
+            .get(name)
+            .copied()
+            .ok_or_else(|| anyhow!(format!("no item created with ref '{name}'"))),
+    };
+    let parse_status = |status: &Option<String>| status.as_deref().map(str::parse).transpose();
+
+    match operation {
+        Operation::CreateEpic {
+            reference,
+            name,
+            description,
+            status,
+        } => {
+            let status: Option<Status> = parse_status(status)?;
+            let epic_id = db.create_epic(Epic::new(name.clone(), description.clone()))?;
+            if let Some(status) = status {
+                db.update_epic_status(epic_id, status)?;
+            }
+            remember(refs, reference, epic_id.0)
+        }
+        Operation::CreateStory {
+            reference,
+            epic,
+            name,
+            description,
+            status,
+            points,
+            fields,
+        } => {
+            let status: Option<Status> = parse_status(status)?;
+            let story_id = db.create_story(
+                Story::new(name.clone(), description.clone()),
+                EpicId(resolve(epic, refs)?),
+            )?;
+            if let Some(status) = status {
+                db.update_story_status(story_id, status)?;
+            }
+            set_story_details(db, story_id, *points, fields, custom_fields)?;
+            remember(refs, reference, story_id.0)
+        }
+        Operation::UpdateEpic {
+            id,
+            name,
+            description,
+            status,
+        } => db.update_epic(
+            EpicId(resolve(id, refs)?),
+            ItemChanges {
+                name: name.clone(),
+                description: description.clone(),
+                status: parse_status(status)?,
+                expected_version: None,
+            },
+        ),
+        Operation::UpdateStory {
+            id,
+            name,
+            description,
+            status,
+            points,
+            fields,
+        } => {
+            let story_id = StoryId(resolve(id, refs)?);
+            let changes = ItemChanges {
+                name: name.clone(),
+                description: description.clone(),
+                status: parse_status(status)?,
+                expected_version: None,
+            };
+            if changes != ItemChanges::default() {
+                db.update_story(story_id, changes)?;
+            }
+            set_story_details(db, story_id, *points, fields, custom_fields)
+        }
+        Operation::DeleteEpic { id } => db.delete_epic(EpicId(resolve(id, refs)?)),
+        Operation::DeleteStory { id } => {
+            let story_id = StoryId(resolve(id, refs)?);
+            let epic_id = db
+                .read_db()?
+                .story_epic(story_id)
+                .ok_or_else(|| anyhow!(format!("story not found: {story_id}")))?;
+            db.delete_story(epic_id, story_id)
+        }
+    }
+}
+
+fn remember(refs: &mut HashMap<String, u32>, reference: &Option<String>, id: u32) -> Result<()> {
+    match reference {
+        Some(name) if refs.insert(name.clone(), id).is_some() => {
+            Err(anyhow!(format!("ref '{name}' is used twice")))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Sets the points and custom fields given in the file; an empty field value
+/// removes the field.
+fn set_story_details(
+    db: &JiraDatabase,
+    story_id: StoryId,
+    points: Option<u32>,
+    fields: &BTreeMap<String, String>,
+    custom_fields: &[CustomField],
+) -> Result<()> {
+    if points.is_some() {
+        db.set_story_points(story_id, points)?;
+    }
+    for (name, value) in fields {
+        let field = custom_fields
+            .iter()
+            .find(|field| field.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| anyhow!(format!("no custom field named '{name}'")))?;
+        let value = match value.trim() {
+            "" => None,
+            value => Some(field.parse(value)?),
+        };
+        db.set_story_field(story_id, &field.name, value)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FieldKind;
+    use crate::db::test_utils::MockDB;
+
+    fn db() -> JiraDatabase {
+        JiraDatabase {
+            database: Box::new(MockDB::new()),
+        }
+    }
+
+    #[test]
+    fn apply_should_run_operations_with_refs() {
+        let db = db();
+        let operations = parse(
+            r#"[
+                { "op": "create_epic", "ref": "checkout", "name": "Checkout", "status": "ip" },
+                { "op": "create_story", "ref": "pay", "epic": "checkout", "name": "Pay",
+                  "points": 3, "fields": { "Severity": "HIGH" } },
+                { "op": "create_story", "ref": "refund", "epic": "checkout", "name": "Refund" },
+                { "op": "update_story", "id": "pay", "status": "resolved" },
+                { "op": "delete_story", "id": "refund" }
+            ]"#,
+        )
+        .unwrap();
+        let severity = CustomField {
+            name: "severity".to_owned(),
+            kind: FieldKind::Enum {
+                values: vec!["low".to_owned(), "high".to_owned()],
+            },
+        };
+
+        let refs = apply(&db, &operations, &[severity]).unwrap();
+        assert_eq!(
+            refs,
+            BTreeMap::from([
+                ("checkout".to_owned(), 1),
+                ("pay".to_owned(), 2),
+                ("refund".to_owned(), 3)
+            ])
+        );
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics[&EpicId(1)].status, Status::InProgress);
+        assert_eq!(db_state.epics[&EpicId(1)].stories, vec![StoryId(2)]);
+        let pay = &db_state.stories[&StoryId(2)];
+        assert_eq!(pay.status, Status::Resolved);
+        assert_eq!(pay.points, Some(3));
+        assert_eq!(pay.fields["severity"], "high");
+    }
+
+    #[test]
+    fn apply_should_change_nothing_if_an_operation_fails() {
+        let db = db();
+        let operations = parse(
+            r#"[
+                { "op": "create_epic", "name": "Checkout" },
+                { "op": "update_epic", "id": 99, "name": "Missing" }
+            ]"#,
+        )
+        .unwrap();
+
+        let error = apply(&db, &operations, &[]).unwrap_err();
+        assert_eq!(error.to_string(), "operation 2 failed, nothing was applied");
+        assert!(db.read_db().unwrap().epics.is_empty());
+        assert!(parse(r#"[{ "op": "rename_board" }]"#).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+
+use crate::db::{Database, JSONFileDatabase};
+use crate::models::DBState;
+
+/// Async counterpart of [`Database`] for storage that is reached over the
+/// network, so waiting for it does not block the thread driving the UI.
+pub trait AsyncDatabase {
+    fn read_db(&self) -> impl Future<Output = Result<DBState>> + Send;
+    fn write_db(&self, db_state: &DBState) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// Runs a blocking database on the runtime's blocking thread pool.
+pub struct Blocking<D> {
+    inner: Arc<D>,
+}
+
+impl<D> Blocking<D> {
+    pub fn new(inner: D) -> Self {
+        Self {
+            inner: Arc::new(inner),
+        }
+    }
+}
+
+impl Blocking<JSONFileDatabase> {
+    pub fn json_file(file_path: &str) -> Self {
+        Self::new(JSONFileDatabase {
+            file_path: file_path.to_owned(),
+        })
+    }
+}
+
+impl<D: Database + Send + Sync + 'static> AsyncDatabase for Blocking<D> {
+    async fn read_db(&self) -> Result<DBState> {
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || inner.read_db())
+            .await
+            .map_err(|e| anyhow!("database task failed: {e}"))?
+    }
+
+    async fn write_db(&self, db_state: &DBState) -> Result<()> {
+        let inner = Arc::clone(&self.inner);
+        let db_state = db_state.clone();
+        tokio::task::spawn_blocking(move || inner.write_db(&db_state))
+            .await
+            .map_err(|e| anyhow!("database task failed: {e}"))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Epic, EpicId};
+
+    #[test]
+    fn blocking_should_read_and_write_the_inner_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        let db = Blocking::json_file(path.to_str().unwrap());
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let mut db_state = DBState::new();
+        db_state
+            .epics
+            .insert(EpicId(1), Epic::new("Async".to_owned(), "".to_owned()));
+        db_state.last_item_id = 1;
+
+        runtime.block_on(db.write_db(&db_state)).unwrap();
+        assert_eq!(runtime.block_on(db.read_db()).unwrap(), db_state);
+        assert!(runtime
+            .block_on(Blocking::json_file("INVALID_PATH").read_db())
+            .is_err());
+    }
+}
@@ -0,0 +1,312 @@
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::models::{EpicId, IdScheme, Status};
+
+#[derive(Debug, Parser)]
+#[command(name = "scrumtask", version, about = "A Jira clone for the terminal")]
+pub struct Cli {
+    /// Database file to open; pass several times to open a workspace of boards
+    #[arg(long = "db", global = true, value_name = "PATH")]
+    db: Vec<String>,
+
+    /// Config file with key bindings and other settings
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        default_value = "./config.json"
+    )]
+    pub config: String,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// Returns the --db paths, or the configured database, or ./db.json.
+    pub fn db_paths(&self, configured: Option<String>) -> Vec<String> {
+        if self.db.is_empty() {
+            vec![configured.unwrap_or_else(|| "./db.json".to_owned())]
+        } else {
+            self.db.clone()
+        }
+    }
+
+    pub fn has_db(&self) -> bool {
+        !self.db.is_empty()
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print item counts, file details and status distribution of the database
+    Stats {
+        /// Print the statistics as JSON, an object keyed by path for several databases
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show how many stories were in each status per day as a cumulative flow chart
+    Flow {
+        /// Print the daily counts as CSV instead
+        #[arg(long)]
+        csv: bool,
+    },
+    /// Renumber epics and stories contiguously after deletions. The activity of deleted
+    /// items is dropped, so the flow chart no longer counts them
+    Compact {
+        /// Compact a board that is synced with other copies. Those copies take the
+        /// renumbered items for deleted and new ones unless they are compacted from the
+        /// same state
+        #[arg(long)]
+        force: bool,
+    },
+    /// Create the database file if it does not exist yet
+    Init {
+        /// Populate the new database with sample epics and stories
+        #[arg(long)]
+        demo: bool,
+    },
+    /// Create a story under an epic again every few days
+    Recur {
+        /// Epic the stories are created under
+        epic_id: EpicId,
+
+        /// Name of the created stories
+        name: String,
+
+        /// Number of days between two stories
+        #[arg(long, value_name = "DAYS")]
+        every: u32,
+
+        /// Description of the created stories
+        #[arg(long, default_value = "")]
+        description: String,
+    },
+    /// Choose how ids are shown and entered: one global counter or numbers per epic (E3-S1)
+    SetIdScheme {
+        #[arg(value_enum)]
+        scheme: IdScheme,
+    },
+    /// Start a sprint to plan stories into, after the previous one was closed
+    StartSprint {
+        name: String,
+
+        /// Story points the team expects to complete in the sprint
+        #[arg(long, value_name = "POINTS")]
+        capacity: u32,
+    },
+    /// Change fields of an epic or story without opening the interactive UI
+    Edit {
+        #[arg(value_enum)]
+        kind: ItemKind,
+
+        id: u32,
+
+        #[arg(long)]
+        name: Option<String>,
+
+        #[arg(long)]
+        description: Option<String>,
+
+        /// New status, accepts the same values as the status prompt (e.g. 2, ip, in-progress)
+        #[arg(long)]
+        status: Option<Status>,
+
+        /// Fail instead of overwriting if the item's version differs, e.g. after a concurrent change
+        #[arg(long, value_name = "VERSION")]
+        if_version: Option<u32>,
+    },
+    /// Move the stories of one epic to another, optionally only those with a given status
+    MoveStories {
+        /// Epic to move the stories from
+        from: EpicId,
+
+        /// Epic to move the stories to
+        to: EpicId,
+
+        /// Only move stories with this status
+        #[arg(long)]
+        status: Option<Status>,
+    },
+    /// Run the create, update and delete operations of a JSON file in order, saving
+    /// the board only if all of them succeed
+    Apply {
+        /// File with a list of operations, e.g. [{"op": "create_epic", "ref": "e", "name": "Checkout"}]
+        file: String,
+    },
+    /// Add an epic from another database file, with new ids for it and its stories
+    ImportEpic {
+        /// Database file to import from, e.g. one written by `export --epic`
+        file: String,
+
+        /// Epic to import, needed if the file contains more than one
+        #[arg(long, value_name = "EPIC_ID")]
+        epic: Option<EpicId>,
+
+        /// Rename the epic while importing it
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Add the epics and stories of a file in another format, picked from a preview of its rows
+    Import {
+        /// File to import from
+        file: String,
+
+        /// Format of the file: json or tsv. Guessed from its extension by default, falling back
+        /// to json
+        #[arg(long)]
+        format: Option<String>,
+
+        /// Import every row without showing the preview
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Check that a database file loads without losing content or referring to missing
+    /// items, and list what saving it again would write differently
+    Verify {
+        /// Database file to check
+        file: String,
+    },
+    /// Show what was added, removed or changed between two database files
+    Diff {
+        /// Earlier database file, the latest snapshot of the first --db if omitted
+        old: Option<String>,
+
+        /// Later database file, the first --db if omitted
+        new: Option<String>,
+
+        /// Print the changes as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Add a story to the inbox epic without opening the interactive UI
+    Capture {
+        /// Name of the story
+        text: String,
+
+        #[arg(long, default_value = "")]
+        description: String,
+    },
+    /// Print the JSON Schema of the database file, for editors and other tools to validate it
+    Schema,
+    /// Write a copy of the database to another file
+    Export {
+        /// File to write the copy to
+        output: String,
+
+        /// Format to write: json, tsv or html. Guessed from the extension of the output
+        /// file by default, falling back to json
+        #[arg(long)]
+        format: Option<String>,
+
+        /// Replace names and descriptions with hashed placeholders
+        #[arg(long)]
+        anonymize: bool,
+
+        /// Only export this epic and its stories, renumbered from 1 as a database of its own
+        #[arg(long, value_name = "EPIC_ID")]
+        epic: Option<EpicId>,
+    },
+    /// Exchange the database with the remote copy configured in the config file, or
+    /// merge it directly with another instance on the network. Peers send their boards
+    /// unencrypted, only use them on trusted networks or through a tunnel
+    Sync {
+        /// Wait for a peer to connect on this address or port, 127.0.0.1:7878 by default.
+        /// Both peers need the same peer_secret in their config
+        #[arg(
+            long,
+            value_name = "ADDR",
+            num_args = 0..=1,
+            default_missing_value = "127.0.0.1:7878",
+            conflicts_with = "connect"
+        )]
+        listen: Option<String>,
+
+        /// Connect to a peer that is listening on this address
+        #[arg(long, value_name = "HOST:PORT")]
+        connect: Option<String>,
+    },
+    /// Post a summary of yesterday's completed, in progress and blocked stories to the
+    /// configured Slack or Discord webhook
+    Digest {
+        /// Print the summary instead of posting it
+        #[arg(long)]
+        print: bool,
+    },
+    /// Print the board as an HTML status report, or mail it to the recipients configured
+    /// in the config file
+    Report {
+        /// Send the report through the configured SMTP server instead of printing it
+        #[arg(long)]
+        email: bool,
+    },
+    /// Serve the board as a read-only web page, for teammates without the CLI
+    View {
+        /// Address to serve the page on, use 0.0.0.0:PORT to reach it from other machines
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+        addr: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ItemKind {
+    Epic,
+    Story,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_default_to_interactive_mode_with_default_db() {
+        let cli = Cli::try_parse_from(["scrumtask"]).unwrap();
+
+        assert!(cli.command.is_none());
+        assert_eq!(cli.db_paths(None), vec!["./db.json".to_owned()]);
+        assert_eq!(
+            cli.db_paths(Some("boards/work.json".to_owned())),
+            vec!["boards/work.json".to_owned()]
+        );
+        assert_eq!(cli.config, "./config.json");
+    }
+
+    #[test]
+    fn should_parse_global_options_and_subcommands() {
+        let cli = Cli::try_parse_from(["scrumtask", "stats", "--json", "--db", "a.json"]).unwrap();
+
+        assert!(matches!(cli.command, Some(Command::Stats { json: true })));
+        assert_eq!(cli.db_paths(None), vec!["a.json".to_owned()]);
+
+        let cli = Cli::try_parse_from([
+            "scrumtask",
+            "edit",
+            "story",
+            "12",
+            "--status",
+            "ip",
+            "--name",
+            "New name",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Edit {
+                kind: ItemKind::Story,
+                id: 12,
+                status: Some(Status::InProgress),
+                description: None,
+                ..
+            })
+        ));
+        assert!(
+            Cli::try_parse_from(["scrumtask", "edit", "story", "12", "--status", "x"]).is_err()
+        );
+
+        let cli = Cli::try_parse_from(["scrumtask", "--db", "a.json", "--db", "b.json"]).unwrap();
+        assert_eq!(
+            cli.db_paths(None),
+            vec!["a.json".to_owned(), "b.json".to_owned()]
+        );
+    }
+}
@@ -0,0 +1,243 @@
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+
+use crate::models::{ActivityEvent, DBState, EpicId, Status};
+
+/// Summary of a closed epic, built from its stories and the activity log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloseOut {
+    pub epic_label: String,
+    pub name: String,
+    /// When the epic was created, if the activity log goes back that far.
+    pub started: Option<DateTime<Utc>>,
+    pub closed: DateTime<Utc>,
+    pub completed: Vec<String>,
+    pub left_open: Vec<String>,
+    /// Stories deleted from the epic before it was closed.
+    pub dropped: Vec<String>,
+    /// Time from the first move to In Progress until resolving, per completed story.
+    pub cycle_times: Vec<Duration>,
+}
+
+impl CloseOut {
+    pub fn collect(db_state: &DBState, epic_id: EpicId, now: DateTime<Utc>) -> Result<Self> {
+        let epic = db_state
+            .epics
+            .get(&epic_id)
+            .ok_or_else(|| anyhow!(format!("epic not found: {epic_id}")))?;
+        let activity = db_state
+            .activity
+            .iter()
+            .filter(|a| a.epic_id == Some(epic_id))
+            .collect::<Vec<_>>();
+
+        let mut completed = vec![];
+        let mut left_open = vec![];
+        let mut cycle_times = vec![];
+        for story_id in &epic.stories {
+            let Some(story) = db_state.stories.get(story_id) else {
+                continue;
+            };
+            let line = format!(
+                "{} {}",
+                db_state.story_label(epic_id, *story_id),
+                story.name
+            );
+            if !matches!(story.status, Status::Resolved | Status::Closed) {
+                left_open.push(line);
+                continue;
+            }
+            completed.push(line);
+
+            let changes = activity
+                .iter()
+                .filter(|a| a.story_id == Some(*story_id))
+                .filter_map(|a| match &a.event {
+                    ActivityEvent::StatusChanged { to, .. } => Some((a.at, to)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let started = changes
+                .iter()
+                .filter(|(_, to)| **to == Status::InProgress)
+                .map(|(at, _)| *at)
+                .min();
+            let finished = changes
+                .iter()
+                .filter(|(_, to)| matches!(to, Status::Resolved | Status::Closed))
+                .map(|(at, _)| *at)
+                .max();
+            if let (Some(started), Some(finished)) = (started, finished) {
+                cycle_times.push(finished - started);
+            }
+        }
+
+        let dropped = activity
+            .iter()
+            .filter(|a| a.event == ActivityEvent::Deleted && a.story_id.is_some())
+            .map(|a| a.name.clone())
+            .collect();
+        let started = activity
+            .iter()
+            .filter(|a| a.event == ActivityEvent::Created && a.story_id.is_none())
+            .map(|a| a.at)
+            .min();
+        let closed = if epic.status == Status::Closed
+            && epic.status_changed_at != DateTime::<Utc>::default()
+        {
+            epic.status_changed_at
+        } else {
+            now
+        };
+
+        Ok(Self {
+            epic_label: db_state.epic_label(epic_id),
+            name: epic.name.clone(),
+            started,
+            closed,
+            completed,
+            left_open,
+            dropped,
+            cycle_times,
+        })
+    }
+
+    pub fn average_cycle_time(&self) -> Option<Duration> {
+        let count = i32::try_from(self.cycle_times.len())
+            .ok()
+            .filter(|n| *n > 0)?;
+        Some(self.cycle_times.iter().copied().sum::<Duration>() / count)
+    }
+
+    /// Returns where the report of an epic is saved, next to the database file.
+    pub fn path_for(db_path: &str, epic_id: EpicId) -> PathBuf {
+        let db_path = Path::new(db_path);
+        let stem = db_path
+            .file_stem()
+            .map_or("db".into(), |stem| stem.to_string_lossy());
+        db_path.with_file_name(format!("{stem}-epic-{epic_id}-close-out.md"))
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    match duration.num_hours() {
+        hours if hours >= 48 => format!("{} days", duration.num_days()),
+        hours if hours >= 1 => format!("{hours} hours"),
+        _ => format!("{} minutes", duration.num_minutes().max(0)),
+    }
+}
+
+/// Renders the report as Markdown.
+impl Display for CloseOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "# Close-out: {} {}", self.epic_label, self.name)?;
+        writeln!(f)?;
+        match self.started {
+            Some(started) => writeln!(
+                f,
+                "- Duration: {} ({} to {})",
+                format_duration(self.closed - started),
+                started.date_naive(),
+                self.closed.date_naive()
+            )?,
+            None => writeln!(f, "- Closed: {}", self.closed.date_naive())?,
+        }
+        writeln!(f, "- Stories completed: {}", self.completed.len())?;
+        writeln!(f, "- Stories left open: {}", self.left_open.len())?;
+        writeln!(f, "- Stories dropped: {}", self.dropped.len())?;
+        if let Some(average) = self.average_cycle_time() {
+            writeln!(
+                f,
+                "- Average cycle time: {} over {} stories",
+                format_duration(average),
+                self.cycle_times.len()
+            )?;
+        }
+
+        for (title, stories) in [
+            ("Completed", &self.completed),
+            ("Left open", &self.left_open),
+            ("Dropped", &self.dropped),
+        ] {
+            if stories.is_empty() {
+                continue;
+            }
+            writeln!(f)?;
+            writeln!(f, "## {title}")?;
+            writeln!(f)?;
+            for story in stories {
+                writeln!(f, "- {story}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Activity, Epic, Story, StoryId};
+
+    #[test]
+    fn collect_should_summarize_the_epic() {
+        let start = Utc::now() - Duration::days(10);
+        let mut db_state = DBState::new();
+        let mut epic = Epic::new("Checkout".to_owned(), "".to_owned());
+        epic.status = Status::Closed;
+        epic.status_changed_at = start + Duration::days(10);
+        epic.stories = vec![StoryId(2), StoryId(3)];
+        db_state.epics.insert(EpicId(1), epic);
+        let mut done = Story::new("Pay".to_owned(), "".to_owned());
+        done.status = Status::Closed;
+        db_state.stories.insert(StoryId(2), done);
+        db_state
+            .stories
+            .insert(StoryId(3), Story::new("Refund".to_owned(), "".to_owned()));
+
+        let changed = |days, to| {
+            Activity::new(
+                start + Duration::days(days),
+                EpicId(1),
+                Some(StoryId(2)),
+                "Pay",
+                ActivityEvent::StatusChanged {
+                    from: Status::Open,
+                    to,
+                },
+            )
+        };
+        db_state.activity = vec![
+            Activity::new(start, EpicId(1), None, "Checkout", ActivityEvent::Created),
+            changed(1, Status::InProgress),
+            changed(4, Status::Closed),
+            Activity::new(
+                start,
+                EpicId(1),
+                Some(StoryId(4)),
+                "Coupons",
+                ActivityEvent::Deleted,
+            ),
+        ];
+
+        let report = CloseOut::collect(&db_state, EpicId(1), Utc::now()).unwrap();
+        assert_eq!(report.completed, vec!["2 Pay".to_owned()]);
+        assert_eq!(report.left_open, vec!["3 Refund".to_owned()]);
+        assert_eq!(report.dropped, vec!["Coupons".to_owned()]);
+        assert_eq!(report.average_cycle_time(), Some(Duration::days(3)));
+
+        let markdown = report.to_string();
+        assert!(markdown.starts_with("# Close-out: 1 Checkout\n"));
+        assert!(markdown.contains("- Duration: 10 days"));
+        assert!(markdown.contains("- Average cycle time: 3 days over 1 stories"));
+        assert!(markdown.contains("## Dropped\n\n- Coupons\n"));
+
+        assert!(CloseOut::collect(&db_state, EpicId(9), Utc::now()).is_err());
+        assert_eq!(
+            CloseOut::path_for("data/db.json", EpicId(1)),
+            PathBuf::from("data/db-epic-1-close-out.md")
+        );
+    }
+}
@@ -0,0 +1,1298 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Action, DBState, EpicId, Status, StatusLabels, Story, StoryId};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Database file opened when no --db is given, instead of ./db.json.
+    pub database: Option<String>,
+    pub keymap: Keymap,
+    pub templates: Templates,
+    pub highlights: Vec<HighlightRule>,
+    /// Changes made within this many milliseconds are written to the database file at once.
+    pub autosave_debounce_ms: u64,
+    /// Row density of the epic and story lists; toggling it on a page keeps the
+    /// choice for all pages of that type and saves it here.
+    pub density: Densities,
+    pub health: HealthRules,
+    /// How rejected input is signalled in addition to the error message.
+    pub invalid_input_feedback: InputFeedback,
+    /// Remote copy of the database kept up to date by `sync`.
+    pub remote: Option<RemoteConfig>,
+    /// Seconds between syncs with the remote while the UI is open; 0 only
+    /// syncs on request.
+    pub sync_interval_secs: u64,
+    /// Opens list rows on click and moves the selection with the wheel. Off by
+    /// default since mouse reporting keeps the terminal from selecting text.
+    pub mouse: bool,
+    /// Epic that `capture` adds stories to. Without one, an epic named "Inbox"
+    /// is used and created on first capture.
+    pub inbox_epic: Option<EpicId>,
+    /// Copies of the database file taken on launch, to diff the board against.
+    pub snapshots: SnapshotPolicy,
+    /// Labels shown and accepted instead of the built-in status names.
+    pub status_labels: StatusLabels,
+    /// Chat webhook that `digest` posts the daily summary to.
+    pub digest_webhook: Option<Webhook>,
+    /// Mail server and recipients of `report --email`.
+    pub report_email: Option<EmailConfig>,
+    /// Fields that can be set on every story in addition to the built-in ones.
+    pub custom_fields: Vec<CustomField>,
+    /// Weights of the "smart" story order.
+    pub smart_sort: SmartSort,
+    /// Recorded actions replayed by pressing their key on any page.
+    pub macros: BTreeMap<String, Vec<Action>>,
+    /// Changes made to matching items whenever the interactive UI is started.
+    pub rules: Vec<Rule>,
+    /// Which changes are confirmed with a yes/no question before they are made.
+    pub confirm: ConfirmPolicy,
+    /// Name changes are recorded under, instead of $USER.
+    pub user: Option<String>,
+    /// File every change made in the interactive UI is appended to as a JSON line.
+    pub action_log: Option<String>,
+    /// Secret both peers of `sync --listen`/`--connect` have to know, read from
+    /// `SCRUMTASK_PEER_SECRET` if left out. It only authenticates the peers, the
+    /// boards themselves are sent unencrypted.
+    pub peer_secret: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            database: None,
+            keymap: Keymap::default(),
+            templates: Templates::default(),
+            highlights: vec![],
+            autosave_debounce_ms: 500,
+            density: Densities::default(),
+            health: HealthRules::default(),
+            invalid_input_feedback: InputFeedback::default(),
+            remote: None,
+            sync_interval_secs: 300,
+            mouse: false,
+            inbox_epic: None,
+            snapshots: SnapshotPolicy::default(),
+            status_labels: BTreeMap::new(),
+            digest_webhook: None,
+            report_email: None,
+            custom_fields: vec![],
+            smart_sort: SmartSort::default(),
+            macros: BTreeMap::new(),
+            rules: vec![],
+            confirm: ConfirmPolicy::default(),
+            user: None,
+            action_log: None,
+            peer_secret: None,
+        }
+    }
+}
+
+impl Config {
+    /// Returns the secret peers authenticate with.
+    pub fn peer_secret(&self) -> Result<String> {
+        self.peer_secret
+            .clone()
+            .or_else(|| std::env::var("SCRUMTASK_PEER_SECRET").ok())
+            .filter(|secret| !secret.is_empty())
+            .ok_or_else(|| {
+                anyhow!(
+                    "no peer secret configured, add \"peer_secret\" to the config file or set SCRUMTASK_PEER_SECRET"
+                )
+            })
+    }
+
+    pub fn autosave_debounce(&self) -> Duration {
+        Duration::from_millis(self.autosave_debounce_ms)
+    }
+
+    /// Returns how long the background sync waits between syncs. Without an
+    /// interval it waits for a requested sync.
+    pub fn sync_interval(&self) -> Duration {
+        match self.sync_interval_secs {
+            0 => Duration::MAX,
+            secs => Duration::from_secs(secs),
+        }
+    }
+
+    /// Returns who is making changes: the configured user, else the login name.
+    pub fn acting_user(&self) -> Option<String> {
+        self.user
+            .clone()
+            .or_else(|| std::env::var("USER").ok())
+            .filter(|user| !user.trim().is_empty())
+    }
+
+    pub fn custom_field(&self, name: &str) -> Option<&CustomField> {
+        self.custom_fields
+            .iter()
+            .find(|field| field.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn load(file_path: &str) -> Result<Self> {
+        if !Path::new(file_path).exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(file_path)
+            .with_context(|| format!("failed to read config file: {file_path}"))?;
+        let config: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse config file: {file_path}"))?;
+        config.keymap.validate()?;
+        for key in config.macros.keys() {
+            config.keymap.check_macro_key(key)?;
+        }
+        for rule in &config.rules {
+            rule.validate(&config.custom_fields)?;
+        }
+
+        Ok(config)
+    }
+
+    /// Adds or replaces a macro in the config file and leaves the rest of the
+    /// file as it was written.
+    pub fn save_macro(file_path: &str, key: &str, actions: &[Action]) -> Result<()> {
+        let actions = serde_json::to_value(actions)?;
+        update_file(file_path, |contents| {
+            contents
+                .entry("macros")
+                .or_insert_with(|| serde_json::json!({}))
+                .as_object_mut()
+                .ok_or_else(|| anyhow!(format!("macros are not a JSON object: {file_path}")))?
+                .insert(key.to_owned(), actions);
+            Ok(())
+        })
+    }
+
+    /// Replaces the densities in the config file and leaves the rest of the
+    /// file as it was written.
+    pub fn save_density(file_path: &str, density: &Densities) -> Result<()> {
+        let density = serde_json::to_value(density)?;
+        update_file(file_path, |contents| {
+            contents.insert("density".to_owned(), density);
+            Ok(())
+        })
+    }
+
+    /// Replaces the inbox epic in the config file, or removes it, and leaves
+    /// the rest of the file as it was written.
+    pub fn save_inbox_epic(file_path: &str, epic_id: Option<EpicId>) -> Result<()> {
+        update_file(file_path, |contents| {
+            match epic_id {
+                Some(epic_id) => contents.insert("inbox_epic".to_owned(), epic_id.0.into()),
+                None => contents.remove("inbox_epic"),
+            };
+            Ok(())
+        })
+    }
+}
+
+/// Changes the top-level object of a config file, which is created if it is
+/// missing.
+fn update_file(
+    file_path: &str,
+    update: impl FnOnce(&mut serde_json::Map<String, serde_json::Value>) -> Result<()>,
+) -> Result<()> {
+    let mut contents = if Path::new(file_path).exists() {
+        serde_json::from_str(&fs::read_to_string(file_path)?)
+            .with_context(|| format!("failed to parse config file: {file_path}"))?
+    } else {
+        serde_json::json!({})
+    };
+    update(
+        contents
+            .as_object_mut()
+            .ok_or_else(|| anyhow!(format!("config file is not a JSON object: {file_path}")))?,
+    )?;
+
+    fs::write(file_path, serde_json::to_string_pretty(&contents)?)
+        .with_context(|| format!("failed to write config file: {file_path}"))
+}
+
+/// Takes a snapshot when the interactive UI is started and the latest one is
+/// older than the interval.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotPolicy {
+    pub every: SnapshotInterval,
+    /// Number of snapshots kept, the oldest are removed first. 0 keeps all.
+    pub keep: usize,
+}
+
+impl Default for SnapshotPolicy {
+    fn default() -> Self {
+        Self {
+            every: SnapshotInterval::Never,
+            keep: 14,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotInterval {
+    Never,
+    Daily,
+    Weekly,
+}
+
+impl SnapshotInterval {
+    pub fn days(&self) -> Option<i64> {
+        match self {
+            Self::Never => None,
+            Self::Daily => Some(1),
+            Self::Weekly => Some(7),
+        }
+    }
+}
+
+/// Rates an epic by the share of its unfinished stories that have been in
+/// their status for at least `stale_after_days`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthRules {
+    pub stale_after_days: i64,
+    pub warning_percent: u32,
+    pub critical_percent: u32,
+}
+
+impl Default for HealthRules {
+    fn default() -> Self {
+        Self {
+            stale_after_days: 14,
+            warning_percent: 25,
+            critical_percent: 50,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Health {
+    Good,
+    Warning,
+    Critical,
+}
+
+impl HealthRules {
+    /// Returns `None` for epics without unfinished stories.
+    pub fn assess<'a>(
+        &self,
+        stories: impl IntoIterator<Item = &'a Story>,
+        now: DateTime<Utc>,
+    ) -> Option<Health> {
+        let (stale, unfinished) = stories
+            .into_iter()
+            .filter(|s| !matches!(s.status, Status::Resolved | Status::Closed))
+            .fold((0, 0), |(stale, unfinished), s| {
+                let is_stale = s.status_changed_at != DateTime::<Utc>::default()
+                    && now.signed_duration_since(s.status_changed_at).num_days()
+                        >= self.stale_after_days;
+                (stale + u32::from(is_stale), unfinished + 1)
+            });
+
+        if unfinished == 0 {
+            return None;
+        }
+        let percent = stale * 100 / unfinished;
+        Some(if percent >= self.critical_percent {
+            Health::Critical
+        } else if percent >= self.warning_percent {
+            Health::Warning
+        } else {
+            Health::Good
+        })
+    }
+}
+
+/// Orders unfinished stories by what to work on next. A story's score is the
+/// sum of each weight times its factor; finished stories always come last.
+/// Priority and due date are read from custom fields, as stories have neither.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmartSort {
+    /// Enum custom field whose first value is the highest priority.
+    pub priority_field: Option<String>,
+    /// Date custom field holding the due date.
+    pub due_field: Option<String>,
+    /// Per priority level above the lowest.
+    pub priority_weight: i64,
+    /// Per day in the current status.
+    pub age_weight: i64,
+    /// Per day closer than `due_within_days` to the due date, overdue days included.
+    pub due_weight: i64,
+    pub due_within_days: i64,
+    /// Added once if the story is blocked; negative to sink blocked stories.
+    pub blocked_weight: i64,
+}
+
+impl Default for SmartSort {
+    fn default() -> Self {
+        Self {
+            priority_field: None,
+            due_field: None,
+            priority_weight: 10,
+            age_weight: 1,
+            due_weight: 5,
+            due_within_days: 14,
+            blocked_weight: -50,
+        }
+    }
+}
+
+impl SmartSort {
+    pub fn score(&self, story: &Story, custom_fields: &[CustomField], now: DateTime<Utc>) -> i64 {
+        let field = |name: &Option<String>| {
+            let name = name.as_ref()?;
+            let field = custom_fields
+                .iter()
+                .find(|field| field.name.eq_ignore_ascii_case(name))?;
+            Some((field, story.fields.get(&field.name)?))
+        };
+
+        let priority = match field(&self.priority_field) {
+            Some((
+                CustomField {
+                    kind: FieldKind::Enum { values },
+                    ..
+                },
+                value,
+            )) => values
+                .iter()
+                .position(|v| v == value)
+                .map_or(0, |i| (values.len() - 1 - i) as i64),
+            _ => 0,
+        };
+        let age = if story.status_changed_at == DateTime::<Utc>::default() {
+            0
+        } else {
+            now.signed_duration_since(story.status_changed_at)
+                .num_days()
+        };
+        let due = field(&self.due_field)
+            .and_then(|(_, value)| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok())
+            .map_or(0, |due| {
+                let days_left = due.signed_duration_since(now.date_naive()).num_days();
+                (self.due_within_days - days_left).max(0)
+            });
+        let blocked = i64::from(story.blocker.is_some());
+
+        self.priority_weight * priority
+            + self.age_weight * age
+            + self.due_weight * due
+            + self.blocked_weight * blocked
+    }
+
+    /// Suggests the open, unblocked story of the board with the highest score,
+    /// the lowest id among equals.
+    pub fn next_up(
+        &self,
+        db_state: &DBState,
+        custom_fields: &[CustomField],
+        now: DateTime<Utc>,
+    ) -> Option<StoryId> {
+        db_state
+            .epics
+            .values()
+            .flat_map(|epic| &epic.stories)
+            .filter_map(|id| Some((*id, db_state.stories.get(id)?)))
+            .filter(|(_, story)| story.status == Status::Open && story.blocker.is_none())
+            .max_by_key(|(id, story)| (self.score(story, custom_fields, now), Reverse(*id)))
+            .map(|(id, _)| id)
+    }
+
+    /// Sorts story ids by descending score, unfinished stories first. Ties keep
+    /// their order.
+    pub fn sort(
+        &self,
+        story_ids: &mut [StoryId],
+        stories: &HashMap<StoryId, Story>,
+        custom_fields: &[CustomField],
+        now: DateTime<Utc>,
+    ) {
+        story_ids.sort_by_cached_key(|id| match stories.get(id) {
+            Some(story) if !matches!(story.status, Status::Resolved | Status::Closed) => {
+                (false, Reverse(self.score(story, custom_fields, now)))
+            }
+            _ => (true, Reverse(0)),
+        });
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Densities {
+    pub home: DensitySetting,
+    pub epic_detail: DensitySetting,
+}
+
+impl Densities {
+    pub fn of(&self, page: DensityPage) -> &DensitySetting {
+        match page {
+            DensityPage::Home => &self.home,
+            DensityPage::EpicDetail => &self.epic_detail,
+        }
+    }
+}
+
+/// A page type whose density is toggled for all its pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DensityPage {
+    Home,
+    EpicDetail,
+}
+
+/// Density of a page type that every page of that type reads and toggles.
+/// Atomic so the config can be shared across threads.
+#[derive(Debug, Default)]
+pub struct DensitySetting(AtomicBool);
+
+impl DensitySetting {
+    pub fn new(density: Density) -> Self {
+        Self(AtomicBool::new(density == Density::Detailed))
+    }
+
+    pub fn get(&self) -> Density {
+        if self.0.load(Ordering::Relaxed) {
+            Density::Detailed
+        } else {
+            Density::Compact
+        }
+    }
+
+    pub fn toggle(&self) {
+        self.0.fetch_xor(true, Ordering::Relaxed);
+    }
+}
+
+impl Clone for DensitySetting {
+    fn clone(&self) -> Self {
+        Self::new(self.get())
+    }
+}
+
+impl PartialEq for DensitySetting {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl Eq for DensitySetting {}
+
+impl Serialize for DensitySetting {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DensitySetting {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Density::deserialize(deserializer).map(Self::new)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Density {
+    /// One line per item.
+    #[default]
+    Compact,
+    /// A second line per item with its description.
+    Detailed,
+}
+
+impl Density {
+    /// Returns how many items fit into `lines` lines.
+    pub fn rows_fitting(&self, lines: usize) -> usize {
+        match self {
+            Self::Compact => lines,
+            Self::Detailed => lines / 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputFeedback {
+    #[default]
+    None,
+    /// Ring the terminal bell.
+    Bell,
+    /// Briefly show the error message in inverse video.
+    Flash,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmPolicy {
+    Nothing,
+    /// Deleting epics, stories and conflict copies.
+    #[default]
+    Delete,
+    /// Deleting, and closing epics, stories and sprints.
+    DeleteAndClose,
+    /// Every change to the board.
+    Everything,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Delete,
+    Close,
+    Other,
+}
+
+impl ConfirmPolicy {
+    pub fn requires(&self, kind: ChangeKind) -> bool {
+        match self {
+            Self::Nothing => false,
+            Self::Delete => kind == ChangeKind::Delete,
+            Self::DeleteAndClose => kind != ChangeKind::Other,
+            Self::Everything => true,
+        }
+    }
+}
+
+/// Where `sync` stores the database. Credentials that are left out are read from the
+/// environment (`SCRUMTASK_WEBDAV_PASSWORD`, `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RemoteConfig {
+    Webdav {
+        /// Full URL of the database file, e.g. `https://dav.example.com/boards/db.json`
+        url: String,
+        username: Option<String>,
+        password: Option<String>,
+    },
+    S3 {
+        /// Endpoint of the S3-compatible service, e.g. `https://s3.eu-central-1.amazonaws.com`
+        endpoint: String,
+        bucket: String,
+        key: String,
+        #[serde(default = "default_region")]
+        region: String,
+        access_key: Option<String>,
+        secret_key: Option<String>,
+    },
+}
+
+fn default_region() -> String {
+    "us-east-1".to_owned()
+}
+
+/// Incoming webhook of a chat channel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Webhook {
+    Slack { url: String },
+    Discord { url: String },
+}
+
+/// SMTP server the status report is sent through. A password that is left out is
+/// read from `SCRUMTASK_SMTP_PASSWORD`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmailConfig {
+    /// Host of the server, reached with STARTTLS
+    pub smtp_host: String,
+    /// Port of the server, 587 if left out
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Sender address, e.g. `Board <board@example.com>`
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+/// Predefined names and descriptions offered when creating epics and stories.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Templates {
+    pub epics: Vec<Template>,
+    pub stories: Vec<Template>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Template {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// A user-defined story field, e.g. `{"name": "severity", "type": "enum", "values": ["low", "high"]}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomField {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: FieldKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum FieldKind {
+    String,
+    Number,
+    /// A date written as YYYY-MM-DD.
+    Date,
+    Enum {
+        values: Vec<String>,
+    },
+}
+
+impl CustomField {
+    /// Checks a value entered for the field and returns it the way it is stored.
+    pub fn parse(&self, input: &str) -> Result<String> {
+        let input = input.trim();
+        let invalid = |expected: &str| anyhow!(format!("{} must be {expected}", self.name));
+        match &self.kind {
+            FieldKind::String => Ok(input.to_owned()),
+            FieldKind::Number => input
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .map(|_| input.to_owned())
+                .ok_or_else(|| invalid("a number")),
+            FieldKind::Date => NaiveDate::parse_from_str(input, "%Y-%m-%d")
+                .map(|date| date.to_string())
+                .map_err(|_| invalid("a date like 2024-05-31")),
+            FieldKind::Enum { values } => values
+                .iter()
+                .find(|value| value.eq_ignore_ascii_case(input))
+                .cloned()
+                .ok_or_else(|| invalid(&format!("one of {}", values.join(", ")))),
+        }
+    }
+
+    /// Describes the values the field accepts, for prompts.
+    pub fn hint(&self) -> String {
+        match &self.kind {
+            FieldKind::String => "text".to_owned(),
+            FieldKind::Number => "number".to_owned(),
+            FieldKind::Date => "YYYY-MM-DD".to_owned(),
+            FieldKind::Enum { values } => values.join("/"),
+        }
+    }
+}
+
+/// Changes the epics or stories that have been in a status for at least
+/// `days_in_status` days, e.g. `{"items": "stories", "status": "InProgress",
+/// "days_in_status": 14, "set_field": {"name": "health", "value": "stale"}}`
+/// or `{"items": "stories", "status": "Open", "days_in_status": 30,
+/// "escalate": "priority"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rule {
+    pub items: RuleItems,
+    pub status: Option<Status>,
+    #[serde(default)]
+    pub days_in_status: i64,
+    #[serde(flatten)]
+    pub action: RuleAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleItems {
+    Epics,
+    Stories,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    SetStatus(Status),
+    /// Sets a custom field, which only stories have.
+    SetField {
+        name: String,
+        value: String,
+    },
+    /// Raises an enum custom field of stories one value towards its first,
+    /// which is the highest. Stories without a value get the last one. A
+    /// story is raised again after another `days_in_status` days unchanged.
+    Escalate(String),
+}
+
+impl Rule {
+    fn validate(&self, custom_fields: &[CustomField]) -> Result<()> {
+        let (name, value) = match &self.action {
+            RuleAction::SetStatus(_) => return Ok(()),
+            RuleAction::SetField { name, value } => (name, value),
+            RuleAction::Escalate(name) => {
+                if self.items == RuleItems::Epics {
+                    return Err(anyhow!("rules can only escalate stories"));
+                }
+                return match custom_fields.iter().find(|field| field.name == *name) {
+                    Some(CustomField {
+                        kind: FieldKind::Enum { .. },
+                        ..
+                    }) => Ok(()),
+                    Some(_) => Err(anyhow!(format!(
+                        "rule escalates '{name}', which is not an enum field"
+                    ))),
+                    None => Err(anyhow!(format!(
+                        "rule escalates unknown custom field '{name}'"
+                    ))),
+                };
+            }
+        };
+        if self.items == RuleItems::Epics {
+            return Err(anyhow!("rules can only set fields of stories"));
+        }
+        let field = custom_fields
+            .iter()
+            .find(|field| field.name == *name)
+            .ok_or_else(|| anyhow!(format!("rule sets unknown custom field '{name}'")))?;
+        if field.parse(value)? != *value {
+            return Err(anyhow!(format!(
+                "rule sets {name} to '{value}', write it as '{}'",
+                field.parse(value)?
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns whether an item with this status, changed to it at
+    /// `status_changed_at`, is matched.
+    pub fn matches(
+        &self,
+        status: &Status,
+        status_changed_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        self.status.as_ref().is_none_or(|s| s == status)
+            && (self.days_in_status <= 0
+                || status_changed_at != DateTime::<Utc>::default()
+                    && now.signed_duration_since(status_changed_at).num_days()
+                        >= self.days_in_status)
+    }
+}
+
+/// Styles list rows whose item matches all given conditions. The first matching rule wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HighlightRule {
+    pub status: Option<Status>,
+    pub name_contains: Option<String>,
+    pub min_days_in_status: Option<i64>,
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+    pub bold: bool,
+    pub blink: bool,
+}
+
+impl HighlightRule {
+    pub fn matches(
+        &self,
+        status: &Status,
+        name: &str,
+        status_changed_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        self.status.as_ref().is_none_or(|s| s == status)
+            && self
+                .name_contains
+                .as_ref()
+                .is_none_or(|text| name.to_lowercase().contains(&text.to_lowercase()))
+            && self.min_days_in_status.is_none_or(|days| {
+                status_changed_at != DateTime::<Utc>::default()
+                    && now.signed_duration_since(status_changed_at).num_days() >= days
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Grey,
+}
+
+/// Move the selection in lists down and up and turn their pages; they can't be
+/// bound to actions.
+pub const SELECTION_KEYS: [&str; 4] = ["j", "k", ">", "<"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keymap {
+    pub quit: String,
+    pub back: String,
+    pub create: String,
+    pub create_and_open: String,
+    pub create_multiple: String,
+    pub update: String,
+    pub delete: String,
+    pub sort: String,
+    pub help: String,
+    pub activity: String,
+    pub density: String,
+    pub recent: String,
+    pub pin: String,
+    pub flow: String,
+    pub move_stories: String,
+    pub inbox: String,
+    pub skip: String,
+    pub note: String,
+    pub block: String,
+    pub sync: String,
+    pub queue: String,
+    pub merge: String,
+    pub estimate: String,
+    pub plan: String,
+    pub retros: String,
+    pub fields: String,
+    pub filter: String,
+    pub watch: String,
+    pub next_up: String,
+    pub start_next_up: String,
+    pub focus: String,
+    pub resolve: String,
+    pub record_macro: String,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            quit: "q".to_owned(),
+            back: "p".to_owned(),
+            create: "c".to_owned(),
+            create_and_open: "C".to_owned(),
+            create_multiple: "m".to_owned(),
+            update: "u".to_owned(),
+            delete: "d".to_owned(),
+            sort: "s".to_owned(),
+            help: "?".to_owned(),
+            activity: "a".to_owned(),
+            density: "v".to_owned(),
+            recent: "r".to_owned(),
+            pin: "P".to_owned(),
+            flow: "f".to_owned(),
+            move_stories: "M".to_owned(),
+            inbox: "i".to_owned(),
+            skip: "n".to_owned(),
+            note: "N".to_owned(),
+            block: "B".to_owned(),
+            sync: "S".to_owned(),
+            queue: "Q".to_owned(),
+            merge: "=".to_owned(),
+            estimate: "e".to_owned(),
+            plan: "t".to_owned(),
+            retros: "R".to_owned(),
+            fields: "F".to_owned(),
+            filter: "/".to_owned(),
+            watch: "w".to_owned(),
+            next_up: "o".to_owned(),
+            start_next_up: "O".to_owned(),
+            focus: "z".to_owned(),
+            resolve: "D".to_owned(),
+            record_macro: "@".to_owned(),
+        }
+    }
+}
+
+impl Keymap {
+    pub fn bindings(&self) -> Vec<(&'static str, &str)> {
+        vec![
+            ("quit", &self.quit),
+            ("back", &self.back),
+            ("create", &self.create),
+            ("create and open", &self.create_and_open),
+            ("create multiple", &self.create_multiple),
+            ("update", &self.update),
+            ("delete", &self.delete),
+            ("sort", &self.sort),
+            ("help", &self.help),
+            ("activity", &self.activity),
+            ("density", &self.density),
+            ("recent", &self.recent),
+            ("pin", &self.pin),
+            ("flow", &self.flow),
+            ("move stories", &self.move_stories),
+            ("inbox", &self.inbox),
+            ("skip", &self.skip),
+            ("note", &self.note),
+            ("block", &self.block),
+            ("sync", &self.sync),
+            ("queue", &self.queue),
+            ("merge", &self.merge),
+            ("estimate", &self.estimate),
+            ("plan", &self.plan),
+            ("retros", &self.retros),
+            ("fields", &self.fields),
+            ("filter", &self.filter),
+            ("watch", &self.watch),
+            ("next up", &self.next_up),
+            ("start next up", &self.start_next_up),
+            ("focus", &self.focus),
+            ("resolve", &self.resolve),
+            ("record macro", &self.record_macro),
+        ]
+    }
+
+    fn validate(&self) -> Result<()> {
+        let bindings = self.bindings();
+
+        for (i, (action, key)) in bindings.iter().enumerate() {
+            check_key(action, key)?;
+            if let Some((other, _)) = bindings[..i].iter().find(|(_, k)| k == key) {
+                return Err(anyhow!("key '{key}' is bound to both {other} and {action}"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that a macro can be replayed with `key` on every page.
+    pub fn check_macro_key(&self, key: &str) -> Result<()> {
+        check_key("macro", key)?;
+        match self.bindings().iter().find(|(_, k)| *k == key) {
+            Some((action, _)) => Err(anyhow!("key '{key}' is already bound to {action}")),
+            None => Ok(()),
+        }
+    }
+}
+
+fn check_key(action: &str, key: &str) -> Result<()> {
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return Err(anyhow!("invalid key for {action}: '{key}'"));
+    }
+    if SELECTION_KEYS.contains(&key) {
+        return Err(anyhow!(
+            "key for {action} is reserved for moving through lists: '{key}'"
+        ));
+    }
+    if key.chars().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow!("key for {action} must not be numeric: '{key}'"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn load_from(contents: &str) -> Result<Config> {
+        let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
+        write!(tmpfile, "{}", contents).unwrap();
+
+        Config::load(tmpfile.path().to_str().unwrap())
+    }
+
+    #[test]
+    fn load_should_default_when_file_is_missing() {
+        assert_eq!(Config::load("INVALID_PATH").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn custom_field_should_check_values_by_type() {
+        let config = load_from(
+            r#"{ "custom_fields": [
+                { "name": "severity", "type": "enum", "values": ["low", "high"] },
+                { "name": "due", "type": "date" },
+                { "name": "cost", "type": "number" },
+                { "name": "owner", "type": "string" }
+            ] }"#,
+        )
+        .unwrap();
+
+        let severity = config.custom_field("Severity").unwrap();
+        assert_eq!(severity.parse("HIGH").unwrap(), "high");
+        assert!(severity.parse("urgent").is_err());
+        assert_eq!(severity.hint(), "low/high");
+
+        let due = config.custom_field("due").unwrap();
+        assert_eq!(due.parse(" 2024-05-31 ").unwrap(), "2024-05-31");
+        assert!(due.parse("31.05.2024").is_err());
+
+        let cost = config.custom_field("cost").unwrap();
+        assert_eq!(cost.parse("12.5").unwrap(), "12.5");
+        assert!(cost.parse("cheap").is_err());
+        assert!(cost.parse("NaN").is_err());
+
+        assert_eq!(
+            config.custom_field("owner").unwrap().parse("ana").unwrap(),
+            "ana"
+        );
+        assert!(config.custom_field("priority").is_none());
+    }
+
+    #[test]
+    fn smart_sort_should_weigh_priority_age_due_date_and_blockers() {
+        use chrono::TimeZone;
+
+        let config = load_from(
+            r#"{ "custom_fields": [
+                { "name": "priority", "type": "enum", "values": ["high", "medium", "low"] },
+                { "name": "due", "type": "date" }
+            ], "smart_sort": { "priority_field": "priority", "due_field": "due" } }"#,
+        )
+        .unwrap();
+        let smart_sort = &config.smart_sort;
+        let now = Utc.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+        let story = |fields: &[(&str, &str)], days_in_status: i64| {
+            let mut story = Story::new("".to_owned(), "".to_owned());
+            story.status_changed_at = now - chrono::Duration::days(days_in_status);
+            story.fields = fields
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            story
+        };
+        let score = |story: &Story| smart_sort.score(story, &config.custom_fields, now);
+
+        assert_eq!(score(&story(&[], 3)), 3);
+        assert_eq!(score(&story(&[("priority", "high")], 0)), 20);
+        assert_eq!(score(&story(&[("priority", "low")], 0)), 0);
+        assert_eq!(score(&story(&[("due", "2024-05-20")], 0)), 20);
+        assert_eq!(score(&story(&[("due", "2024-06-30")], 0)), 0);
+        let mut blocked = story(&[("priority", "high")], 0);
+        blocked.blocker = Some(crate::models::Blocker::Reason("".to_owned()));
+        assert_eq!(score(&blocked), -30);
+
+        let mut done = story(&[("priority", "high")], 0);
+        done.status = Status::Closed;
+        let stories = HashMap::from([
+            (StoryId(1), story(&[], 3)),
+            (StoryId(2), done),
+            (StoryId(3), story(&[("priority", "medium")], 0)),
+            (StoryId(4), blocked),
+            (StoryId(5), story(&[], 3)),
+        ]);
+        let mut ids = [1, 2, 3, 4, 5].map(StoryId).to_vec();
+        smart_sort.sort(&mut ids, &stories, &config.custom_fields, now);
+        assert_eq!(ids, [3, 1, 5, 4, 2].map(StoryId));
+    }
+
+    #[test]
+    fn next_up_should_suggest_the_best_open_unblocked_story() {
+        let mut db_state = DBState::new();
+        let mut epic = crate::models::Epic::new("".to_owned(), "".to_owned());
+        epic.stories = vec![StoryId(2), StoryId(3), StoryId(4), StoryId(5)];
+        db_state.epics.insert(EpicId(1), epic);
+        let now = Utc::now();
+        for (id, status, days_in_status) in [
+            (2, Status::Open, 1),
+            (3, Status::InProgress, 9),
+            (4, Status::Open, 5),
+            (5, Status::Open, 7),
+        ] {
+            let mut story = Story::new("".to_owned(), "".to_owned());
+            story.status = status;
+            story.status_changed_at = now - chrono::Duration::days(days_in_status);
+            db_state.stories.insert(StoryId(id), story);
+        }
+        db_state.stories.get_mut(&StoryId(5)).unwrap().blocker =
+            Some(crate::models::Blocker::Reason("".to_owned()));
+
+        let smart_sort = SmartSort::default();
+        assert_eq!(smart_sort.next_up(&db_state, &[], now), Some(StoryId(4)));
+        db_state.stories.get_mut(&StoryId(4)).unwrap().status = Status::Resolved;
+        assert_eq!(smart_sort.next_up(&db_state, &[], now), Some(StoryId(2)));
+        db_state.stories.get_mut(&StoryId(2)).unwrap().status = Status::Closed;
+        assert_eq!(smart_sort.next_up(&db_state, &[], now), None);
+    }
+
+    #[test]
+    fn load_should_reject_macros_on_bound_keys() {
+        let config =
+            load_from(r#"{ "macros": { "g": [{ "create_story": { "epic_id": 1 } }] } }"#).unwrap();
+        assert_eq!(
+            config.macros["g"],
+            vec![Action::CreateStory { epic_id: EpicId(1) }]
+        );
+
+        let error = load_from(r#"{ "macros": { "c": ["close_sprint"] } }"#).unwrap_err();
+        assert_eq!(error.to_string(), "key 'c' is already bound to create");
+        assert!(load_from(r#"{ "macros": { "7": [] } }"#).is_err());
+    }
+
+    #[test]
+    fn save_macro_should_keep_the_rest_of_the_file() {
+        let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
+        write!(tmpfile, r#"{{ "mouse": true }}"#).unwrap();
+        let path = tmpfile.path().to_str().unwrap();
+
+        Config::save_macro(path, "g", &[Action::NavigateToInbox]).unwrap();
+        let config = Config::load(path).unwrap();
+        assert!(config.mouse);
+        assert_eq!(config.macros["g"], vec![Action::NavigateToInbox]);
+    }
+
+    #[test]
+    fn save_density_should_round_trip() {
+        let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
+        write!(tmpfile, r#"{{ "mouse": true }}"#).unwrap();
+        let path = tmpfile.path().to_str().unwrap();
+        let density = Densities {
+            home: DensitySetting::new(Density::Compact),
+            epic_detail: DensitySetting::new(Density::Detailed),
+        };
+
+        Config::save_density(path, &density).unwrap();
+        let config = Config::load(path).unwrap();
+        assert!(config.mouse);
+        assert_eq!(config.density, density);
+    }
+
+    #[test]
+    fn save_inbox_epic_should_replace_or_remove_it() {
+        let mut tmpfile = tempfile::NamedTempFile::new().unwrap();
+        write!(tmpfile, r#"{{ "mouse": true, "inbox_epic": 7 }}"#).unwrap();
+        let path = tmpfile.path().to_str().unwrap();
+
+        Config::save_inbox_epic(path, Some(EpicId(2))).unwrap();
+        let config = Config::load(path).unwrap();
+        assert!(config.mouse);
+        assert_eq!(config.inbox_epic, Some(EpicId(2)));
+
+        Config::save_inbox_epic(path, None).unwrap();
+        assert_eq!(Config::load(path).unwrap().inbox_epic, None);
+    }
+
+    #[test]
+    fn load_should_check_the_fields_rules_set() {
+        let fields =
+            r#""custom_fields": [{ "name": "health", "type": "enum", "values": ["ok", "stale"] }]"#;
+        let rule = |items: &str, value: &str| {
+            format!(
+                r#"{{ {fields}, "rules": [{{ "items": "{items}",
+                   "set_field": {{ "name": "health", "value": "{value}" }} }}] }}"#
+            )
+        };
+
+        assert!(load_from(&rule("stories", "stale")).is_ok());
+        assert!(load_from(&rule("stories", "STALE")).is_err());
+        assert!(load_from(&rule("stories", "gone")).is_err());
+        assert!(load_from(&rule("epics", "stale")).is_err());
+
+        let escalate = |items: &str, name: &str| {
+            format!(r#"{{ {fields}, "rules": [{{ "items": "{items}", "escalate": "{name}" }}] }}"#)
+        };
+        assert!(load_from(&escalate("stories", "health")).is_ok());
+        assert!(load_from(&escalate("stories", "priority")).is_err());
+        assert!(load_from(&escalate("epics", "health")).is_err());
+    }
+
+    #[test]
+    fn load_should_merge_partial_keymap_with_defaults() {
+        let config = load_from(r#"{ "keymap": { "quit": "x", "back": "b" } }"#).unwrap();
+
+        assert_eq!(config.keymap.quit, "x");
+        assert_eq!(config.keymap.back, "b");
+        assert_eq!(config.keymap.create, "c");
+    }
+
+    #[test]
+    fn load_should_read_templates() {
+        let config = load_from(
+            r#"{ "templates": { "stories": [ { "name": "Bug", "description": "Steps:" } ] } }"#,
+        )
+        .unwrap();
+
+        assert!(config.templates.epics.is_empty());
+        assert_eq!(
+            config.templates.stories,
+            vec![Template {
+                name: "Bug".to_owned(),
+                description: "Steps:".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    fn highlight_rules_should_match_all_conditions() {
+        let config = load_from(
+            r#"{ "highlights": [ { "status": "InProgress", "min_days_in_status": 3, "foreground": "red" } ] }"#,
+        )
+        .unwrap();
+        let rule = &config.highlights[0];
+        let now = Utc::now();
+
+        assert_eq!(rule.foreground, Some(Color::Red));
+        assert!(rule.matches(
+            &Status::InProgress,
+            "",
+            now - chrono::Duration::days(3),
+            now
+        ));
+        assert!(!rule.matches(
+            &Status::InProgress,
+            "",
+            now - chrono::Duration::days(2),
+            now
+        ));
+        assert!(!rule.matches(&Status::Open, "", now - chrono::Duration::days(3), now));
+        assert!(!rule.matches(&Status::InProgress, "", DateTime::<Utc>::default(), now));
+
+        let rule = HighlightRule {
+            name_contains: Some("urgent".to_owned()),
+            ..Default::default()
+        };
+        assert!(rule.matches(&Status::Open, "URGENT: fix login", now, now));
+        assert!(!rule.matches(&Status::Open, "fix login", now, now));
+    }
+
+    #[test]
+    fn health_should_rate_by_share_of_stale_stories() {
+        let config = load_from(r#"{ "health": { "stale_after_days": 7 } }"#).unwrap();
+        let rules = &config.health;
+        assert_eq!(rules.critical_percent, 50);
+
+        let now = Utc::now();
+        let story = |status: Status, days: i64| {
+            let mut story = Story::new("".to_owned(), "".to_owned());
+            story.status = status;
+            story.status_changed_at = now - chrono::Duration::days(days);
+            story
+        };
+        let fresh = story(Status::Open, 1);
+        let stale = story(Status::InProgress, 7);
+        let done = story(Status::Closed, 30);
+
+        assert_eq!(rules.assess([&done], now), None);
+        assert_eq!(rules.assess([&fresh, &done], now), Some(Health::Good));
+        assert_eq!(
+            rules.assess([&fresh, &fresh, &fresh, &stale], now),
+            Some(Health::Warning)
+        );
+        assert_eq!(rules.assess([&fresh, &stale], now), Some(Health::Critical));
+    }
+
+    #[test]
+    fn load_should_read_input_feedback() {
+        assert_eq!(
+            load_from(r#"{ "invalid_input_feedback": "bell" }"#)
+                .unwrap()
+                .invalid_input_feedback,
+            InputFeedback::Bell
+        );
+        assert!(load_from(r#"{ "invalid_input_feedback": "beep" }"#).is_err());
+    }
+
+    #[test]
+    fn load_should_reject_invalid_keymaps() {
+        assert!(load_from(r#"{ "keymap": { "quit": "c" } }"#).is_err());
+        assert!(load_from(r#"{ "keymap": { "quit": "" } }"#).is_err());
+        assert!(load_from(r#"{ "keymap": { "quit": "1" } }"#).is_err());
+        assert!(load_from(r#"{ "keymap": { "quit": "j" } }"#).is_err());
+        assert!(load_from(r#"{ "keymap": { "quit": ">" } }"#).is_err());
+        assert!(load_from(r#"{ "keymap": { "density": "<" } }"#).is_err());
+        assert!(load_from(r#"{ "macros": { ">": [] } }"#).is_err());
+        assert!(load_from(r#"{ "keymap": "q" }"#).is_err());
+    }
+}
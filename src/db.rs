@@ -1,9 +1,19 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Display;
 use std::fs::{self, OpenOptions};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use itertools::Itertools;
 
-use crate::models::{DBState, Epic, Status, Story};
+use crate::config::SnapshotPolicy;
+use crate::models::{
+    Activity, ActivityEvent, Blocker, DBState, Epic, EpicId, IdScheme, Note, RecurringStory,
+    Retrospective, Sprint, Status, Story, StoryId,
+};
 
 pub struct JiraDatabase {
     pub(crate) database: Box<dyn Database>,
@@ -17,7 +27,12 @@ impl JiraDatabase {
         };
 
         if !Path::new(file_path).exists() {
-            match OpenOptions::new().create(true).write(true).open(file_path) {
+            match OpenOptions::new()
+                .create(true)
+                .truncate(true)
+                .write(true)
+                .open(file_path)
+            {
                 Err(e) => return Err(anyhow!("failed to open/create database file: {e}")),
                 Ok(_) => {
                     db.database.write_db(&DBState::new())?;
@@ -29,36 +44,153 @@ impl JiraDatabase {
         Ok(db)
     }
 
+    /// Coalesces writes that happen within `delay` of the first unsaved one.
+    /// A zero delay keeps writing through on every change.
+    pub fn with_debounce(self, delay: Duration) -> Self {
+        if delay.is_zero() {
+            return self;
+        }
+
+        Self {
+            database: Box::new(DebouncedDatabase {
+                inner: self.database,
+                delay,
+                pending: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Records `user` as the author of every change saved from now on. Without
+    /// a user, changes stay unattributed.
+    pub fn with_user(self, user: Option<String>) -> Self {
+        match user {
+            Some(user) => Self {
+                database: Box::new(AttributedDatabase {
+                    inner: self.database,
+                    user,
+                    previous: Mutex::new(None),
+                }),
+            },
+            None => self,
+        }
+    }
+
+    /// Runs `f` against a copy of the board in memory and saves the result in
+    /// one write. Nothing is saved if `f` fails.
+    pub fn transaction<T>(&self, f: impl FnOnce(&JiraDatabase) -> Result<T>) -> Result<T> {
+        let copy = JiraDatabase {
+            database: Box::new(MemoryDatabase {
+                state: Mutex::new(self.read_db()?),
+            }),
+        };
+        let result = f(&copy)?;
+        self.database.write_db(&copy.read_db()?)?;
+        Ok(result)
+    }
+
     pub fn read_db(&self) -> Result<DBState> {
         self.database.read_db()
     }
 
-    pub fn create_epic(&self, epic: Epic) -> Result<u32> {
+    pub fn flush(&self) -> Result<()> {
+        self.database.flush()
+    }
+
+    /// Writes the pending changes of `db` when the process exits on a signal,
+    /// which skips dropping it.
+    pub fn flush_on_exit(db: &Arc<Self>) {
+        let db = Arc::downgrade(db);
+        crate::signals::on_exit(move || {
+            if let Some(Err(e)) = db.upgrade().map(|db| db.flush()) {
+                eprintln!("failed to save changes: {e}");
+            }
+        });
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.database.has_unsaved_changes()
+    }
+
+    pub fn location(&self) -> Option<&str> {
+        self.database.location()
+    }
+
+    pub fn create_epic(&self, epic: Epic) -> Result<EpicId> {
         let mut db = self.read_db()?;
 
-        let id = db.last_item_id + 1;
-        db.last_item_id = id;
+        db.last_item_id += 1;
+        let id = EpicId(db.last_item_id);
+        db.activity.push(Activity::new(
+            Utc::now(),
+            id,
+            None,
+            &epic.name,
+            ActivityEvent::Created,
+        ));
         db.epics.insert(id, epic);
         self.database.write_db(&db)?;
         Ok(id)
     }
 
-    pub fn create_story(&self, story: Story, epic_id: u32) -> Result<u32> {
+    pub fn create_story(&self, mut story: Story, epic_id: EpicId) -> Result<StoryId> {
         let mut db = self.read_db()?;
 
-        let id = db.last_item_id + 1;
-        db.last_item_id = id;
-        db.stories.insert(id, story);
-        db.epics
+        db.last_item_id += 1;
+        let id = StoryId(db.last_item_id);
+        let epic = db
+            .epics
             .get_mut(&epic_id)
-            .ok_or_else(|| anyhow!(format!("epic not found: {epic_id}")))?
-            .stories
-            .push(id);
+            .ok_or_else(|| anyhow!(format!("epic not found: {epic_id}")))?;
+        epic.last_story_number += 1;
+        story.number = epic.last_story_number;
+        db.activity.push(Activity::new(
+            Utc::now(),
+            epic_id,
+            Some(id),
+            &story.name,
+            ActivityEvent::Created,
+        ));
+        db.stories.insert(id, story);
+        epic.stories.push(id);
+        epic.updated_at = Utc::now();
+        epic.version += 1;
         self.database.write_db(&db)?;
         Ok(id)
     }
 
-    pub fn delete_epic(&self, epic_id: u32) -> Result<()> {
+    pub fn create_stories(&self, stories: Vec<Story>, epic_id: EpicId) -> Result<Vec<StoryId>> {
+        let mut db = self.read_db()?;
+
+        let epic = db
+            .epics
+            .get_mut(&epic_id)
+            .ok_or_else(|| anyhow!(format!("epic not found: {epic_id}")))?;
+
+        let mut ids = vec![];
+        for mut story in stories {
+            db.last_item_id += 1;
+            let id = StoryId(db.last_item_id);
+            epic.last_story_number += 1;
+            story.number = epic.last_story_number;
+            db.activity.push(Activity::new(
+                Utc::now(),
+                epic_id,
+                Some(id),
+                &story.name,
+                ActivityEvent::Created,
+            ));
+            db.stories.insert(id, story);
+            epic.stories.push(id);
+            ids.push(id);
+        }
+        epic.updated_at = Utc::now();
+        epic.version += 1;
+
+        self.database.write_db(&db)?;
+        Ok(ids)
+    }
+
+    pub fn delete_epic(&self, epic_id: EpicId) -> Result<()> {
         let mut db = self.read_db()?;
 
         let epic = db
@@ -66,14 +198,22 @@ impl JiraDatabase {
             .get(&epic_id)
             .ok_or_else(|| anyhow!(format!("epic not found: {epic_id}")))?;
         db.stories.retain(|k, _| !epic.stories.contains(k));
+        db.activity.push(Activity::new(
+            Utc::now(),
+            epic_id,
+            None,
+            &epic.name,
+            ActivityEvent::Deleted,
+        ));
 
         db.epics.retain(|k, _| k != &epic_id);
+        db.clear_stale_references();
 
         self.database.write_db(&db)?;
         Ok(())
     }
 
-    pub fn delete_story(&self, epic_id: u32, story_id: u32) -> Result<()> {
+    pub fn delete_story(&self, epic_id: EpicId, story_id: StoryId) -> Result<()> {
         let mut db = self.read_db()?;
 
         let epic = db
@@ -88,44 +228,1054 @@ impl JiraDatabase {
         }
 
         epic.stories.retain(|k| k != &story_id);
+        epic.updated_at = Utc::now();
+        epic.version += 1;
+
+        if let Some(story) = db.stories.remove(&story_id) {
+            db.activity.push(Activity::new(
+                Utc::now(),
+                epic_id,
+                Some(story_id),
+                &story.name,
+                ActivityEvent::Deleted,
+            ));
+        }
+        db.clear_stale_references();
 
-        db.stories.retain(|k, _| k != &story_id);
+        self.database.write_db(&db)?;
+        Ok(())
+    }
+
+    pub fn update_epic_status(&self, epic_id: EpicId, status: Status) -> Result<()> {
+        self.update_epic(
+            epic_id,
+            ItemChanges {
+                status: Some(status),
+                ..Default::default()
+            },
+        )
+    }
+
+    pub fn update_story_status(&self, story_id: StoryId, status: Status) -> Result<()> {
+        self.update_story(
+            story_id,
+            ItemChanges {
+                status: Some(status),
+                ..Default::default()
+            },
+        )
+    }
+
+    pub fn update_epic(&self, epic_id: EpicId, changes: ItemChanges) -> Result<()> {
+        let mut db = self.read_db()?;
+
+        let epic = db
+            .epics
+            .get_mut(&epic_id)
+            .ok_or_else(|| anyhow!(format!("epic not found: {epic_id}")))?;
+        changes.check_version(&format!("epic {epic_id}"), epic.version)?;
+
+        let now = Utc::now();
+        if let Some(status) = changes.status.filter(|s| *s != epic.status) {
+            db.activity.push(Activity::new(
+                now,
+                epic_id,
+                None,
+                &epic.name,
+                ActivityEvent::StatusChanged {
+                    from: epic.status.clone(),
+                    to: status.clone(),
+                },
+            ));
+            epic.status = status;
+            epic.status_changed_at = now;
+        }
+        if let Some(name) = changes.name {
+            epic.name = name;
+        }
+        if let Some(description) = changes.description {
+            epic.description = description;
+        }
+        epic.updated_at = now;
+        epic.version += 1;
+
+        self.database.write_db(&db)?;
+        Ok(())
+    }
+
+    pub fn update_story(&self, story_id: StoryId, changes: ItemChanges) -> Result<()> {
+        let mut db = self.read_db()?;
+
+        let epic_id = db.story_epic(story_id);
+        let story = db
+            .stories
+            .get_mut(&story_id)
+            .ok_or_else(|| anyhow!(format!("story not found: {story_id}")))?;
+        changes.check_version(&format!("story {story_id}"), story.version)?;
+
+        let now = Utc::now();
+        if let Some(status) = changes.status.filter(|s| *s != story.status) {
+            db.activity.push(Activity::new(
+                now,
+                epic_id,
+                Some(story_id),
+                &story.name,
+                ActivityEvent::StatusChanged {
+                    from: story.status.clone(),
+                    to: status.clone(),
+                },
+            ));
+            story.status = status;
+            story.status_changed_at = now;
+        }
+        if let Some(name) = changes.name {
+            story.name = name;
+        }
+        if let Some(description) = changes.description {
+            story.description = description;
+        }
+        story.updated_at = now;
+        story.version += 1;
 
         self.database.write_db(&db)?;
         Ok(())
     }
 
-    pub fn update_epic_status(&self, epic_id: u32, status: Status) -> Result<()> {
+    /// Pins or unpins an epic and returns whether it is pinned now.
+    pub fn toggle_epic_pin(&self, epic_id: EpicId) -> Result<bool> {
+        let mut db = self.read_db()?;
+
+        let epic = db
+            .epics
+            .get_mut(&epic_id)
+            .ok_or_else(|| anyhow!(format!("epic not found: {epic_id}")))?;
+        // pinning is a view preference, so updated_at is left alone
+        epic.pinned = !epic.pinned;
+        epic.version += 1;
+        let pinned = epic.pinned;
+
+        self.database.write_db(&db)?;
+        Ok(pinned)
+    }
+
+    /// Watches or unwatches a story and returns whether it is watched now.
+    pub fn toggle_story_watch(&self, story_id: StoryId) -> Result<bool> {
+        let mut db = self.read_db()?;
+
+        let story = db
+            .stories
+            .get_mut(&story_id)
+            .ok_or_else(|| anyhow!(format!("story not found: {story_id}")))?;
+        // watching is a view preference, so updated_at is left alone
+        story.watched = !story.watched;
+        story.version += 1;
+        let watched = story.watched;
+
+        self.database.write_db(&db)?;
+        Ok(watched)
+    }
+
+    /// Adds an epic of another database with its stories, activity and
+    /// recurring stories under new ids, and returns the new epic id. Without
+    /// `source_epic_id` the source must contain exactly one epic.
+    pub fn import_epic(
+        &self,
+        source: &DBState,
+        source_epic_id: Option<EpicId>,
+        name: Option<String>,
+    ) -> Result<EpicId> {
+        let source_epic_id = match source_epic_id {
+            Some(epic_id) => epic_id,
+            None if source.epics.len() == 1 => *source.epics.keys().next().expect("one epic"),
+            None => {
+                return Err(anyhow!(format!(
+                    "the file contains {} epics, choose one with --epic",
+                    source.epics.len()
+                )))
+            }
+        };
+        let epic = source
+            .epics
+            .get(&source_epic_id)
+            .ok_or_else(|| anyhow!(format!("epic not found: {source_epic_id}")))?;
+
+        let mut db = self.read_db()?;
+        let mut ids = HashMap::new();
+        let mut remap = |id: u32| {
+            *ids.entry(id).or_insert_with(|| {
+                db.last_item_id += 1;
+                db.last_item_id
+            })
+        };
+
+        let epic_id = EpicId(remap(source_epic_id.0));
+        let mut new_epic = epic.clone();
+        if let Some(name) = name {
+            new_epic.name = name;
+        }
+        new_epic.pinned = false;
+        new_epic.stories = vec![];
+        let mut stories = vec![];
+        for story_id in &epic.stories {
+            if let Some(story) = source.stories.get(story_id) {
+                let id = StoryId(remap(story_id.0));
+                new_epic.stories.push(id);
+                stories.push((id, story.clone()));
+            }
+        }
+        let activity = source
+            .activity
+            .iter()
+            .filter(|a| a.epic_id == Some(source_epic_id))
+            .map(|a| {
+                let mut activity = a.clone();
+                activity.epic_id = Some(epic_id);
+                activity.story_id = activity.story_id.map(|id| StoryId(remap(id.0)));
+                activity
+            })
+            .collect::<Vec<_>>();
+        for (_, story) in stories.iter_mut() {
+            story.blocker = story.blocker.as_ref().map(|b| b.remapped(&ids));
+        }
+
+        db.stories.extend(stories);
+        db.epics.insert(epic_id, new_epic);
+        db.activity.extend(activity);
+        db.activity.sort_by_key(|a| a.at);
+        db.recurring_stories.extend(
+            source
+                .recurring_stories
+                .iter()
+                .filter(|r| r.epic_id == source_epic_id)
+                .map(|r| RecurringStory {
+                    epic_id,
+                    ..r.clone()
+                }),
+        );
+
+        self.database.write_db(&db)?;
+        Ok(epic_id)
+    }
+
+    /// Appends a dated entry to the notes of an epic.
+    pub fn add_epic_note(&self, epic_id: EpicId, text: String) -> Result<()> {
         let mut db = self.read_db()?;
 
-        db.epics
+        let epic = db
+            .epics
             .get_mut(&epic_id)
-            .ok_or_else(|| anyhow!(format!("epic not found: {epic_id}")))?
-            .status = status;
+            .ok_or_else(|| anyhow!(format!("epic not found: {epic_id}")))?;
+        let now = Utc::now();
+        epic.notes.push(Note { at: now, text });
+        epic.updated_at = now;
+        epic.version += 1;
+
+        self.database.write_db(&db)?;
+        Ok(())
+    }
+
+    /// Returns the configured inbox epic, or the epic named [`INBOX_EPIC_NAME`],
+    /// creating it if there is none.
+    pub fn inbox_epic(&self, configured: Option<EpicId>) -> Result<EpicId> {
+        let db = self.read_db()?;
+
+        match (db.inbox_epic(configured, INBOX_EPIC_NAME), configured) {
+            (Some(epic_id), _) => Ok(epic_id),
+            (None, Some(epic_id)) => Err(anyhow!(format!("inbox epic not found: {epic_id}"))),
+            (None, None) => self.create_epic(Epic::new(
+                INBOX_EPIC_NAME.to_owned(),
+                "Stories captured from the command line".to_owned(),
+            )),
+        }
+    }
+
+    /// Moves the stories of one epic, optionally only those with the given
+    /// status, to another epic in a single write and returns their ids.
+    pub fn move_stories(
+        &self,
+        from_epic_id: EpicId,
+        to_epic_id: EpicId,
+        status: Option<Status>,
+    ) -> Result<Vec<StoryId>> {
+        self.move_stories_where(from_epic_id, to_epic_id, |_, story| {
+            status.as_ref().is_none_or(|status| &story.status == status)
+        })
+    }
+
+    /// Marks a story as blocked by another story or for a reason, or clears
+    /// the blocker with `None`.
+    pub fn set_story_blocker(&self, story_id: StoryId, blocker: Option<Blocker>) -> Result<()> {
+        let mut db = self.read_db()?;
+
+        if let Some(Blocker::Story(blocking_id)) = blocker {
+            if blocking_id == story_id {
+                return Err(anyhow!("a story can't block itself"));
+            }
+            if !db.stories.contains_key(&blocking_id) {
+                return Err(anyhow!(format!("story not found: {blocking_id}")));
+            }
+        }
+        let story = db
+            .stories
+            .get_mut(&story_id)
+            .ok_or_else(|| anyhow!(format!("story not found: {story_id}")))?;
+        story.blocker = blocker;
+        story.updated_at = Utc::now();
+        story.version += 1;
+
+        self.database.write_db(&db)?;
+        Ok(())
+    }
+
+    pub fn set_story_points(&self, story_id: StoryId, points: Option<u32>) -> Result<()> {
+        let mut db = self.read_db()?;
+
+        let story = db
+            .stories
+            .get_mut(&story_id)
+            .ok_or_else(|| anyhow!(format!("story not found: {story_id}")))?;
+        story.points = points;
+        story.updated_at = Utc::now();
+        story.version += 1;
 
         self.database.write_db(&db)?;
         Ok(())
     }
 
-    pub fn update_story_status(&self, story_id: u32, status: Status) -> Result<()> {
+    /// Checks or unchecks an acceptance criterion of a story and returns whether
+    /// it is checked now.
+    pub fn toggle_story_criterion(&self, story_id: StoryId, index: usize) -> Result<bool> {
+        let mut db = self.read_db()?;
+
+        let story = db
+            .stories
+            .get_mut(&story_id)
+            .ok_or_else(|| anyhow!(format!("story not found: {story_id}")))?;
+        let done = story.toggle_checklist_item(index).ok_or_else(|| {
+            anyhow!(format!(
+                "story {story_id} has no acceptance criterion {}",
+                index + 1
+            ))
+        })?;
+        story.updated_at = Utc::now();
+        story.version += 1;
+
+        self.database.write_db(&db)?;
+        Ok(done)
+    }
+
+    /// Sets or, with `None`, removes the value of a custom field of a story.
+    pub fn set_story_field(
+        &self,
+        story_id: StoryId,
+        name: &str,
+        value: Option<String>,
+    ) -> Result<()> {
         let mut db = self.read_db()?;
 
-        db.stories
+        let story = db
+            .stories
             .get_mut(&story_id)
-            .ok_or_else(|| anyhow!(format!("story not found: {story_id}")))?
-            .status = status;
+            .ok_or_else(|| anyhow!(format!("story not found: {story_id}")))?;
+        match value {
+            Some(value) => story.fields.insert(name.to_owned(), value),
+            None => story.fields.remove(name),
+        };
+        story.updated_at = Utc::now();
+        story.version += 1;
+
+        self.database.write_db(&db)?;
+        Ok(())
+    }
+
+    /// Starts a new sprint, the previous one has to be closed.
+    pub fn start_sprint(&self, sprint: Sprint) -> Result<usize> {
+        let mut db = self.read_db()?;
+
+        if let Some(index) = db.current_sprint() {
+            return Err(anyhow!(format!(
+                "sprint '{}' is still open",
+                db.sprints[index].name
+            )));
+        }
+        db.sprints.push(sprint);
+
+        self.database.write_db(&db)?;
+        Ok(db.sprints.len() - 1)
+    }
+
+    /// Closes the open sprint and stores its retrospective.
+    pub fn close_sprint(&self, retrospective: Retrospective) -> Result<()> {
+        let mut db = self.read_db()?;
+
+        let index = db
+            .current_sprint()
+            .ok_or_else(|| anyhow!("no open sprint"))?;
+        let sprint = &mut db.sprints[index];
+        sprint.closed = true;
+        sprint.retrospective = retrospective;
+
+        self.database.write_db(&db)?;
+        Ok(())
+    }
+
+    /// Adds a story to the open sprint or takes it out again. Returns whether
+    /// the story is in the sprint afterwards.
+    pub fn toggle_sprint_story(&self, story_id: StoryId) -> Result<bool> {
+        let mut db = self.read_db()?;
+
+        if !db.stories.contains_key(&story_id) {
+            return Err(anyhow!(format!("story not found: {story_id}")));
+        }
+        let index = db
+            .current_sprint()
+            .ok_or_else(|| anyhow!("no open sprint, start one with `start-sprint`"))?;
+        let sprint = &mut db.sprints[index];
+        let planned = !sprint.stories.contains(&story_id);
+        if planned {
+            sprint.stories.push(story_id);
+        } else {
+            sprint.stories.retain(|id| *id != story_id);
+        }
+
+        self.database.write_db(&db)?;
+        Ok(planned)
+    }
+
+    /// Merges a duplicate into a story: appends its description, takes over
+    /// its blocker if the story has none, points stories blocked by the
+    /// duplicate to the story and deletes the duplicate.
+    pub fn merge_stories(&self, story_id: StoryId, duplicate_id: StoryId) -> Result<()> {
+        let mut db = self.read_db()?;
+
+        if story_id == duplicate_id {
+            return Err(anyhow!("a story can't be merged into itself"));
+        }
+        if !db.stories.contains_key(&story_id) {
+            return Err(anyhow!(format!("story not found: {story_id}")));
+        }
+        let duplicate_epic_id = db
+            .story_epic(duplicate_id)
+            .ok_or_else(|| anyhow!(format!("story not found: {duplicate_id}")))?;
+        let duplicate = db
+            .stories
+            .remove(&duplicate_id)
+            .ok_or_else(|| anyhow!(format!("story not found: {duplicate_id}")))?;
+
+        let now = Utc::now();
+        let epic = db
+            .epics
+            .get_mut(&duplicate_epic_id)
+            .ok_or_else(|| anyhow!(format!("epic not found: {duplicate_epic_id}")))?;
+        epic.stories.retain(|id| *id != duplicate_id);
+        epic.updated_at = now;
+        epic.version += 1;
+
+        for (id, story) in db.stories.iter_mut() {
+            if story.blocker == Some(Blocker::Story(duplicate_id)) {
+                story.blocker = (*id != story_id).then_some(Blocker::Story(story_id));
+            }
+        }
+
+        let story = db
+            .stories
+            .get_mut(&story_id)
+            .ok_or_else(|| anyhow!(format!("story not found: {story_id}")))?;
+        let description = duplicate.description.trim();
+        if !description.is_empty() && !story.description.contains(description) {
+            let merged = [story.description.trim(), description]
+                .into_iter()
+                .filter(|d| !d.is_empty())
+                .join("\n\n");
+            story.description = merged;
+        }
+        story.points = story.points.or(duplicate.points);
+        for (name, value) in duplicate.fields {
+            story.fields.entry(name).or_insert(value);
+        }
+        if story.blocker.is_none() {
+            story.blocker = duplicate
+                .blocker
+                .filter(|blocker| *blocker != Blocker::Story(story_id));
+        }
+        story.updated_at = now;
+        story.version += 1;
+
+        db.activity.push(Activity::new(
+            now,
+            duplicate_epic_id,
+            Some(duplicate_id),
+            &duplicate.name,
+            ActivityEvent::Deleted,
+        ));
+        db.clear_stale_references();
+
+        self.database.write_db(&db)?;
+        Ok(())
+    }
+
+    /// Moves a single story to another epic.
+    pub fn move_story(&self, story_id: StoryId, to_epic_id: EpicId) -> Result<()> {
+        let from_epic_id = self
+            .read_db()?
+            .story_epic(story_id)
+            .ok_or_else(|| anyhow!(format!("story not found: {story_id}")))?;
+        self.move_stories_where(from_epic_id, to_epic_id, |id, _| id == story_id)?;
+        Ok(())
+    }
+
+    fn move_stories_where(
+        &self,
+        from_epic_id: EpicId,
+        to_epic_id: EpicId,
+        predicate: impl Fn(StoryId, &Story) -> bool,
+    ) -> Result<Vec<StoryId>> {
+        let mut db = self.read_db()?;
+
+        if from_epic_id == to_epic_id {
+            return Err(anyhow!(format!(
+                "cannot move stories of epic {from_epic_id} to itself"
+            )));
+        }
+        if !db.epics.contains_key(&to_epic_id) {
+            return Err(anyhow!(format!("epic not found: {to_epic_id}")));
+        }
+        let from = db
+            .epics
+            .get_mut(&from_epic_id)
+            .ok_or_else(|| anyhow!(format!("epic not found: {from_epic_id}")))?;
+
+        let (moved, kept): (Vec<StoryId>, Vec<StoryId>) = from
+            .stories
+            .iter()
+            .partition(|id| db.stories.get(id).is_some_and(|s| predicate(**id, s)));
+        if moved.is_empty() {
+            return Ok(moved);
+        }
+
+        let now = Utc::now();
+        from.stories = kept;
+        from.updated_at = now;
+        from.version += 1;
+
+        let to = db.epics.get_mut(&to_epic_id).expect("checked above");
+        for id in &moved {
+            if let Some(story) = db.stories.get_mut(id) {
+                // keep per-epic numbers unique within the new epic
+                if story.number > 0 {
+                    to.last_story_number += 1;
+                    story.number = to.last_story_number;
+                }
+                story.updated_at = now;
+                story.version += 1;
+            }
+        }
+        to.stories.extend(&moved);
+        to.updated_at = now;
+        to.version += 1;
+
+        self.database.write_db(&db)?;
+        Ok(moved)
+    }
+
+    pub fn add_recurring_story(&self, recurring_story: RecurringStory) -> Result<()> {
+        let mut db = self.read_db()?;
+
+        if !db.epics.contains_key(&recurring_story.epic_id) {
+            return Err(anyhow!(format!(
+                "epic not found: {}",
+                recurring_story.epic_id
+            )));
+        }
+        if recurring_story.interval_days == 0 {
+            return Err(anyhow!("interval must be at least one day"));
+        }
+
+        db.recurring_stories.push(recurring_story);
+        self.database.write_db(&db)?;
+        Ok(())
+    }
+
+    /// Copies the database file into the snapshot directory if the latest
+    /// snapshot is older than the policy's interval, then removes the oldest
+    /// snapshots beyond the number kept. Returns the path of a new snapshot.
+    pub fn take_due_snapshot(
+        &self,
+        policy: &SnapshotPolicy,
+        now: DateTime<Utc>,
+    ) -> Result<Option<PathBuf>> {
+        let (Some(days), Some(db_path)) = (policy.every.days(), self.location()) else {
+            return Ok(None);
+        };
+
+        let today = now.date_naive();
+        let mut snapshots = list_snapshots(db_path)?;
+        let mut taken = None;
+        if snapshots
+            .last()
+            .is_none_or(|(date, _)| (today - *date).num_days() >= days)
+        {
+            let path = snapshot_dir(db_path).join(format!("{}-{today}.json", file_stem(db_path)));
+            fs::create_dir_all(snapshot_dir(db_path))
+                .and_then(|_| fs::copy(db_path, &path))
+                .with_context(|| format!("failed to take snapshot: {}", path.display()))?;
+            snapshots.push((today, path.clone()));
+            taken = Some(path);
+        }
+
+        if policy.keep > 0 {
+            for (_, path) in &snapshots[..snapshots.len().saturating_sub(policy.keep)] {
+                fs::remove_file(path)
+                    .with_context(|| format!("failed to remove snapshot: {}", path.display()))?;
+            }
+        }
+        Ok(taken)
+    }
+
+    /// Creates an open story for every recurring story that is due and returns
+    /// the new story ids. Recurring stories of deleted epics are skipped.
+    pub fn create_due_recurring_stories(&self, now: DateTime<Utc>) -> Result<Vec<StoryId>> {
+        let mut db = self.read_db()?;
+        let mut story_ids = vec![];
+
+        for recurring_story in db.recurring_stories.iter_mut() {
+            if !recurring_story.is_due(now) {
+                continue;
+            }
+            let Some(epic) = db.epics.get_mut(&recurring_story.epic_id) else {
+                continue;
+            };
+
+            db.last_item_id += 1;
+            let id = StoryId(db.last_item_id);
+            let mut story = Story::new(
+                recurring_story.name.clone(),
+                recurring_story.description.clone(),
+            );
+            epic.last_story_number += 1;
+            story.number = epic.last_story_number;
+            db.activity.push(Activity::new(
+                now,
+                recurring_story.epic_id,
+                Some(id),
+                &story.name,
+                ActivityEvent::Created,
+            ));
+            db.stories.insert(id, story);
+            epic.stories.push(id);
+            epic.updated_at = now;
+            epic.version += 1;
+            recurring_story.last_created_at = Some(now);
+            story_ids.push(id);
+        }
+
+        if !story_ids.is_empty() {
+            self.database.write_db(&db)?;
+        }
+        Ok(story_ids)
+    }
+
+    pub fn set_id_scheme(&self, id_scheme: IdScheme) -> Result<()> {
+        let mut db = self.read_db()?;
+
+        db.number_stories();
+        db.id_scheme = id_scheme;
 
         self.database.write_db(&db)?;
         Ok(())
     }
+
+    /// Renumbers all epics and stories to 1..=n in their current order and
+    /// returns the mapping from old to new ids. Activity of deleted items is
+    /// dropped, as their ids are handed out again.
+    pub fn compact_ids(&self) -> Result<BTreeMap<u32, u32>> {
+        let mut db = self.read_db()?;
+
+        let mapping: BTreeMap<u32, u32> = db
+            .epics
+            .keys()
+            .map(|id| id.0)
+            .chain(db.stories.keys().map(|id| id.0))
+            .sorted()
+            .zip(1..)
+            .collect();
+
+        db.epics = db
+            .epics
+            .into_iter()
+            .map(|(id, mut epic)| {
+                epic.stories = epic
+                    .stories
+                    .iter()
+                    .filter_map(|s| mapping.get(&s.0).copied().map(StoryId))
+                    .collect();
+                (EpicId(mapping[&id.0]), epic)
+            })
+            .collect();
+        let story_ids: HashMap<u32, u32> =
+            db.stories.keys().map(|id| (id.0, mapping[&id.0])).collect();
+        db.stories = db
+            .stories
+            .into_iter()
+            .map(|(id, mut story)| {
+                story.blocker = story.blocker.map(|b| b.remapped(&story_ids));
+                (StoryId(story_ids[&id.0]), story)
+            })
+            .collect();
+        for sprint in db.sprints.iter_mut() {
+            sprint.stories = sprint
+                .stories
+                .iter()
+                .filter_map(|s| story_ids.get(&s.0).copied().map(StoryId))
+                .collect();
+        }
+        db.activity.retain_mut(|activity| {
+            if let Some(epic_id) = activity.epic_id {
+                let Some(epic_id) = mapping.get(&epic_id.0).copied() else {
+                    return false;
+                };
+                activity.epic_id = Some(EpicId(epic_id));
+            }
+            match activity.story_id {
+                Some(story_id) => match story_ids.get(&story_id.0) {
+                    Some(id) => {
+                        activity.story_id = Some(StoryId(*id));
+                        true
+                    }
+                    None => false,
+                },
+                None => true,
+            }
+        });
+        // recurring stories of deleted epics would point at a renumbered item
+        db.recurring_stories
+            .retain_mut(|r| match mapping.get(&r.epic_id.0).copied().map(EpicId) {
+                Some(epic_id) if db.epics.contains_key(&epic_id) => {
+                    r.epic_id = epic_id;
+                    true
+                }
+                _ => false,
+            });
+        db.last_item_id = mapping.len() as u32;
+        db.clear_stale_references();
+
+        self.database.write_db(&db)?;
+        Ok(mapping)
+    }
+}
+
+/// Field changes for an epic or story. With `expected_version` set, the update
+/// fails with a [`Conflict`] if the item was changed in the meantime.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ItemChanges {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub status: Option<Status>,
+    pub expected_version: Option<u32>,
+}
+
+impl ItemChanges {
+    fn check_version(&self, item: &str, actual: u32) -> Result<()> {
+        match self.expected_version {
+            Some(expected) if expected != actual => Err(Conflict {
+                item: item.to_owned(),
+                expected,
+                actual,
+            }
+            .into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub item: String,
+    pub expected: u32,
+    pub actual: u32,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "conflict: {} was changed in the meantime (expected version {}, found {})",
+            self.item, self.expected, self.actual
+        )
+    }
 }
 
-pub trait Database {
+impl std::error::Error for Conflict {}
+
+pub const INBOX_EPIC_NAME: &str = "Inbox";
+
+static EXCLUSIVE_ACCESS: Mutex<()> = Mutex::new(());
+
+/// Held while a series of reads and writes must not interleave with those of
+/// another thread, e.g. handling an action while the background sync pulls.
+pub fn exclusive_access() -> MutexGuard<'static, ()> {
+    EXCLUSIVE_ACCESS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Directory the snapshots of a database file are kept in, next to the file.
+pub fn snapshot_dir(db_path: &str) -> PathBuf {
+    Path::new(db_path).with_file_name("snapshots")
+}
+
+/// Returns the snapshots of a database file with their dates, oldest first.
+pub fn list_snapshots(db_path: &str) -> Result<Vec<(NaiveDate, PathBuf)>> {
+    let dir = snapshot_dir(db_path);
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let prefix = format!("{}-", file_stem(db_path));
+    let mut snapshots = fs::read_dir(&dir)
+        .with_context(|| format!("failed to read snapshots: {}", dir.display()))?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_name()?.to_str()?;
+            let date = name.strip_prefix(&prefix)?.strip_suffix(".json")?;
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+            Some((date, path))
+        })
+        .collect::<Vec<_>>();
+    snapshots.sort();
+    Ok(snapshots)
+}
+
+fn file_stem(db_path: &str) -> String {
+    Path::new(db_path)
+        .file_stem()
+        .map_or("db".into(), |stem| stem.to_string_lossy().into_owned())
+}
+
+pub fn read_db_file(file_path: &str) -> Result<DBState> {
+    JSONFileDatabase {
+        file_path: file_path.to_owned(),
+    }
+    .read_db()
+    .with_context(|| format!("failed to read database file: {file_path}"))
+}
+
+pub fn write_db_file(file_path: &str, db_state: &DBState) -> Result<()> {
+    JSONFileDatabase {
+        file_path: file_path.to_owned(),
+    }
+    .write_db(db_state)
+}
+
+pub struct Board {
+    pub name: String,
+    pub db: Arc<JiraDatabase>,
+}
+
+impl Board {
+    pub fn open(file_path: &str, debounce: Duration, user: Option<String>) -> Result<Self> {
+        let name = Path::new(file_path).file_stem().map_or_else(
+            || file_path.to_owned(),
+            |s| s.to_string_lossy().into_owned(),
+        );
+
+        let db = Arc::new(
+            JiraDatabase::new(file_path)?
+                .with_debounce(debounce)
+                .with_user(user),
+        );
+        JiraDatabase::flush_on_exit(&db);
+        Ok(Self { name, db })
+    }
+}
+
+/// Storage of the board. Implementations are shared across threads.
+pub trait Database: Send + Sync {
     fn read_db(&self) -> Result<DBState>;
     fn write_db(&self, db_state: &DBState) -> Result<()>;
+    fn has_unsaved_changes(&self) -> bool {
+        false
+    }
+    fn location(&self) -> Option<&str> {
+        None
+    }
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Holds the board in memory only, for changes that are saved all at once.
+struct MemoryDatabase {
+    state: Mutex<DBState>,
+}
+
+impl Database for MemoryDatabase {
+    fn read_db(&self) -> Result<DBState> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone())
+    }
+
+    fn write_db(&self, db_state: &DBState) -> Result<()> {
+        *self.state.lock().unwrap_or_else(PoisonError::into_inner) = db_state.clone();
+        Ok(())
+    }
+}
+
+/// Keeps the latest state in memory and only writes it to the inner database
+/// once `delay` has passed since the first unsaved change, or on flush.
+struct DebouncedDatabase {
+    inner: Box<dyn Database>,
+    delay: Duration,
+    pending: Mutex<Option<(DBState, Instant)>>,
+}
+
+impl DebouncedDatabase {
+    fn pending(&self) -> MutexGuard<'_, Option<(DBState, Instant)>> {
+        // the pending state is replaced as a whole, so it is intact even if a
+        // thread panicked while holding the lock
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Database for DebouncedDatabase {
+    fn read_db(&self) -> Result<DBState> {
+        match self.pending().as_ref() {
+            Some((db_state, _)) => Ok(db_state.clone()),
+            None => self.inner.read_db(),
+        }
+    }
+
+    fn write_db(&self, db_state: &DBState) -> Result<()> {
+        let since = {
+            let mut pending = self.pending();
+            let since = pending
+                .as_ref()
+                .map_or_else(Instant::now, |(_, since)| *since);
+            *pending = Some((db_state.clone(), since));
+            since
+        };
+
+        if since.elapsed() >= self.delay {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn has_unsaved_changes(&self) -> bool {
+        self.pending().is_some() || self.inner.has_unsaved_changes()
+    }
+
+    fn location(&self) -> Option<&str> {
+        self.inner.location()
+    }
+
+    fn flush(&self) -> Result<()> {
+        let pending = self.pending().take();
+        if let Some((db_state, _)) = pending {
+            self.inner.write_db(&db_state)?;
+        }
+        self.inner.flush()
+    }
+}
+
+impl Drop for DebouncedDatabase {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            eprintln!("failed to save database: {e}");
+        }
+    }
+}
+
+/// Stamps the items and activity changed by a write with the acting user.
+/// Changes that arrive with an author of their own, e.g. from a sync, keep it.
+struct AttributedDatabase {
+    inner: Box<dyn Database>,
+    user: String,
+    /// State of the last read or write, which a write is compared against.
+    previous: Mutex<Option<DBState>>,
+}
+
+impl AttributedDatabase {
+    fn stamp(&self, previous: &DBState, db_state: &mut DBState) {
+        for (id, epic) in db_state.epics.iter_mut() {
+            let before = previous.epics.get(id).map(|e| (e.version, &e.updated_by));
+            if changed_anonymously(epic.version, &epic.updated_by, before) {
+                epic.updated_by = Some(self.user.clone());
+            }
+        }
+        for (id, story) in db_state.stories.iter_mut() {
+            let before = previous.stories.get(id).map(|s| (s.version, &s.updated_by));
+            if changed_anonymously(story.version, &story.updated_by, before) {
+                story.updated_by = Some(self.user.clone());
+            }
+        }
+        // entries can be reordered or renumbered by a write, so they are told
+        // apart by what they record rather than their position or ids
+        let logged: BTreeSet<_> = previous
+            .activity
+            .iter()
+            .map(|a| (a.at, &a.event, &a.name))
+            .collect();
+        for activity in db_state.activity.iter_mut() {
+            if activity.by.is_none()
+                && !logged.contains(&(activity.at, &activity.event, &activity.name))
+            {
+                activity.by = Some(self.user.clone());
+            }
+        }
+    }
+}
+
+/// Whether a write changed an item without naming who made the change. The
+/// item's previous version and author are `before`, if it existed.
+fn changed_anonymously(
+    version: u32,
+    updated_by: &Option<String>,
+    before: Option<(u32, &Option<String>)>,
+) -> bool {
+    match before {
+        Some((before_version, before_by)) => version != before_version && updated_by == before_by,
+        None => updated_by.is_none(),
+    }
 }
 
-struct JSONFileDatabase {
+impl Database for AttributedDatabase {
+    fn read_db(&self) -> Result<DBState> {
+        let db_state = self.inner.read_db()?;
+        *self.previous.lock().unwrap() = Some(db_state.clone());
+        Ok(db_state)
+    }
+
+    fn write_db(&self, db_state: &DBState) -> Result<()> {
+        let mut db_state = db_state.clone();
+        let mut previous = self.previous.lock().unwrap();
+        let before = match previous.take() {
+            Some(before) => before,
+            None => self.inner.read_db()?,
+        };
+        self.stamp(&before, &mut db_state);
+        self.inner.write_db(&db_state)?;
+        *previous = Some(db_state);
+        Ok(())
+    }
+
+    fn has_unsaved_changes(&self) -> bool {
+        self.inner.has_unsaved_changes()
+    }
+
+    fn location(&self) -> Option<&str> {
+        self.inner.location()
+    }
+
+    fn flush(&self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub struct JSONFileDatabase {
     pub file_path: String,
 }
 
@@ -138,28 +1288,28 @@ impl Database for JSONFileDatabase {
 
     fn write_db(&self, db_state: &DBState) -> Result<()> {
         let state = serde_json::to_vec_pretty(&db_state)?;
+        let _guard = crate::signals::write_guard();
         Ok(fs::write(&self.file_path, state)?)
     }
+
+    fn location(&self) -> Option<&str> {
+        Some(&self.file_path)
+    }
 }
 
 pub mod test_utils {
-    use std::{cell::RefCell, collections::HashMap};
-
     use super::*;
 
+    #[derive(Default)]
     pub struct MockDB {
-        last_written_state: RefCell<DBState>,
+        last_written_state: Mutex<DBState>,
     }
 
     impl MockDB {
         #[allow(dead_code)]
         pub fn new() -> Self {
             Self {
-                last_written_state: RefCell::new(DBState {
-                    last_item_id: 0,
-                    epics: HashMap::new(),
-                    stories: HashMap::new(),
-                }),
+                last_written_state: Mutex::new(DBState::new()),
             }
         }
     }
@@ -167,14 +1317,14 @@ pub mod test_utils {
     impl Database for MockDB {
         fn read_db(&self) -> Result<DBState> {
             // TODO: fix this error by deriving the appropriate traits for Story
-            let state = self.last_written_state.borrow().clone();
+            let state = self.last_written_state.lock().unwrap().clone();
             Ok(state)
         }
 
         fn write_db(&self, db_state: &DBState) -> Result<()> {
             let latest_state = &self.last_written_state;
             // TODO: fix this error by deriving the appropriate traits for DBState
-            *latest_state.borrow_mut() = db_state.clone();
+            *latest_state.lock().unwrap() = db_state.clone();
             Ok(())
         }
     }
@@ -185,6 +1335,32 @@ mod tests {
     use super::test_utils::MockDB;
     use super::*;
 
+    #[test]
+    fn database_should_be_shareable_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<JiraDatabase>();
+        assert_send_sync::<Board>();
+        assert_send_sync::<crate::config::Config>();
+
+        let db = Arc::new(
+            JiraDatabase {
+                database: Box::new(MockDB::new()),
+            }
+            .with_debounce(Duration::from_secs(60)),
+        );
+        let writer = Arc::clone(&db);
+        std::thread::spawn(move || {
+            writer
+                .create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap()
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(db.read_db().unwrap().epics.len(), 1);
+        assert!(db.has_unsaved_changes());
+    }
+
     #[test]
     fn create_epic_should_work() {
         let db = JiraDatabase {
@@ -195,15 +1371,15 @@ mod tests {
         // TODO: fix this error by deriving the appropriate traits for Epic
         let result = db.create_epic(epic.clone());
 
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let id = result.unwrap();
         let db_state = db.read_db().unwrap();
 
-        let expected_id = 1;
+        let expected_id = EpicId(1);
 
         assert_eq!(id, expected_id);
-        assert_eq!(db_state.last_item_id, expected_id);
+        assert_eq!(db_state.last_item_id, expected_id.0);
         assert_eq!(db_state.epics.get(&id), Some(&epic));
     }
 
@@ -214,41 +1390,91 @@ mod tests {
         };
         let story = Story::new("".to_owned(), "".to_owned());
 
-        let non_existent_epic_id = 999;
+        let non_existent_epic_id = EpicId(999);
 
         let result = db.create_story(story, non_existent_epic_id);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn create_story_should_work() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic = Epic::new("".to_owned(), "".to_owned());
+        let story = Story::new("".to_owned(), "".to_owned());
+
+        let result = db.create_epic(epic);
+        assert!(result.is_ok());
+
+        let epic_id = result.unwrap();
+
+        // TODO: fix this error by deriving the appropriate traits for Story
+        let result = db.create_story(story.clone(), epic_id);
+        assert!(result.is_ok());
+
+        let id = result.unwrap();
+        let db_state = db.read_db().unwrap();
+
+        let expected_id = StoryId(2);
+
+        assert_eq!(id, expected_id);
+        assert_eq!(db_state.last_item_id, expected_id.0);
+        assert!(db_state.epics.get(&epic_id).unwrap().stories.contains(&id));
+        assert_eq!(
+            db_state.stories.get(&id),
+            Some(&Story { number: 1, ..story })
+        );
+    }
+
+    #[test]
+    fn create_stories_should_error_if_invalid_epic_id() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let stories = vec![Story::new("".to_owned(), "".to_owned())];
+
+        let result = db.create_stories(stories, EpicId(999));
+        assert!(result.is_err());
+        assert_eq!(db.read_db().unwrap().last_item_id, 0);
     }
 
     #[test]
-    fn create_story_should_work() {
+    fn create_stories_should_work() {
         let db = JiraDatabase {
             database: Box::new(MockDB::new()),
         };
-        let epic = Epic::new("".to_owned(), "".to_owned());
-        let story = Story::new("".to_owned(), "".to_owned());
-
-        let result = db.create_epic(epic);
-        assert_eq!(result.is_ok(), true);
-
-        let epic_id = result.unwrap();
-
-        // TODO: fix this error by deriving the appropriate traits for Story
-        let result = db.create_story(story.clone(), epic_id);
-        assert_eq!(result.is_ok(), true);
-
-        let id = result.unwrap();
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let stories = vec![
+            Story::new("one".to_owned(), "".to_owned()),
+            Story::new("two".to_owned(), "".to_owned()),
+        ];
+
+        let ids = db.create_stories(stories.clone(), epic_id).unwrap();
         let db_state = db.read_db().unwrap();
 
-        let expected_id = 2;
-
-        assert_eq!(id, expected_id);
-        assert_eq!(db_state.last_item_id, expected_id);
+        assert_eq!(ids, vec![StoryId(2), StoryId(3)]);
+        assert_eq!(db_state.last_item_id, 3);
+        assert_eq!(
+            db_state.epics.get(&epic_id).unwrap().stories,
+            vec![StoryId(2), StoryId(3)]
+        );
         assert_eq!(
-            db_state.epics.get(&epic_id).unwrap().stories.contains(&id),
-            true
+            db_state.stories.get(&StoryId(2)),
+            Some(&Story {
+                number: 1,
+                ..stories[0].clone()
+            })
+        );
+        assert_eq!(
+            db_state.stories.get(&StoryId(3)),
+            Some(&Story {
+                number: 2,
+                ..stories[1].clone()
+            })
         );
-        assert_eq!(db_state.stories.get(&id), Some(&story));
     }
 
     #[test]
@@ -257,10 +1483,10 @@ mod tests {
             database: Box::new(MockDB::new()),
         };
 
-        let non_existent_epic_id = 999;
+        let non_existent_epic_id = EpicId(999);
 
         let result = db.delete_epic(non_existent_epic_id);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
@@ -272,17 +1498,17 @@ mod tests {
         let story = Story::new("".to_owned(), "".to_owned());
 
         let result = db.create_epic(epic);
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let epic_id = result.unwrap();
 
         let result = db.create_story(story, epic_id);
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let story_id = result.unwrap();
 
         let result = db.delete_epic(epic_id);
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let db_state = db.read_db().unwrap();
 
@@ -302,19 +1528,19 @@ mod tests {
         let story = Story::new("".to_owned(), "".to_owned());
 
         let result = db.create_epic(epic);
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let epic_id = result.unwrap();
 
         let result = db.create_story(story, epic_id);
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let story_id = result.unwrap();
 
-        let non_existent_epic_id = 999;
+        let non_existent_epic_id = EpicId(999);
 
         let result = db.delete_story(non_existent_epic_id, story_id);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
@@ -326,17 +1552,17 @@ mod tests {
         let story = Story::new("".to_owned(), "".to_owned());
 
         let result = db.create_epic(epic);
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let epic_id = result.unwrap();
 
         let result = db.create_story(story, epic_id);
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
-        let non_existent_story_id = 999;
+        let non_existent_story_id = StoryId(999);
 
         let result = db.delete_story(epic_id, non_existent_story_id);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
@@ -348,33 +1574,346 @@ mod tests {
         let story = Story::new("".to_owned(), "".to_owned());
 
         let result = db.create_epic(epic);
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let epic_id = result.unwrap();
 
         let result = db.create_story(story, epic_id);
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let story_id = result.unwrap();
 
         let result = db.delete_story(epic_id, story_id);
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let db_state = db.read_db().unwrap();
 
         let expected_last_id = 2;
 
         assert_eq!(db_state.last_item_id, expected_last_id);
+        assert!(!db_state
+            .epics
+            .get(&epic_id)
+            .unwrap()
+            .stories
+            .contains(&story_id));
+        assert_eq!(db_state.stories.get(&story_id), None);
+    }
+
+    #[test]
+    fn toggle_epic_pin_should_flip_pinned_flag() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let updated_at = db.read_db().unwrap().epics[&epic_id].updated_at;
+
+        assert!(db.toggle_epic_pin(epic_id).unwrap());
+        assert!(db.read_db().unwrap().epics[&epic_id].pinned);
+        assert!(!db.toggle_epic_pin(epic_id).unwrap());
+        assert_eq!(db.read_db().unwrap().epics[&epic_id].updated_at, updated_at);
+        assert!(db.toggle_epic_pin(EpicId(999)).is_err());
+    }
+
+    #[test]
+    fn toggle_story_criterion_should_check_the_box_in_the_description() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(
+                Story::new("".to_owned(), "[ ] login [ ] logout".to_owned()),
+                epic_id,
+            )
+            .unwrap();
+
+        assert!(db.toggle_story_criterion(story_id, 1).unwrap());
+        let story = &db.read_db().unwrap().stories[&story_id];
+        assert_eq!(story.description, "[ ] login [x] logout");
+        assert_eq!(story.version, 2);
+        assert!(db.toggle_story_criterion(story_id, 2).is_err());
+    }
+
+    #[test]
+    fn toggle_story_watch_should_flip_watched_flag() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        assert!(db.toggle_story_watch(story_id).unwrap());
+        assert!(db.read_db().unwrap().stories[&story_id].watched);
+        assert!(!db.toggle_story_watch(story_id).unwrap());
+        assert!(db.toggle_story_watch(StoryId(999)).is_err());
+    }
+
+    #[test]
+    fn import_epic_should_add_the_epic_under_new_ids() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let existing = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let source = {
+            let source = JiraDatabase {
+                database: Box::new(MockDB::new()),
+            };
+            let epic_id = source
+                .create_epic(Epic::new("imported".to_owned(), "".to_owned()))
+                .unwrap();
+            source
+                .create_story(Story::new("story".to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+            source.read_db().unwrap()
+        };
+
+        let epic_id = db
+            .import_epic(&source, None, Some("renamed".to_owned()))
+            .unwrap();
+        assert_ne!(epic_id, existing);
+
+        let db_state = db.read_db().unwrap();
+        let epic = &db_state.epics[&epic_id];
+        assert_eq!(epic.name, "renamed");
+        assert_eq!(db_state.stories[&epic.stories[0]].name, "story");
+        assert_eq!(db_state.last_item_id, 3);
         assert_eq!(
             db_state
-                .epics
-                .get(&epic_id)
-                .unwrap()
-                .stories
-                .contains(&story_id),
-            false
+                .activity
+                .iter()
+                .filter(|a| a.epic_id == Some(epic_id))
+                .count(),
+            2
         );
-        assert_eq!(db_state.stories.get(&story_id), None);
+        assert!(db.import_epic(&source, Some(EpicId(999)), None).is_err());
+    }
+
+    #[test]
+    fn set_story_blocker_should_check_the_blocking_story() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let other_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        db.set_story_blocker(story_id, Some(Blocker::Story(other_id)))
+            .unwrap();
+        let story = &db.read_db().unwrap().stories[&story_id];
+        assert_eq!(story.blocker, Some(Blocker::Story(other_id)));
+        assert_eq!(story.version, 2);
+
+        assert!(db
+            .set_story_blocker(story_id, Some(Blocker::Story(story_id)))
+            .is_err());
+        assert!(db
+            .set_story_blocker(story_id, Some(Blocker::Story(StoryId(999))))
+            .is_err());
+        assert!(db.set_story_blocker(StoryId(999), None).is_err());
+
+        db.set_story_blocker(story_id, None).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].blocker, None);
+
+        db.set_story_blocker(story_id, Some(Blocker::Story(other_id)))
+            .unwrap();
+        db.delete_story(epic_id, other_id).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].blocker, None);
+    }
+
+    #[test]
+    fn merge_stories_should_combine_and_delete_the_duplicate() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let other_epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(
+                Story::new("Login".to_owned(), "Use SSO".to_owned()),
+                epic_id,
+            )
+            .unwrap();
+        let duplicate_id = db
+            .create_story(
+                Story::new("Sign in".to_owned(), "Remember the user".to_owned()),
+                other_epic_id,
+            )
+            .unwrap();
+        let blocked_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.set_story_blocker(blocked_id, Some(Blocker::Story(duplicate_id)))
+            .unwrap();
+        db.set_story_blocker(duplicate_id, Some(Blocker::Reason("waiting".to_owned())))
+            .unwrap();
+
+        assert!(db.merge_stories(story_id, story_id).is_err());
+        assert!(db.merge_stories(story_id, StoryId(999)).is_err());
+        assert!(db.merge_stories(StoryId(999), duplicate_id).is_err());
+
+        db.merge_stories(story_id, duplicate_id).unwrap();
+        let db_state = db.read_db().unwrap();
+        assert!(!db_state.stories.contains_key(&duplicate_id));
+        assert!(db_state.epics[&other_epic_id].stories.is_empty());
+
+        let story = &db_state.stories[&story_id];
+        assert_eq!(story.description, "Use SSO\n\nRemember the user");
+        assert_eq!(story.blocker, Some(Blocker::Reason("waiting".to_owned())));
+        assert_eq!(
+            db_state.stories[&blocked_id].blocker,
+            Some(Blocker::Story(story_id))
+        );
+        assert_eq!(
+            db_state.activity.last().unwrap().event,
+            ActivityEvent::Deleted
+        );
+    }
+
+    #[test]
+    fn sprints_should_track_planned_stories() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.set_story_points(story_id, Some(5)).unwrap();
+
+        assert!(db.toggle_sprint_story(story_id).is_err());
+        assert!(db.close_sprint(Retrospective::default()).is_err());
+        assert_eq!(
+            db.start_sprint(Sprint::new("Sprint 1".to_owned(), 3))
+                .unwrap(),
+            0
+        );
+        assert!(db
+            .start_sprint(Sprint::new("Sprint 2".to_owned(), 3))
+            .is_err());
+        assert!(db.toggle_sprint_story(StoryId(999)).is_err());
+
+        assert!(db.toggle_sprint_story(story_id).unwrap());
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.current_sprint(), Some(0));
+        assert_eq!(db_state.sprint_points(&db_state.sprints[0]), 5);
+
+        assert!(!db.toggle_sprint_story(story_id).unwrap());
+        assert!(db.toggle_sprint_story(story_id).unwrap());
+        db.delete_story(epic_id, story_id).unwrap();
+        assert!(db.read_db().unwrap().sprints[0].stories.is_empty());
+
+        db.close_sprint(Retrospective::default()).unwrap();
+        assert_eq!(db.read_db().unwrap().current_sprint(), None);
+        assert_eq!(
+            db.start_sprint(Sprint::new("Sprint 2".to_owned(), 3))
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn add_epic_note_should_append_entries() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+
+        db.add_epic_note(epic_id, "first".to_owned()).unwrap();
+        db.add_epic_note(epic_id, "second\nline".to_owned())
+            .unwrap();
+
+        let epic = &db.read_db().unwrap().epics[&epic_id];
+        assert_eq!(
+            epic.notes
+                .iter()
+                .map(|n| n.text.as_str())
+                .collect::<Vec<_>>(),
+            vec!["first", "second\nline"]
+        );
+        assert_eq!(epic.version, 3);
+        assert!(db.add_epic_note(EpicId(999), "".to_owned()).is_err());
+    }
+
+    #[test]
+    fn inbox_epic_should_reuse_or_create_the_inbox() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let other = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+
+        let inbox = db.inbox_epic(None).unwrap();
+        assert_ne!(inbox, other);
+        assert_eq!(db.read_db().unwrap().epics[&inbox].name, INBOX_EPIC_NAME);
+        assert_eq!(db.inbox_epic(None).unwrap(), inbox);
+        assert_eq!(db.inbox_epic(Some(other)).unwrap(), other);
+        assert!(db.inbox_epic(Some(EpicId(999))).is_err());
+    }
+
+    #[test]
+    fn move_stories_should_move_matching_stories() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let from = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let to = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let open = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), from)
+            .unwrap();
+        let resolved = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), from)
+            .unwrap();
+        db.create_story(Story::new("".to_owned(), "".to_owned()), to)
+            .unwrap();
+        db.update_story_status(resolved, Status::Resolved).unwrap();
+
+        assert_eq!(
+            db.move_stories(from, to, Some(Status::Resolved)).unwrap(),
+            vec![resolved]
+        );
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics[&from].stories, vec![open]);
+        assert_eq!(db_state.epics[&to].stories.len(), 2);
+        assert_eq!(db_state.stories[&resolved].number, 2);
+
+        assert_eq!(db.move_stories(from, to, None).unwrap(), vec![open]);
+        assert!(db.read_db().unwrap().epics[&from].stories.is_empty());
+        assert!(db.move_stories(from, from, None).is_err());
+
+        db.move_story(open, from).unwrap();
+        assert_eq!(db.read_db().unwrap().epics[&from].stories, vec![open]);
+        assert!(db.move_story(StoryId(999), from).is_err());
+        assert!(db.move_stories(from, EpicId(999), None).is_err());
     }
 
     #[test]
@@ -383,10 +1922,10 @@ mod tests {
             database: Box::new(MockDB::new()),
         };
 
-        let non_existent_epic_id = 999;
+        let non_existent_epic_id = EpicId(999);
 
         let result = db.update_epic_status(non_existent_epic_id, Status::Closed);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
@@ -398,13 +1937,13 @@ mod tests {
 
         let result = db.create_epic(epic);
 
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let epic_id = result.unwrap();
 
         let result = db.update_epic_status(epic_id, Status::Closed);
 
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let db_state = db.read_db().unwrap();
 
@@ -417,10 +1956,10 @@ mod tests {
             database: Box::new(MockDB::new()),
         };
 
-        let non_existent_story_id = 999;
+        let non_existent_story_id = StoryId(999);
 
         let result = db.update_story_status(non_existent_story_id, Status::Closed);
-        assert_eq!(result.is_err(), true);
+        assert!(result.is_err());
     }
 
     #[test]
@@ -441,7 +1980,7 @@ mod tests {
 
         let result = db.update_story_status(story_id, Status::Closed);
 
-        assert_eq!(result.is_ok(), true);
+        assert!(result.is_ok());
 
         let db_state = db.read_db().unwrap();
 
@@ -451,18 +1990,514 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_story_status_should_bump_epic_last_activity() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        let before = db.read_db().unwrap().epic_last_activity(&epic_id).unwrap();
+
+        db.update_story_status(story_id, Status::Closed).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        let story = db_state.stories.get(&story_id).unwrap();
+
+        assert!(story.updated_at >= before);
+        assert_eq!(
+            db_state.epic_last_activity(&epic_id),
+            Some(story.updated_at)
+        );
+    }
+
+    #[test]
+    fn update_story_status_should_track_status_changes_only() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        db.update_story_status(story_id, Status::InProgress)
+            .unwrap();
+        let changed_at = db.read_db().unwrap().stories[&story_id].status_changed_at;
+
+        db.update_story_status(story_id, Status::InProgress)
+            .unwrap();
+        let story = db.read_db().unwrap().stories[&story_id].clone();
+
+        assert_eq!(story.status_changed_at, changed_at);
+        assert!(story.updated_at >= changed_at);
+    }
+
+    #[test]
+    fn update_story_status_should_log_no_epic_for_stories_outside_of_any_epic() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let mut db_state = db.read_db().unwrap();
+        db_state
+            .stories
+            .insert(StoryId(1), Story::new("orphan".to_owned(), "".to_owned()));
+        db.database.write_db(&db_state).unwrap();
+
+        db.update_story_status(StoryId(1), Status::Closed).unwrap();
+
+        let activity = db.read_db().unwrap().activity;
+        assert_eq!(activity.len(), 1);
+        assert_eq!(activity[0].epic_id, None);
+        assert_eq!(activity[0].story_id, Some(StoryId(1)));
+    }
+
+    #[test]
+    fn create_due_recurring_stories_should_respect_interval() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+
+        assert!(db
+            .add_recurring_story(RecurringStory::new(
+                "".to_owned(),
+                "".to_owned(),
+                EpicId(999),
+                7
+            ))
+            .is_err());
+        assert!(db
+            .add_recurring_story(RecurringStory::new(
+                "".to_owned(),
+                "".to_owned(),
+                epic_id,
+                0
+            ))
+            .is_err());
+        db.add_recurring_story(RecurringStory::new(
+            "weekly".to_owned(),
+            "".to_owned(),
+            epic_id,
+            7,
+        ))
+        .unwrap();
+
+        let now = Utc::now();
+        let story_ids = db.create_due_recurring_stories(now).unwrap();
+        assert_eq!(story_ids.len(), 1);
+        assert!(db
+            .create_due_recurring_stories(now + chrono::Duration::days(6))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            db.create_due_recurring_stories(now + chrono::Duration::days(7))
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let db_state = db.read_db().unwrap();
+        let epic = db_state.epics.get(&epic_id).unwrap();
+        assert_eq!(epic.stories.len(), 2);
+        assert_eq!(db_state.stories[&story_ids[0]].name, "weekly");
+        assert_eq!(db_state.stories[&story_ids[0]].status, Status::Open);
+    }
+
+    #[test]
+    fn update_should_only_change_given_fields() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("epic".to_owned(), "old".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("story".to_owned(), "old".to_owned()), epic_id)
+            .unwrap();
+
+        db.update_epic(
+            epic_id,
+            ItemChanges {
+                name: Some("renamed".to_owned()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        db.update_story(
+            story_id,
+            ItemChanges {
+                description: Some("new".to_owned()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics[&epic_id].name, "renamed");
+        assert_eq!(db_state.epics[&epic_id].description, "old");
+        assert_eq!(db_state.stories[&story_id].name, "story");
+        assert_eq!(db_state.stories[&story_id].description, "new");
+        assert!(db.update_epic(EpicId(999), ItemChanges::default()).is_err());
+        assert!(db
+            .update_story(StoryId(epic_id.0), ItemChanges::default())
+            .is_err());
+    }
+
+    #[test]
+    fn update_should_fail_on_version_conflict() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let version = db.read_db().unwrap().stories[&story_id].version;
+
+        db.update_story_status(story_id, Status::InProgress)
+            .unwrap();
+
+        let result = db.update_story(
+            story_id,
+            ItemChanges {
+                status: Some(Status::Closed),
+                expected_version: Some(version),
+                ..Default::default()
+            },
+        );
+        let conflict = result.unwrap_err().downcast::<Conflict>().unwrap();
+        assert_eq!(conflict.expected, version);
+        assert_eq!(conflict.actual, version + 1);
+        assert_eq!(
+            db.read_db().unwrap().stories[&story_id].status,
+            Status::InProgress
+        );
+
+        db.update_story(
+            story_id,
+            ItemChanges {
+                status: Some(Status::Closed),
+                expected_version: Some(version + 1),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(db.read_db().unwrap().epics[&epic_id].version, 2);
+    }
+
+    #[test]
+    fn debounced_writes_should_be_coalesced_until_flush() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        }
+        .with_debounce(Duration::from_secs(3600));
+
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        assert!(db.has_unsaved_changes());
+        assert_eq!(db.read_db().unwrap().stories.len(), 1);
+
+        db.flush().unwrap();
+
+        assert!(!db.has_unsaved_changes());
+        assert_eq!(db.read_db().unwrap().last_item_id, 2);
+    }
+
+    #[test]
+    fn changes_should_be_attributed_to_the_user() {
+        let mock = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = mock
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let other_id = mock
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let db = JiraDatabase {
+            database: mock.database,
+        }
+        .with_user(Some("alice".to_owned()));
+
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.update_story_status(story_id, Status::Resolved).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(
+            db_state.epics[&epic_id].updated_by.as_deref(),
+            Some("alice")
+        );
+        assert_eq!(
+            db_state.stories[&story_id].updated_by.as_deref(),
+            Some("alice")
+        );
+        assert_eq!(db_state.stories[&other_id].updated_by, None);
+        assert_eq!(
+            db_state
+                .activity
+                .iter()
+                .map(|a| a.by.as_deref())
+                .collect::<Vec<_>>(),
+            vec![None, None, Some("alice"), Some("alice")]
+        );
+
+        // a change that names its author, e.g. from a sync, keeps it
+        let mut synced = db_state.clone();
+        let story = synced.stories.get_mut(&other_id).unwrap();
+        story.version += 1;
+        story.updated_by = Some("bob".to_owned());
+        db.database.write_db(&synced).unwrap();
+        assert_eq!(
+            db.read_db().unwrap().stories[&other_id]
+                .updated_by
+                .as_deref(),
+            Some("bob")
+        );
+    }
+
+    #[test]
+    fn import_epic_should_not_attribute_earlier_activity() {
+        let source = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        source
+            .create_epic(Epic::new("imported".to_owned(), "".to_owned()))
+            .unwrap();
+        let mut source = source.read_db().unwrap();
+        for activity in source.activity.iter_mut() {
+            activity.at -= chrono::Duration::days(30);
+        }
+
+        let mock = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = mock
+            .create_epic(Epic::new("existing".to_owned(), "".to_owned()))
+            .unwrap();
+        mock.create_story(Story::new("existing".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let db = JiraDatabase {
+            database: mock.database,
+        }
+        .with_user(Some("alice".to_owned()));
+
+        db.import_epic(&source, None, None).unwrap();
+
+        let activity = db.read_db().unwrap().activity;
+        assert_eq!(activity.len(), 3);
+        assert!(activity
+            .iter()
+            .filter(|a| a.name == "existing")
+            .all(|a| a.by.is_none()));
+    }
+
+    #[test]
+    fn mutations_should_be_recorded_in_activity_log() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("epic".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("story".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.update_story_status(story_id, Status::Resolved).unwrap();
+        db.update_story_status(story_id, Status::Resolved).unwrap();
+        db.delete_story(epic_id, story_id).unwrap();
+
+        let activity = db.read_db().unwrap().activity;
+        let events = activity
+            .iter()
+            .map(|a| (a.epic_id, a.story_id, a.name.as_str(), a.event.clone()))
+            .collect::<Vec<_>>();
+        let epic_id = Some(epic_id);
+
+        assert_eq!(
+            events,
+            vec![
+                (epic_id, None, "epic", ActivityEvent::Created),
+                (epic_id, Some(story_id), "story", ActivityEvent::Created),
+                (
+                    epic_id,
+                    Some(story_id),
+                    "story",
+                    ActivityEvent::StatusChanged {
+                        from: Status::Open,
+                        to: Status::Resolved
+                    }
+                ),
+                (epic_id, Some(story_id), "story", ActivityEvent::Deleted),
+            ]
+        );
+    }
+
+    #[test]
+    fn compact_ids_should_renumber_items_contiguously() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let deleted = db
+            .create_epic(Epic::new("deleted".to_owned(), "".to_owned()))
+            .unwrap();
+        let epic_id = db
+            .create_epic(Epic::new("kept".to_owned(), "".to_owned()))
+            .unwrap();
+        let deleted_story = db
+            .create_story(Story::new("deleted".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("kept".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.delete_story(epic_id, deleted_story).unwrap();
+        db.delete_epic(deleted).unwrap();
+
+        let mapping = db.compact_ids().unwrap();
+
+        assert_eq!(mapping, BTreeMap::from([(epic_id.0, 1), (story_id.0, 2)]));
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.last_item_id, 2);
+        assert_eq!(db_state.epics.get(&EpicId(1)).unwrap().name, "kept");
+        assert_eq!(
+            db_state.epics.get(&EpicId(1)).unwrap().stories,
+            vec![StoryId(2)]
+        );
+        assert_eq!(db_state.stories.get(&StoryId(2)).unwrap().name, "kept");
+
+        assert_eq!(
+            db.create_epic(Epic::new("".to_owned(), "".to_owned()))
+                .unwrap(),
+            EpicId(3)
+        );
+    }
+
+    #[test]
+    fn compact_ids_should_renumber_blockers_sprints_and_activity() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let deleted = db
+            .create_epic(Epic::new("deleted".to_owned(), "".to_owned()))
+            .unwrap();
+        let epic_id = db
+            .create_epic(Epic::new("kept".to_owned(), "".to_owned()))
+            .unwrap();
+        let deleted_story = db
+            .create_story(Story::new("deleted".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let blocking = db
+            .create_story(Story::new("blocking".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let blocked = db
+            .create_story(Story::new("blocked".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.set_story_blocker(blocked, Some(Blocker::Story(blocking)))
+            .unwrap();
+        db.start_sprint(Sprint::new("one".to_owned(), 10)).unwrap();
+        db.toggle_sprint_story(blocked).unwrap();
+        db.delete_story(epic_id, deleted_story).unwrap();
+        db.delete_epic(deleted).unwrap();
+
+        db.compact_ids().unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(
+            db_state.stories.get(&StoryId(3)).unwrap().blocker,
+            Some(Blocker::Story(StoryId(2)))
+        );
+        assert_eq!(db_state.sprints[0].stories, vec![StoryId(3)]);
+        assert_eq!(
+            db_state
+                .activity
+                .iter()
+                .map(|a| (a.epic_id, a.story_id, a.name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some(EpicId(1)), None, "kept"),
+                (Some(EpicId(1)), Some(StoryId(2)), "blocking"),
+                (Some(EpicId(1)), Some(StoryId(3)), "blocked"),
+            ]
+        );
+    }
+
     mod database {
         use std::collections::HashMap;
         use std::io::Write;
 
         use super::*;
 
+        #[test]
+        fn read_db_file_should_not_create_a_missing_file() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("typo.json");
+
+            let error = read_db_file(path.to_str().unwrap()).unwrap_err();
+            assert!(error.to_string().contains("typo.json"));
+            assert!(!path.exists());
+        }
+
+        #[test]
+        fn take_due_snapshot_should_follow_the_policy() {
+            use crate::config::SnapshotInterval;
+
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("board.json");
+            let path = path.to_str().unwrap();
+            let db = JiraDatabase::new(path).unwrap();
+            let policy = SnapshotPolicy {
+                every: SnapshotInterval::Daily,
+                keep: 2,
+            };
+            let day = |d| Utc::now() + chrono::Duration::days(d);
+
+            assert!(db.take_due_snapshot(&policy, day(0)).unwrap().is_some());
+            assert!(db.take_due_snapshot(&policy, day(0)).unwrap().is_none());
+            assert!(db.take_due_snapshot(&policy, day(1)).unwrap().is_some());
+            let taken = db.take_due_snapshot(&policy, day(2)).unwrap().unwrap();
+
+            let snapshots = list_snapshots(path).unwrap();
+            assert_eq!(
+                snapshots.iter().map(|(date, _)| *date).collect::<Vec<_>>(),
+                vec![day(1).date_naive(), day(2).date_naive()]
+            );
+            assert_eq!(snapshots[1].1, taken);
+            assert_eq!(
+                read_db_file(taken.to_str().unwrap()).unwrap(),
+                DBState::new()
+            );
+
+            let never = SnapshotPolicy {
+                every: SnapshotInterval::Never,
+                ..policy
+            };
+            assert!(db.take_due_snapshot(&never, day(9)).unwrap().is_none());
+        }
+
         #[test]
         fn read_db_should_fail_with_invalid_path() {
             let db = JSONFileDatabase {
                 file_path: "INVALID_PATH".to_owned(),
             };
-            assert_eq!(db.read_db().is_err(), true);
+            assert!(db.read_db().is_err());
         }
 
         #[test]
@@ -482,7 +2517,7 @@ mod tests {
 
             let result = db.read_db();
 
-            assert_eq!(result.is_err(), true);
+            assert!(result.is_err());
         }
 
         #[test]
@@ -502,7 +2537,7 @@ mod tests {
 
             let result = db.read_db();
 
-            assert_eq!(result.is_ok(), true);
+            assert!(result.is_ok());
         }
 
         #[test]
@@ -524,30 +2559,50 @@ mod tests {
                 name: "epic 1".to_owned(),
                 description: "epic 1".to_owned(),
                 status: Status::Open,
+                updated_at: Utc::now(),
+                status_changed_at: Utc::now(),
+                number: 1,
+                version: 1,
+                blocker: None,
+                points: None,
+                fields: BTreeMap::new(),
+                watched: false,
+                updated_by: None,
             };
             let epic = Epic {
                 name: "epic 1".to_owned(),
                 description: "epic 1".to_owned(),
                 status: Status::Open,
-                stories: vec![2],
+                stories: vec![StoryId(2)],
+                updated_at: Utc::now(),
+                status_changed_at: Utc::now(),
+                last_story_number: 1,
+                version: 1,
+                pinned: false,
+                notes: vec![],
+                updated_by: None,
             };
 
             let mut stories = HashMap::new();
-            stories.insert(2, story);
+            stories.insert(StoryId(2), story);
 
             let mut epics = HashMap::new();
-            epics.insert(1, epic);
+            epics.insert(EpicId(1), epic);
 
             let state = DBState {
                 last_item_id: 2,
                 epics,
                 stories,
+                recurring_stories: vec![],
+                id_scheme: IdScheme::Global,
+                activity: vec![],
+                sprints: vec![],
             };
 
             let write_result = db.write_db(&state);
             let read_result = db.read_db().unwrap();
 
-            assert_eq!(write_result.is_ok(), true);
+            assert!(write_result.is_ok());
             assert_eq!(read_result, state);
         }
     }
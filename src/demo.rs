@@ -0,0 +1,132 @@
+use anyhow::{anyhow, Result};
+
+use crate::db::JiraDatabase;
+use crate::models::{Epic, Status, Story};
+
+type DemoStory = (&'static str, &'static str, Status);
+
+static DEMO_BOARD: &[(&str, &str, Status, &[DemoStory])] = &[
+    (
+        "User accounts",
+        "Sign up, login and profile management for end users.",
+        Status::InProgress,
+        &[
+            (
+                "Sign up with email",
+                "Users can create an account with email and password.",
+                Status::Closed,
+            ),
+            (
+                "Password reset",
+                "Send a reset link that expires after one hour.",
+                Status::Resolved,
+            ),
+            (
+                "Profile page",
+                "Show and edit display name and avatar.",
+                Status::InProgress,
+            ),
+            (
+                "Two-factor authentication",
+                "Support TOTP apps as a second factor.",
+                Status::Open,
+            ),
+        ],
+    ),
+    (
+        "Checkout",
+        "Everything between the cart and the order confirmation.",
+        Status::Open,
+        &[
+            (
+                "Cart summary",
+                "List items, quantities and the total incl. taxes.",
+                Status::InProgress,
+            ),
+            (
+                "Card payments",
+                "Integrate the payment provider for credit cards.",
+                Status::Open,
+            ),
+            (
+                "Order confirmation email",
+                "Send a receipt once the payment succeeded.",
+                Status::Open,
+            ),
+        ],
+    ),
+    (
+        "Release 1.0",
+        "Housekeeping needed before the first public release.",
+        Status::Resolved,
+        &[
+            (
+                "Write changelog",
+                "Summarize all user facing changes.",
+                Status::Closed,
+            ),
+            (
+                "Tag release",
+                "Tag the release commit and publish the binaries.",
+                Status::Resolved,
+            ),
+        ],
+    ),
+];
+
+/// Populates an empty database with a small, realistic sample board.
+pub fn seed(db: &JiraDatabase) -> Result<()> {
+    let db_state = db.read_db()?;
+    if !db_state.epics.is_empty() || !db_state.stories.is_empty() {
+        return Err(anyhow!(
+            "refusing to add demo data to a database that already contains items"
+        ));
+    }
+
+    for (name, description, status, stories) in DEMO_BOARD {
+        let epic_id = db.create_epic(Epic::new(name.to_string(), description.to_string()))?;
+        let story_ids = db.create_stories(
+            stories
+                .iter()
+                .map(|(name, description, _)| Story::new(name.to_string(), description.to_string()))
+                .collect(),
+            epic_id,
+        )?;
+
+        for (story_id, (_, _, status)) in story_ids.into_iter().zip(stories.iter()) {
+            if *status != Status::Open {
+                db.update_story_status(story_id, status.clone())?;
+            }
+        }
+        if *status != Status::Open {
+            db.update_epic_status(epic_id, status.clone())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::MockDB;
+
+    #[test]
+    fn seed_should_populate_empty_database_only() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+
+        seed(&db).unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics.len(), DEMO_BOARD.len());
+        assert_eq!(db_state.stories.len(), 9);
+        assert!(db_state
+            .stories
+            .values()
+            .any(|s| s.status == Status::InProgress));
+
+        assert!(seed(&db).is_err());
+    }
+}
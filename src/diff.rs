@@ -0,0 +1,226 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::models::DBState;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", rename_all = "lowercase")]
+pub enum ItemChange {
+    Added,
+    Removed,
+    Changed { fields: Vec<FieldChange> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ItemDiff {
+    pub id: u32,
+    pub label: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub change: ItemChange,
+}
+
+/// What was added, removed or changed between two versions of a board.
+/// Timestamps, versions and numbering are left out; they change with every edit.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BoardDiff {
+    pub epics: Vec<ItemDiff>,
+    pub stories: Vec<ItemDiff>,
+}
+
+impl BoardDiff {
+    pub fn collect(old: &DBState, new: &DBState) -> Self {
+        let epic_fields = |db_state: &DBState| {
+            db_state
+                .epics
+                .iter()
+                .map(|(id, epic)| {
+                    let fields = vec![
+                        ("name", epic.name.clone()),
+                        ("description", epic.description.clone()),
+                        ("status", epic.status.to_string()),
+                    ];
+                    (id.0, (db_state.epic_label(*id), epic.name.clone(), fields))
+                })
+                .collect::<HashMap<_, _>>()
+        };
+        let story_fields = |db_state: &DBState| {
+            db_state
+                .stories
+                .iter()
+                .map(|(id, story)| {
+                    let epic_id = db_state.story_epic(*id);
+                    let label = epic_id
+                        .map_or(id.to_string(), |epic_id| db_state.story_label(epic_id, *id));
+                    let fields = vec![
+                        ("name", story.name.clone()),
+                        ("description", story.description.clone()),
+                        ("status", story.status.to_string()),
+                        (
+                            "epic",
+                            epic_id.map_or(String::new(), |id| db_state.epic_label(id)),
+                        ),
+                        (
+                            "blocker",
+                            story
+                                .blocker
+                                .as_ref()
+                                .map_or(String::new(), |b| db_state.blocker_label(b)),
+                        ),
+                        (
+                            "points",
+                            story.points.map_or(String::new(), |p| p.to_string()),
+                        ),
+                    ];
+                    (id.0, (label, story.name.clone(), fields))
+                })
+                .collect::<HashMap<_, _>>()
+        };
+
+        Self {
+            epics: diff_items(epic_fields(old), epic_fields(new)),
+            stories: diff_items(story_fields(old), story_fields(new)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.epics.is_empty() && self.stories.is_empty()
+    }
+}
+
+type Fields = (String, String, Vec<(&'static str, String)>);
+
+fn diff_items(old: HashMap<u32, Fields>, new: HashMap<u32, Fields>) -> Vec<ItemDiff> {
+    let ids = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+
+    ids.into_iter()
+        .filter_map(|id| {
+            let (label, name, change) = match (old.get(id), new.get(id)) {
+                (None, Some((label, name, _))) => (label, name, ItemChange::Added),
+                (Some((label, name, _)), None) => (label, name, ItemChange::Removed),
+                (Some((_, _, before)), Some((label, name, after))) => {
+                    let fields = before
+                        .iter()
+                        .zip(after)
+                        .filter(|((_, from), (_, to))| from != to)
+                        .map(|((field, from), (_, to))| FieldChange {
+                            field,
+                            from: from.clone(),
+                            to: to.clone(),
+                        })
+                        .collect::<Vec<_>>();
+                    if fields.is_empty() {
+                        return None;
+                    }
+                    (label, name, ItemChange::Changed { fields })
+                }
+                (None, None) => return None,
+            };
+            Some(ItemDiff {
+                id: *id,
+                label: label.clone(),
+                name: name.clone(),
+                change,
+            })
+        })
+        .collect()
+}
+
+impl Display for BoardDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no changes");
+        }
+
+        for (title, items) in [("epics", &self.epics), ("stories", &self.stories)] {
+            if items.is_empty() {
+                continue;
+            }
+            writeln!(f, "{title}:")?;
+            for item in items {
+                match &item.change {
+                    ItemChange::Added => writeln!(f, "  + {} {}", item.label, item.name)?,
+                    ItemChange::Removed => writeln!(f, "  - {} {}", item.label, item.name)?,
+                    ItemChange::Changed { fields } => {
+                        let fields = fields
+                            .iter()
+                            .map(|c| format!("{} {:?} → {:?}", c.field, c.from, c.to))
+                            .collect::<Vec<_>>();
+                        writeln!(f, "  ~ {} {}: {}", item.label, item.name, fields.join(", "))?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Epic, EpicId, Status, Story, StoryId};
+
+    #[test]
+    fn collect_should_list_added_removed_and_changed_items() {
+        let mut old = DBState::new();
+        let mut epic = Epic::new("Checkout".to_owned(), "".to_owned());
+        epic.stories = vec![StoryId(2), StoryId(3)];
+        old.epics.insert(EpicId(1), epic);
+        old.stories
+            .insert(StoryId(2), Story::new("Pay".to_owned(), "".to_owned()));
+        old.stories
+            .insert(StoryId(3), Story::new("Refund".to_owned(), "".to_owned()));
+
+        let mut new = old.clone();
+        new.stories.remove(&StoryId(3));
+        new.stories
+            .insert(StoryId(4), Story::new("Coupons".to_owned(), "".to_owned()));
+        new.epics.get_mut(&EpicId(1)).unwrap().stories = vec![StoryId(2), StoryId(4)];
+        let pay = new.stories.get_mut(&StoryId(2)).unwrap();
+        pay.status = Status::Resolved;
+        pay.version += 1;
+
+        let diff = BoardDiff::collect(&old, &new);
+        assert!(diff.epics.is_empty());
+        assert_eq!(
+            diff.stories
+                .iter()
+                .map(|s| (s.id, s.change.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    2,
+                    ItemChange::Changed {
+                        fields: vec![FieldChange {
+                            field: "status",
+                            from: "OPEN".to_owned(),
+                            to: "RESOLVED".to_owned()
+                        }]
+                    }
+                ),
+                (3, ItemChange::Removed),
+                (4, ItemChange::Added),
+            ]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "stories:\n  ~ 2 Pay: status \"OPEN\" → \"RESOLVED\"\n  - 3 Refund\n  + 4 Coupons\n"
+        );
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["stories"][1]["change"], "removed");
+        assert_eq!(json["stories"][0]["fields"][0]["to"], "RESOLVED");
+
+        assert!(BoardDiff::collect(&old, &old).is_empty());
+        assert_eq!(BoardDiff::default().to_string(), "no changes\n");
+    }
+}
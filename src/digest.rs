@@ -0,0 +1,137 @@
+use std::fmt::Display;
+
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use itertools::Itertools;
+use serde_json::json;
+
+use crate::config::Webhook;
+use crate::models::{DBState, Status};
+
+/// Daily summary of the board for a team chat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Digest {
+    pub date: NaiveDate,
+    /// Stories resolved or closed the day before.
+    pub completed: Vec<String>,
+    pub in_progress: Vec<String>,
+    /// Unfinished stories with a blocker, with what blocks them.
+    pub blocked: Vec<String>,
+}
+
+impl Digest {
+    pub fn collect(db_state: &DBState, today: NaiveDate) -> Self {
+        let yesterday = today.pred_opt().unwrap_or(today);
+        let stories = db_state
+            .epics
+            .iter()
+            .sorted_by_key(|(id, _)| **id)
+            .flat_map(|(epic_id, epic)| {
+                epic.stories.iter().filter_map(|story_id| {
+                    let story = db_state.stories.get(story_id)?;
+                    let line = format!(
+                        "{} {}",
+                        db_state.story_label(*epic_id, *story_id),
+                        story.name
+                    );
+                    Some((story, line))
+                })
+            })
+            .collect::<Vec<_>>();
+        let is_done = |status: &Status| matches!(status, Status::Resolved | Status::Closed);
+
+        Self {
+            date: today,
+            completed: stories
+                .iter()
+                .filter(|(s, _)| is_done(&s.status))
+                .filter(|(s, _)| s.status_changed_at.date_naive() == yesterday)
+                .map(|(_, line)| line.clone())
+                .collect(),
+            in_progress: stories
+                .iter()
+                .filter(|(s, _)| s.status == Status::InProgress)
+                .map(|(_, line)| line.clone())
+                .collect(),
+            blocked: stories
+                .iter()
+                .filter(|(s, _)| !is_done(&s.status))
+                .filter_map(|(s, line)| {
+                    let blocker = s.blocker.as_ref()?;
+                    Some(format!("{line} ({})", db_state.blocker_label(blocker)))
+                })
+                .collect(),
+        }
+    }
+
+    /// Posts the digest as a message to a Slack or Discord webhook.
+    pub fn post(&self, webhook: &Webhook) -> Result<()> {
+        let (url, payload) = match webhook {
+            Webhook::Slack { url } => (url, json!({ "text": self.to_string() })),
+            Webhook::Discord { url } => (url, json!({ "content": self.to_string() })),
+        };
+        ureq::post(url)
+            .header("Content-Type", "application/json")
+            .send(&serde_json::to_vec(&payload)?)
+            .map_err(|e| anyhow!(format!("POST {url} failed: {e}")))?;
+        Ok(())
+    }
+}
+
+impl Display for Digest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Board digest for {}", self.date)?;
+        for (title, lines) in [
+            ("Completed yesterday", &self.completed),
+            ("In progress", &self.in_progress),
+            ("Blocked", &self.blocked),
+        ] {
+            writeln!(f)?;
+            writeln!(f, "{title} ({}):", lines.len())?;
+            if lines.is_empty() {
+                writeln!(f, "  none")?;
+            }
+            for line in lines {
+                writeln!(f, "  • {line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Blocker, Epic, EpicId, Story, StoryId};
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn collect_should_summarize_yesterday_and_open_work() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+        let mut db_state = DBState::new();
+        let mut epic = Epic::new("Checkout".to_owned(), "".to_owned());
+        epic.stories = vec![StoryId(2), StoryId(3), StoryId(4), StoryId(5)];
+        db_state.epics.insert(EpicId(1), epic);
+
+        let mut story = |id: u32, name: &str, status: Status, day: u32| {
+            let mut story = Story::new(name.to_owned(), "".to_owned());
+            story.status = status;
+            story.status_changed_at = Utc.with_ymd_and_hms(2024, 5, day, 12, 0, 0).unwrap();
+            db_state.stories.insert(StoryId(id), story);
+        };
+        story(2, "Pay", Status::Resolved, 9);
+        story(3, "Refund", Status::Closed, 8);
+        story(4, "Coupons", Status::InProgress, 9);
+        story(5, "Invoices", Status::Open, 9);
+        db_state.stories.get_mut(&StoryId(5)).unwrap().blocker = Some(Blocker::Story(StoryId(4)));
+
+        let digest = Digest::collect(&db_state, today);
+        assert_eq!(digest.completed, vec!["2 Pay"]);
+        assert_eq!(digest.in_progress, vec!["4 Coupons"]);
+        assert_eq!(digest.blocked, vec!["5 Invoices (blocked by 4)"]);
+        assert_eq!(
+            digest.to_string(),
+            "Board digest for 2024-05-10\n\nCompleted yesterday (1):\n  • 2 Pay\n\nIn progress (1):\n  • 4 Coupons\n\nBlocked (1):\n  • 5 Invoices (blocked by 4)\n"
+        );
+    }
+}
@@ -0,0 +1,348 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+use crate::listing::write_listing;
+use crate::models::{Blocker, DBState, EpicId, StoryId};
+use crate::web;
+
+/// A file format the board can be exported to.
+pub trait Exporter {
+    /// Name the format is chosen by, e.g. with `export --format`.
+    fn name(&self) -> &'static str;
+    /// Extension of the files the format is guessed from.
+    fn extension(&self) -> &'static str;
+    fn export(&self, db_state: &DBState, out: &mut dyn Write) -> Result<()>;
+}
+
+/// The database itself, which can be opened with `--db` or imported from.
+struct JsonExporter;
+
+impl Exporter for JsonExporter {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+    fn export(&self, db_state: &DBState, out: &mut dyn Write) -> Result<()> {
+        Ok(serde_json::to_writer_pretty(out, db_state)?)
+    }
+}
+
+/// The tab separated listing printed when stdout is not a terminal.
+struct TsvExporter;
+
+impl Exporter for TsvExporter {
+    fn name(&self) -> &'static str {
+        "tsv"
+    }
+    fn extension(&self) -> &'static str {
+        "tsv"
+    }
+    fn export(&self, db_state: &DBState, mut out: &mut dyn Write) -> Result<()> {
+        Ok(write_listing(db_state, "", &mut out)?)
+    }
+}
+
+/// The page served by `view`.
+struct HtmlExporter;
+
+impl Exporter for HtmlExporter {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+    fn extension(&self) -> &'static str {
+        "html"
+    }
+    fn export(&self, db_state: &DBState, out: &mut dyn Write) -> Result<()> {
+        Ok(out.write_all(web::render(db_state).as_bytes())?)
+    }
+}
+
+/// Every export format, the default one first. New formats are added here.
+pub fn exporters() -> Vec<Box<dyn Exporter>> {
+    vec![
+        Box::new(JsonExporter),
+        Box::new(TsvExporter),
+        Box::new(HtmlExporter),
+    ]
+}
+
+/// Returns the exporter named `format`, or without one the exporter for the
+/// extension of `output`, falling back to the default format.
+pub fn find_exporter(format: Option<&str>, output: &str) -> Result<Box<dyn Exporter>> {
+    let mut exporters = exporters();
+    let index = match format {
+        Some(format) => exporters
+            .iter()
+            .position(|e| e.name().eq_ignore_ascii_case(format.trim()))
+            .ok_or_else(|| {
+                anyhow!(format!(
+                    "unknown export format '{format}', expected one of: {}",
+                    exporters.iter().map(|e| e.name()).join(", ")
+                ))
+            })?,
+        None => {
+            let extension = Path::new(output).extension().and_then(|e| e.to_str());
+            exporters
+                .iter()
+                .position(|e| extension.is_some_and(|ext| ext.eq_ignore_ascii_case(e.extension())))
+                .unwrap_or(0)
+        }
+    };
+    Ok(exporters.swap_remove(index))
+}
+
+fn placeholder(prefix: &str, text: &str) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    format!("{prefix}-{:016x}", hasher.finish())
+}
+
+/// Replaces all names, descriptions, notes, custom field values and user names with hashed placeholders while keeping
+/// ids, statuses and the epic/story structure intact.
+pub fn anonymize(db_state: &DBState) -> DBState {
+    let mut anonymized = db_state.clone();
+
+    for epic in anonymized.epics.values_mut() {
+        epic.name = placeholder("epic", &epic.name);
+        epic.description = placeholder("description", &epic.description);
+        for note in epic.notes.iter_mut() {
+            note.text = placeholder("note", &note.text);
+        }
+        anonymize_user(&mut epic.updated_by);
+    }
+    for story in anonymized.stories.values_mut() {
+        story.name = placeholder("story", &story.name);
+        story.description = placeholder("description", &story.description);
+        if let Some(Blocker::Reason(reason)) = &mut story.blocker {
+            *reason = placeholder("reason", reason);
+        }
+        for value in story.fields.values_mut() {
+            *value = placeholder("field", value);
+        }
+        anonymize_user(&mut story.updated_by);
+    }
+    for activity in anonymized.activity.iter_mut() {
+        let prefix = if activity.story_id.is_some() {
+            "story"
+        } else {
+            "epic"
+        };
+        activity.name = placeholder(prefix, &activity.name);
+        anonymize_user(&mut activity.by);
+    }
+    for recurring_story in anonymized.recurring_stories.iter_mut() {
+        recurring_story.name = placeholder("story", &recurring_story.name);
+        recurring_story.description = placeholder("description", &recurring_story.description);
+    }
+    for sprint in anonymized.sprints.iter_mut() {
+        sprint.name = placeholder("sprint", &sprint.name);
+        let retrospective = &mut sprint.retrospective;
+        for entry in retrospective
+            .went_well
+            .iter_mut()
+            .chain(retrospective.to_improve.iter_mut())
+            .chain(retrospective.actions.iter_mut())
+        {
+            *entry = placeholder("retrospective", entry);
+        }
+    }
+
+    anonymized
+}
+
+fn anonymize_user(user: &mut Option<String>) {
+    if let Some(user) = user {
+        *user = placeholder("user", user);
+    }
+}
+
+/// Extracts an epic with its stories, activity and recurring stories into a
+/// database of its own. The epic gets id 1 and its stories the ids after it,
+/// in the order they are listed.
+pub fn extract_epic(db_state: &DBState, epic_id: EpicId) -> Result<DBState> {
+    let epic = db_state
+        .epics
+        .get(&epic_id)
+        .ok_or_else(|| anyhow!(format!("epic not found: {epic_id}")))?;
+
+    let mut ids = HashMap::from([(epic_id.0, 1)]);
+    let mut next_id = 2;
+    let mut remap = |id: u32| {
+        *ids.entry(id).or_insert_with(|| {
+            next_id += 1;
+            next_id - 1
+        })
+    };
+
+    let mut extracted = DBState::new();
+    extracted.id_scheme = db_state.id_scheme;
+
+    let mut new_epic = epic.clone();
+    new_epic.stories = vec![];
+    for story_id in &epic.stories {
+        if let Some(story) = db_state.stories.get(story_id) {
+            let id = StoryId(remap(story_id.0));
+            new_epic.stories.push(id);
+            extracted.stories.insert(id, story.clone());
+        }
+    }
+    extracted.epics.insert(EpicId(1), new_epic);
+
+    // deleted stories keep their history under ids of their own
+    for activity in db_state
+        .activity
+        .iter()
+        .filter(|a| a.epic_id == Some(epic_id))
+    {
+        let mut activity = activity.clone();
+        activity.epic_id = Some(EpicId(1));
+        activity.story_id = activity.story_id.map(|id| StoryId(remap(id.0)));
+        extracted.activity.push(activity);
+    }
+    for recurring_story in db_state
+        .recurring_stories
+        .iter()
+        .filter(|r| r.epic_id == epic_id)
+    {
+        let mut recurring_story = recurring_story.clone();
+        recurring_story.epic_id = EpicId(1);
+        extracted.recurring_stories.push(recurring_story);
+    }
+
+    for story in extracted.stories.values_mut() {
+        story.blocker = story.blocker.as_ref().map(|b| b.remapped(&ids));
+    }
+
+    extracted.last_item_id = next_id - 1;
+    Ok(extracted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Activity, ActivityEvent, Epic, Note, Status, Story};
+
+    #[test]
+    fn anonymize_should_replace_text_but_keep_structure() {
+        let mut db_state = DBState::new();
+        let mut epic = Epic::new("Secret project".to_owned(), "Top secret".to_owned());
+        epic.stories.push(StoryId(2));
+        epic.status = Status::InProgress;
+        epic.notes.push(Note {
+            at: chrono::Utc::now(),
+            text: "Secret meeting".to_owned(),
+        });
+        epic.updated_by = Some("secretary".to_owned());
+        db_state.epics.insert(EpicId(1), epic);
+        db_state.stories.insert(
+            StoryId(2),
+            Story::new("Secret project".to_owned(), "".to_owned()),
+        );
+        db_state.last_item_id = 2;
+
+        let anonymized = anonymize(&db_state);
+
+        let epic = anonymized.epics.get(&EpicId(1)).unwrap();
+        let story = anonymized.stories.get(&StoryId(2)).unwrap();
+
+        assert_eq!(anonymized.last_item_id, 2);
+        assert_eq!(epic.stories, vec![StoryId(2)]);
+        assert_eq!(epic.status, Status::InProgress);
+        assert!(epic.name.starts_with("epic-"));
+        assert!(epic.description.starts_with("description-"));
+        assert!(story.name.starts_with("story-"));
+        assert_eq!(story.description, "");
+        assert_eq!(
+            epic.name.trim_start_matches("epic-"),
+            story.name.trim_start_matches("story-")
+        );
+        assert!(!serde_json::to_string(&anonymized)
+            .unwrap()
+            .contains("ecret"));
+        assert_eq!(anonymize(&db_state), anonymized);
+    }
+
+    #[test]
+    fn find_exporter_should_use_the_format_or_the_extension() {
+        let name = |format, output| find_exporter(format, output).map(|e| e.name());
+        assert_eq!(name(Some("HTML"), "board.json").unwrap(), "html");
+        assert_eq!(name(None, "board.tsv").unwrap(), "tsv");
+        assert_eq!(name(None, "board.bak").unwrap(), "json");
+        assert_eq!(
+            name(Some("pdf"), "board.pdf").unwrap_err().to_string(),
+            "unknown export format 'pdf', expected one of: json, tsv, html"
+        );
+
+        let mut db_state = DBState::new();
+        db_state
+            .epics
+            .insert(EpicId(1), Epic::new("Epic".to_owned(), "".to_owned()));
+        let mut out = Vec::new();
+        find_exporter(Some("tsv"), "-")
+            .unwrap()
+            .export(&db_state, &mut out)
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "epic\t1\t1\tOpen\tEpic\n");
+    }
+
+    #[test]
+    fn extract_epic_should_renumber_the_epic_and_its_stories() {
+        let mut db_state = DBState::new();
+        db_state
+            .epics
+            .insert(EpicId(1), Epic::new("other".to_owned(), "".to_owned()));
+        let mut epic = Epic::new("kept".to_owned(), "".to_owned());
+        epic.stories = vec![StoryId(7), StoryId(5)];
+        db_state.epics.insert(EpicId(4), epic);
+        db_state
+            .stories
+            .insert(StoryId(5), Story::new("b".to_owned(), "".to_owned()));
+        db_state
+            .stories
+            .insert(StoryId(7), Story::new("a".to_owned(), "".to_owned()));
+        db_state.activity = vec![
+            Activity::new(
+                chrono::Utc::now(),
+                EpicId(4),
+                Some(StoryId(9)),
+                "gone",
+                ActivityEvent::Deleted,
+            ),
+            Activity::new(
+                chrono::Utc::now(),
+                EpicId(1),
+                None,
+                "other",
+                ActivityEvent::Created,
+            ),
+        ];
+        db_state.last_item_id = 9;
+
+        let extracted = extract_epic(&db_state, EpicId(4)).unwrap();
+
+        assert_eq!(extracted.epics.len(), 1);
+        assert_eq!(extracted.epics[&EpicId(1)].name, "kept");
+        assert_eq!(
+            extracted.epics[&EpicId(1)].stories,
+            vec![StoryId(2), StoryId(3)]
+        );
+        assert_eq!(extracted.stories[&StoryId(2)].name, "a");
+        assert_eq!(extracted.stories[&StoryId(3)].name, "b");
+        assert_eq!(extracted.activity.len(), 1);
+        assert_eq!(extracted.activity[0].story_id, Some(StoryId(4)));
+        assert_eq!(extracted.last_item_id, 4);
+        assert!(extract_epic(&db_state, EpicId(2)).is_err());
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, Write};
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::models::{ActivityEvent, DBState, EpicId, Status, StoryId};
+
+/// Stacking order of the chart from the bottom up, with the fill of each band.
+const BANDS: [(Status, char); 4] = [
+    (Status::Closed, '#'),
+    (Status::Resolved, '='),
+    (Status::InProgress, '+'),
+    (Status::Open, '.'),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FlowDay {
+    pub date: NaiveDate,
+    pub counts: BTreeMap<Status, usize>,
+}
+
+/// Story counts per status at the end of each day, replayed from the activity log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CumulativeFlow {
+    pub days: Vec<FlowDay>,
+}
+
+impl CumulativeFlow {
+    /// Stories without a recorded creation are counted from the first day on,
+    /// in the status they had before their first recorded change.
+    pub fn collect(db_state: &DBState, today: NaiveDate) -> Self {
+        let mut activity = db_state.activity.iter().collect::<Vec<_>>();
+        activity.sort_by_key(|a| a.at);
+
+        let created: HashSet<StoryId> = activity
+            .iter()
+            .filter(|a| a.event == ActivityEvent::Created)
+            .filter_map(|a| a.story_id)
+            .collect();
+
+        let mut alive: HashMap<StoryId, (Option<EpicId>, Status)> = HashMap::new();
+        for (epic_id, epic) in &db_state.epics {
+            for story_id in &epic.stories {
+                if let Some(story) = db_state.stories.get(story_id) {
+                    alive.insert(*story_id, (Some(*epic_id), story.status.clone()));
+                }
+            }
+        }
+        // rewind to the state before the first logged event
+        for activity in activity.iter().rev() {
+            let Some(story_id) = activity.story_id else {
+                continue;
+            };
+            match &activity.event {
+                ActivityEvent::Created => {
+                    alive.remove(&story_id);
+                }
+                ActivityEvent::StatusChanged { from, .. } => {
+                    alive.insert(story_id, (activity.epic_id, from.clone()));
+                }
+                ActivityEvent::Deleted if !created.contains(&story_id) => {
+                    alive
+                        .entry(story_id)
+                        .or_insert((activity.epic_id, Status::Open));
+                }
+                ActivityEvent::Deleted => {}
+            }
+        }
+
+        let first_day = activity
+            .first()
+            .map_or(today, |a| a.at.date_naive().min(today));
+        let mut events = activity.iter().peekable();
+        let mut days = vec![];
+        for date in first_day.iter_days().take_while(|d| *d <= today) {
+            while let Some(activity) = events.next_if(|a| a.at.date_naive() <= date) {
+                match (&activity.event, activity.story_id) {
+                    (ActivityEvent::Created, Some(story_id)) => {
+                        alive.insert(story_id, (activity.epic_id, Status::Open));
+                    }
+                    (ActivityEvent::StatusChanged { to, .. }, Some(story_id)) => {
+                        alive.insert(story_id, (activity.epic_id, to.clone()));
+                    }
+                    (ActivityEvent::Deleted, Some(story_id)) => {
+                        alive.remove(&story_id);
+                    }
+                    (ActivityEvent::Deleted, None) => {
+                        alive.retain(|_, (epic_id, _)| *epic_id != activity.epic_id);
+                    }
+                    _ => {}
+                }
+            }
+
+            let mut counts: BTreeMap<Status, usize> =
+                Status::ALL.iter().map(|s| (s.clone(), 0)).collect();
+            for (_, status) in alive.values() {
+                *counts.entry(status.clone()).or_default() += 1;
+            }
+            days.push(FlowDay { date, counts });
+        }
+
+        Self { days }
+    }
+
+    pub fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "date,open,in_progress,resolved,closed")?;
+        for day in &self.days {
+            write!(out, "{}", day.date)?;
+            for status in &Status::ALL {
+                write!(out, ",{}", day.counts[status])?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the last `width` days as stacked columns, one character per day.
+    pub fn chart(&self, width: usize, height: usize) -> Vec<String> {
+        let days = &self.days[self.days.len().saturating_sub(width)..];
+        let max = days
+            .iter()
+            .map(|d| d.counts.values().sum::<usize>())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        let columns: Vec<Vec<char>> = days
+            .iter()
+            .map(|day| {
+                let mut column = vec![' '; height];
+                let mut total = 0;
+                let mut filled = 0;
+                for (status, fill) in &BANDS {
+                    total += day.counts[status];
+                    let top = (total * height + max / 2) / max;
+                    column[filled..top.max(filled)].fill(*fill);
+                    filled = top.max(filled);
+                }
+                column
+            })
+            .collect();
+
+        let mut lines: Vec<String> = (0..height)
+            .rev()
+            .map(|row| {
+                let label = if row + 1 == height {
+                    format!("{max:>4} |")
+                } else {
+                    "     |".to_owned()
+                };
+                label + &columns.iter().map(|c| c[row]).collect::<String>()
+            })
+            .collect();
+
+        lines.push(format!("     +{}", "-".repeat(days.len())));
+        match (days.first(), days.last()) {
+            (Some(first), Some(last)) if first != last => lines.push(format!(
+                "      {}{:>width$}",
+                first.date,
+                last.date.to_string(),
+                width = days.len().saturating_sub(10).max(11)
+            )),
+            (Some(first), _) => lines.push(format!("      {}", first.date)),
+            _ => {}
+        }
+        lines.push(
+            BANDS
+                .iter()
+                .rev()
+                .map(|(status, fill)| format!("{fill} {status}"))
+                .collect::<Vec<_>>()
+                .join("   "),
+        );
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::*;
+    use crate::models::{Activity, Epic, Story};
+
+    #[test]
+    fn collect_should_replay_status_changes_per_day() {
+        let start = Utc::now() - Duration::days(2);
+        let mut db_state = DBState::new();
+        let mut epic = Epic::new("".to_owned(), "".to_owned());
+        epic.stories = vec![StoryId(2), StoryId(3)];
+        db_state.epics.insert(EpicId(1), epic);
+        let mut story = Story::new("".to_owned(), "".to_owned());
+        story.status = Status::Closed;
+        db_state.stories.insert(StoryId(2), story);
+        // story 3 predates the activity log
+        db_state
+            .stories
+            .insert(StoryId(3), Story::new("".to_owned(), "".to_owned()));
+
+        db_state.activity = vec![
+            Activity::new(
+                start,
+                EpicId(1),
+                Some(StoryId(2)),
+                "",
+                ActivityEvent::Created,
+            ),
+            Activity::new(
+                start + Duration::days(1),
+                EpicId(1),
+                Some(StoryId(2)),
+                "",
+                ActivityEvent::StatusChanged {
+                    from: Status::Open,
+                    to: Status::Closed,
+                },
+            ),
+            Activity::new(
+                start,
+                EpicId(1),
+                Some(StoryId(4)),
+                "",
+                ActivityEvent::Created,
+            ),
+            Activity::new(
+                start + Duration::days(2),
+                EpicId(1),
+                Some(StoryId(4)),
+                "",
+                ActivityEvent::Deleted,
+            ),
+        ];
+
+        let flow = CumulativeFlow::collect(&db_state, Utc::now().date_naive());
+        let counts = |day: usize, status: Status| flow.days[day].counts[&status];
+
+        assert_eq!(flow.days.len(), 3);
+        assert_eq!(counts(0, Status::Open), 3);
+        assert_eq!(counts(1, Status::Open), 2);
+        assert_eq!(counts(1, Status::Closed), 1);
+        assert_eq!(counts(2, Status::Open), 1);
+        assert_eq!(counts(2, Status::Closed), 1);
+
+        let mut csv = vec![];
+        flow.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.lines().last().unwrap().ends_with(",1,0,0,1"));
+
+        let chart = flow.chart(60, 4);
+        assert_eq!(chart[0], "   3 |.. ");
+        assert_eq!(chart[3], "     |.##");
+        assert!(chart[5].ends_with(&Utc::now().date_naive().to_string()));
+    }
+}
@@ -0,0 +1,408 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use itertools::Itertools;
+
+use crate::db::JiraDatabase;
+use crate::models::{DBState, Epic, EpicId, Status, Story};
+
+/// A file format the board can be imported from. Importers only read the
+/// file into a draft; what is imported is decided on its preview.
+pub trait Importer {
+    /// Name the format is chosen by, e.g. with `import --format`.
+    fn name(&self) -> &'static str;
+    /// Extension of the files the format is guessed from.
+    fn extension(&self) -> &'static str;
+    fn read(&self, contents: &str) -> Result<Draft>;
+}
+
+/// Epics and stories read from an import file, not yet in the database.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Draft {
+    pub epics: Vec<DraftEpic>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DraftEpic {
+    pub name: String,
+    pub description: String,
+    pub status: Status,
+    pub stories: Vec<DraftStory>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DraftStory {
+    pub name: String,
+    pub description: String,
+    pub status: Status,
+}
+
+impl Draft {
+    pub fn story_count(&self) -> usize {
+        self.epics.iter().map(|epic| epic.stories.len()).sum()
+    }
+
+    /// Creates the drafted epics with their stories in one write and returns
+    /// the ids of the new epics.
+    pub fn commit(&self, db: &JiraDatabase) -> Result<Vec<EpicId>> {
+        db.transaction(|db| {
+            let mut epic_ids = vec![];
+            for draft in &self.epics {
+                let epic_id =
+                    db.create_epic(Epic::new(draft.name.clone(), draft.description.clone()))?;
+                if draft.status != Status::Open {
+                    db.update_epic_status(epic_id, draft.status.clone())?;
+                }
+                for story in &draft.stories {
+                    let story_id = db.create_story(
+                        Story::new(story.name.clone(), story.description.clone()),
+                        epic_id,
+                    )?;
+                    if story.status != Status::Open {
+                        db.update_story_status(story_id, story.status.clone())?;
+                    }
+                }
+                epic_ids.push(epic_id);
+            }
+            Ok(epic_ids)
+        })
+    }
+}
+
+/// The rows of a draft, numbered from 1 in the order they are shown, each of
+/// which can be left out of the import. Leaving out an epic leaves out its
+/// stories.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preview {
+    draft: Draft,
+    excluded: BTreeSet<usize>,
+}
+
+impl Preview {
+    pub fn new(draft: Draft) -> Self {
+        Self {
+            draft,
+            excluded: BTreeSet::new(),
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        self.draft.epics.len() + self.draft.story_count()
+    }
+
+    /// Selects the row if it was left out and leaves it out otherwise.
+    pub fn toggle(&mut self, row: usize) -> Result<()> {
+        if row == 0 || row > self.row_count() {
+            return Err(anyhow!(format!("no row {row}")));
+        }
+        if !self.excluded.remove(&row) {
+            self.excluded.insert(row);
+        }
+        Ok(())
+    }
+
+    /// Returns the epics and stories that are selected.
+    pub fn selected(&self) -> Draft {
+        let mut row = 0;
+        let mut epics = vec![];
+        for epic in &self.draft.epics {
+            row += 1;
+            let epic_selected = !self.excluded.contains(&row);
+            let mut selected = DraftEpic {
+                stories: vec![],
+                ..epic.clone()
+            };
+            for story in &epic.stories {
+                row += 1;
+                if epic_selected && !self.excluded.contains(&row) {
+                    selected.stories.push(story.clone());
+                }
+            }
+            if epic_selected {
+                epics.push(selected);
+            }
+        }
+        Draft { epics }
+    }
+}
+
+impl fmt::Display for Preview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut row = 0;
+        for epic in &self.draft.epics {
+            row += 1;
+            let epic_selected = !self.excluded.contains(&row);
+            let mark = |selected| if selected { "[x]" } else { "[ ]" };
+            writeln!(
+                f,
+                "{row:>4} {} epic  {} [{}]",
+                mark(epic_selected),
+                epic.name,
+                epic.status
+            )?;
+            for story in &epic.stories {
+                row += 1;
+                writeln!(
+                    f,
+                    "{row:>4} {}   story  {} [{}]",
+                    mark(epic_selected && !self.excluded.contains(&row)),
+                    story.name,
+                    story.status
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The epics and stories of another database file. Only names, descriptions
+/// and statuses are taken; `import-epic` copies an epic with its history.
+struct JsonImporter;
+
+impl Importer for JsonImporter {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+    fn read(&self, contents: &str) -> Result<Draft> {
+        let db_state: DBState = serde_json::from_str(contents)?;
+        let epics = db_state
+            .epics
+            .iter()
+            .sorted_by_key(|(id, _)| **id)
+            .map(|(_, epic)| DraftEpic {
+                name: epic.name.clone(),
+                description: epic.description.clone(),
+                status: epic.status.clone(),
+                stories: epic
+                    .stories
+                    .iter()
+                    .filter_map(|id| db_state.stories.get(id))
+                    .map(|story| DraftStory {
+                        name: story.name.clone(),
+                        description: story.description.clone(),
+                        status: story.status.clone(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        Ok(Draft { epics })
+    }
+}
+
+/// The tab separated listing written by `export --format tsv`: kind, id, epic
+/// id, status and name. Stories may come before or after their epic.
+struct TsvImporter;
+
+impl Importer for TsvImporter {
+    fn name(&self) -> &'static str {
+        "tsv"
+    }
+    fn extension(&self) -> &'static str {
+        "tsv"
+    }
+    fn read(&self, contents: &str) -> Result<Draft> {
+        let mut epic_ids = vec![];
+        let mut draft = Draft::default();
+        let mut stories = vec![];
+        for (number, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match parse_row(line).with_context(|| format!("line {}", number + 1))? {
+                Row::Epic(epic_id, epic) => {
+                    epic_ids.push(epic_id);
+                    draft.epics.push(epic);
+                }
+                Row::Story(epic_id, story) => stories.push((epic_id, story)),
+            }
+        }
+
+        for (epic_id, story) in stories {
+            let index = epic_ids
+                .iter()
+                .position(|id| *id == epic_id)
+                .ok_or_else(|| anyhow!(format!("epic not found: {epic_id}")))?;
+            draft.epics[index].stories.push(story);
+        }
+        Ok(draft)
+    }
+}
+
+enum Row {
+    Epic(u32, DraftEpic),
+    Story(u32, DraftStory),
+}
+
+fn parse_row(line: &str) -> Result<Row> {
+    let Some((kind, _id, epic_id, status, name)) = line.splitn(5, '\t').collect_tuple() else {
+        return Err(anyhow!("expected 5 tab separated columns"));
+    };
+    let epic_id = epic_id
+        .trim()
+        .parse()
+        .map_err(|_| anyhow!(format!("invalid epic id: {epic_id}")))?;
+    let status = status.parse()?;
+    let name = name.trim().to_owned();
+    match kind.trim() {
+        "epic" => Ok(Row::Epic(
+            epic_id,
+            DraftEpic {
+                name,
+                description: String::new(),
+                status,
+                stories: vec![],
+            },
+        )),
+        "story" => Ok(Row::Story(
+            epic_id,
+            DraftStory {
+                name,
+                description: String::new(),
+                status,
+            },
+        )),
+        _ => Err(anyhow!(format!("unknown kind of item: {kind}"))),
+    }
+}
+
+/// Every import format, the default one first. New formats are added here.
+pub fn importers() -> Vec<Box<dyn Importer>> {
+    vec![Box::new(JsonImporter), Box::new(TsvImporter)]
+}
+
+/// Returns the importer named `format`, or without one the importer for the
+/// extension of `input`, falling back to the default format.
+pub fn find_importer(format: Option<&str>, input: &str) -> Result<Box<dyn Importer>> {
+    let mut importers = importers();
+    let index = match format {
+        Some(format) => importers
+            .iter()
+            .position(|i| i.name().eq_ignore_ascii_case(format.trim()))
+            .ok_or_else(|| {
+                anyhow!(format!(
+                    "unknown import format '{format}', expected one of: {}",
+                    importers.iter().map(|i| i.name()).join(", ")
+                ))
+            })?,
+        None => {
+            let extension = Path::new(input).extension().and_then(|e| e.to_str());
+            importers
+                .iter()
+                .position(|i| extension.is_some_and(|ext| ext.eq_ignore_ascii_case(i.extension())))
+                .unwrap_or(0)
+        }
+    };
+    Ok(importers.swap_remove(index))
+}
+
+/// Reads `input` into a draft with the importer for it. Errors name the file
+/// and the format it was read as.
+pub fn read(format: Option<&str>, input: &str) -> Result<Draft> {
+    let importer = find_importer(format, input)?;
+    let contents = std::fs::read_to_string(input)
+        .with_context(|| format!("failed to read import file: {input}"))?;
+    importer
+        .read(&contents)
+        .with_context(|| format!("failed to import {input} as {}", importer.name()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_utils::MockDB;
+    use crate::export::find_exporter;
+
+    #[test]
+    fn tsv_import_should_read_an_exported_listing() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("Checkout".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("Pay".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.update_story_status(story_id, Status::InProgress)
+            .unwrap();
+        let mut listing = Vec::new();
+        find_exporter(Some("tsv"), "-")
+            .unwrap()
+            .export(&db.read_db().unwrap(), &mut listing)
+            .unwrap();
+
+        let draft = find_importer(None, "board.tsv")
+            .unwrap()
+            .read(&String::from_utf8(listing).unwrap())
+            .unwrap();
+        assert_eq!(
+            Preview::new(draft.clone()).to_string(),
+            "   1 [x] epic  Checkout [OPEN]\n   2 [x]   story  Pay [IN PROGRESS]\n"
+        );
+
+        let epic_ids = draft.commit(&db).unwrap();
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics.len(), 2);
+        let imported = &db_state.epics[&epic_ids[0]];
+        assert_eq!(imported.name, "Checkout");
+        assert_eq!(
+            db_state.stories[&imported.stories[0]].status,
+            Status::InProgress
+        );
+    }
+
+    #[test]
+    fn preview_should_leave_out_deselected_rows() {
+        let story = |name: &str| DraftStory {
+            name: name.to_owned(),
+            description: String::new(),
+            status: Status::Open,
+        };
+        let epic = |name: &str, stories| DraftEpic {
+            name: name.to_owned(),
+            description: String::new(),
+            status: Status::Open,
+            stories,
+        };
+        let mut preview = Preview::new(Draft {
+            epics: vec![
+                epic("Checkout", vec![story("Pay"), story("Refund")]),
+                epic("Search", vec![story("Filters")]),
+            ],
+        });
+
+        preview.toggle(3).unwrap();
+        preview.toggle(4).unwrap();
+        preview.toggle(2).unwrap();
+        preview.toggle(2).unwrap();
+        assert!(preview.toggle(6).is_err());
+        assert_eq!(
+            preview.to_string(),
+            "   1 [x] epic  Checkout [OPEN]\n   2 [x]   story  Pay [OPEN]\n   3 [ ]   story  Refund [OPEN]\n   4 [ ] epic  Search [OPEN]\n   5 [ ]   story  Filters [OPEN]\n"
+        );
+        assert_eq!(
+            preview.selected(),
+            Draft {
+                epics: vec![epic("Checkout", vec![story("Pay")])]
+            }
+        );
+    }
+
+    #[test]
+    fn tsv_import_should_report_the_failing_line() {
+        let importer = find_importer(Some("tsv"), "board.txt").unwrap();
+        let error = importer
+            .read("epic\t1\t1\tOpen\tCheckout\nstory\t2\t1\tDone\tPay\n")
+            .unwrap_err();
+        assert_eq!(format!("{error:#}"), "line 2: invalid status: Done");
+
+        let error = importer.read("story\t2\t7\tOpen\tPay\n").unwrap_err();
+        assert_eq!(error.to_string(), "epic not found: 7");
+        assert!(find_importer(Some("csv"), "board.csv").is_err());
+    }
+}
@@ -1,13 +1,221 @@
-use std::io;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crossterm::{
+    cursor,
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
+    execute,
+    terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+use crate::signals;
+
+static ALTERNATE_SCREEN: AtomicBool = AtomicBool::new(false);
+static MOUSE_CAPTURE: AtomicBool = AtomicBool::new(false);
+static REDRAW: AtomicBool = AtomicBool::new(false);
+
+/// How often the input checks for a requested redraw while waiting for keys.
+const FRAME_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Pages showing elapsed times are drawn again this often while idle.
+const CLOCK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Prefix of the input returned for a click, followed by the screen line.
+pub const CLICK_PREFIX: &str = "click:";
+
+/// Shows the UI on the terminal's alternate screen while it is alive, so the
+/// previous scrollback is restored on exit.
+pub struct AlternateScreen;
+
+impl AlternateScreen {
+    pub fn enter() -> io::Result<Option<Self>> {
+        if !io::stdout().is_terminal() {
+            return Ok(None);
+        }
+
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        ALTERNATE_SCREEN.store(true, Ordering::SeqCst);
+
+        Ok(Some(Self))
+    }
+}
+
+impl Drop for AlternateScreen {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+/// Leaves raw mode, mouse capture and the alternate screen if they are active.
+pub fn restore_terminal() {
+    let _ = terminal::disable_raw_mode();
+    if MOUSE_CAPTURE.swap(false, Ordering::SeqCst) {
+        let _ = execute!(io::stdout(), DisableMouseCapture);
+    }
+    if ALTERNATE_SCREEN.swap(false, Ordering::SeqCst) {
+        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+    }
+}
+
+/// Clears the terminal before a page is drawn, so the lines of its rows are
+/// known for mouse clicks.
+pub fn clear_screen() {
+    if io::stdout().is_terminal() {
+        let _ = execute!(
+            io::stdout(),
+            terminal::Clear(ClearType::All),
+            cursor::MoveTo(0, 0)
+        );
+    }
+}
 
 pub fn get_user_input() -> String {
-    let mut user_input = String::new();
+    read_input(false, false)
+}
 
-    io::stdin().read_line(&mut user_input).unwrap();
+/// Reads the input of a page. On a terminal, `j`/`k` and the arrow keys typed
+/// at the start of the line are returned right away as `j` or `k` so list
+/// selections move without pressing Enter, as are `>` and `<`. With `mouse`, the wheel moves the
+/// selection the same way and a left click returns [`CLICK_PREFIX`] followed
+/// by the clicked screen line. A redraw requested before anything was typed,
+/// e.g. by resizing the terminal, returns nothing, see [`take_redraw`].
+pub fn get_command_input(mouse: bool) -> String {
+    read_input(true, mouse)
+}
+
+fn read_input(selection_keys: bool, mouse: bool) -> String {
+    // skip the remaining questions of a prompt that was cancelled
+    if signals::is_interrupted() {
+        return String::new();
+    }
+
+    let user_input = if io::stdin().is_terminal() {
+        read_line_from_terminal(selection_keys, mouse).unwrap_or_else(|_| read_line_from_stdin())
+    } else {
+        read_line_from_stdin()
+    };
 
     user_input.trim().to_owned()
 }
 
+/// Asks for the current page to be drawn again without waiting for input, e.g.
+/// because what it shows changed in the background. Can be called from any thread.
+pub fn request_redraw() {
+    REDRAW.store(true, Ordering::SeqCst);
+}
+
+/// Returns whether a redraw was requested since the last call and resets the flag.
+pub fn take_redraw() -> bool {
+    REDRAW.swap(false, Ordering::SeqCst)
+}
+
 pub fn wait_for_key_press() {
-    io::stdin().read_line(&mut String::new()).unwrap();
+    get_user_input();
+}
+
+fn read_line_from_stdin() -> String {
+    let mut user_input = String::new();
+
+    io::stdin().read_line(&mut user_input).unwrap();
+
+    user_input
+}
+
+// Reads key events instead of relying on the console's line mode, which behaves
+// differently between cmd, PowerShell and unix terminals.
+fn read_line_from_terminal(selection_keys: bool, mouse: bool) -> io::Result<String> {
+    terminal::enable_raw_mode()?;
+    if mouse {
+        execute!(io::stdout(), EnableMouseCapture)?;
+        MOUSE_CAPTURE.store(true, Ordering::SeqCst);
+    }
+    let user_input = read_keys(selection_keys);
+    if MOUSE_CAPTURE.swap(false, Ordering::SeqCst) {
+        execute!(io::stdout(), DisableMouseCapture)?;
+    }
+    terminal::disable_raw_mode()?;
+    println!();
+
+    user_input
+}
+
+fn read_keys(selection_keys: bool) -> io::Result<String> {
+    let mut stdout = io::stdout();
+    let mut line = String::new();
+
+    let waiting_since = Instant::now();
+
+    loop {
+        if !event::poll(FRAME_INTERVAL)? {
+            if waiting_since.elapsed() >= CLOCK_INTERVAL {
+                request_redraw();
+            }
+            if selection_keys && line.is_empty() && REDRAW.load(Ordering::SeqCst) {
+                return Ok(String::new());
+            }
+            continue;
+        }
+
+        let (code, modifiers, kind) = match event::read()? {
+            Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind,
+                ..
+            }) => (code, modifiers, kind),
+            Event::Mouse(MouseEvent { kind, row, .. }) => match kind {
+                MouseEventKind::ScrollDown => return Ok("j".to_owned()),
+                MouseEventKind::ScrollUp => return Ok("k".to_owned()),
+                MouseEventKind::Down(MouseButton::Left) => {
+                    return Ok(format!("{CLICK_PREFIX}{row}"));
+                }
+                _ => continue,
+            },
+            Event::Resize(..) => {
+                request_redraw();
+                continue;
+            }
+            _ => continue,
+        };
+
+        // Windows reports both key presses and releases.
+        if kind == KeyEventKind::Release {
+            continue;
+        }
+
+        match code {
+            KeyCode::Down | KeyCode::Char('j') if selection_keys && line.is_empty() => {
+                return Ok("j".to_owned());
+            }
+            KeyCode::Up | KeyCode::Char('k') if selection_keys && line.is_empty() => {
+                return Ok("k".to_owned());
+            }
+            KeyCode::Char(c @ ('>' | '<')) if selection_keys && line.is_empty() => {
+                return Ok(c.to_string());
+            }
+            KeyCode::Enter => return Ok(line),
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                signals::interrupt();
+                return Ok(String::new());
+            }
+            KeyCode::Char(c) => {
+                line.push(c);
+                write!(stdout, "{c}")?;
+            }
+            KeyCode::Backspace if line.pop().is_some() => {
+                execute!(
+                    stdout,
+                    cursor::MoveLeft(1),
+                    terminal::Clear(ClearType::UntilNewLine)
+                )?;
+            }
+            _ => {}
+        }
+
+        stdout.flush()?;
+    }
 }
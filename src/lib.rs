@@ -0,0 +1,31 @@
+//! The board, its storage and the interactive UI of scrumtask. Other ratatui
+//! applications can embed a view of a board with [`ui::BoardWidget`].
+
+pub mod apply;
+#[cfg(feature = "async")]
+pub mod async_db;
+pub mod cli;
+pub mod closeout;
+pub mod config;
+pub mod db;
+pub mod demo;
+pub mod diff;
+pub mod digest;
+pub mod export;
+pub mod flow;
+pub mod import;
+pub mod io_utils;
+pub mod listing;
+pub mod middleware;
+pub mod models;
+pub mod navigator;
+pub mod onboarding;
+pub mod report;
+pub mod rules;
+pub mod signals;
+pub mod stats;
+pub mod sync;
+pub mod ui;
+pub mod verify;
+pub mod watch;
+pub mod web;
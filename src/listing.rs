@@ -0,0 +1,57 @@
+use std::io::{self, Write};
+
+use itertools::Itertools;
+
+use crate::models::DBState;
+
+/// Writes one tab separated line per item: kind, id, epic id, status and name.
+/// Stories follow the epic they belong to.
+pub fn write_listing(db_state: &DBState, prefix: &str, out: &mut impl Write) -> io::Result<()> {
+    for (epic_id, epic) in db_state.epics.iter().sorted_by_key(|(id, _)| **id) {
+        writeln!(
+            out,
+            "{prefix}epic\t{epic_id}\t{epic_id}\t{:?}\t{}",
+            epic.status, epic.name
+        )?;
+
+        for story_id in epic.stories.iter().sorted() {
+            if let Some(story) = db_state.stories.get(story_id) {
+                writeln!(
+                    out,
+                    "{prefix}story\t{story_id}\t{epic_id}\t{:?}\t{}",
+                    story.status, story.name
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Epic, EpicId, Status, Story, StoryId};
+
+    #[test]
+    fn write_listing_should_print_epics_with_their_stories() {
+        let mut db_state = DBState::new();
+        let mut epic = Epic::new("Epic".to_owned(), "".to_owned());
+        epic.stories = vec![StoryId(3), StoryId(2)];
+        db_state.epics.insert(EpicId(1), epic);
+        let mut story = Story::new("Second".to_owned(), "".to_owned());
+        story.status = Status::InProgress;
+        db_state.stories.insert(StoryId(2), story);
+        db_state
+            .stories
+            .insert(StoryId(3), Story::new("Third".to_owned(), "".to_owned()));
+
+        let mut out = Vec::new();
+        write_listing(&db_state, "", &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "epic\t1\t1\tOpen\tEpic\nstory\t2\t1\tInProgress\tSecond\nstory\t3\t1\tOpen\tThird\n"
+        );
+    }
+}
@@ -1,26 +1,467 @@
-use std::rc::Rc;
+use std::io::{self, IsTerminal};
+use std::sync::Arc;
 
-mod models;
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use clap::Parser;
 
-mod db;
-use anyhow::Result;
-use db::*;
+use scrumtask_cli::cli::{Cli, Command, ItemKind};
+use scrumtask_cli::config::Config;
+use scrumtask_cli::db::*;
+use scrumtask_cli::diff::BoardDiff;
+use scrumtask_cli::digest::Digest;
+use scrumtask_cli::flow::CumulativeFlow;
+use scrumtask_cli::io_utils::*;
+use scrumtask_cli::models::{EpicId, RecurringStory, Sprint, Status, Story, StoryId};
+use scrumtask_cli::navigator::*;
+use scrumtask_cli::onboarding::Onboarding;
+use scrumtask_cli::stats::BoardStats;
+use scrumtask_cli::{
+    apply, demo, export, import, io_utils, listing, middleware, models, report, rules, signals,
+    sync, ui, verify, watch, web,
+};
 
-mod ui;
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    // an invalid config file is reported by the commands that use it
+    let config = Config::load(&cli.config).ok();
+    if let Some(config) = &config {
+        Status::set_labels(config.status_labels.clone());
+    }
+    let user = config.clone().unwrap_or_default().acting_user();
+    let paths = cli.db_paths(config.and_then(|config| config.database));
 
-mod io_utils;
-use io_utils::*;
+    match cli.command {
+        None if !io::stdout().is_terminal() => print_listing(&paths),
+        None if !cli.has_db()
+            && io::stdin().is_terminal()
+            && Onboarding::is_first_run(&cli.config) =>
+        {
+            let path = Onboarding::ask().apply(&cli.config)?;
+            run_interactive(&[path], &cli.config)
+        }
+        None => run_interactive(&paths, &cli.config),
+        Some(Command::Stats { json }) => {
+            let mut by_path = serde_json::Map::new();
+            for path in &paths {
+                let stats = BoardStats::collect(&read_db_file(path)?, Some(path));
+                if json {
+                    by_path.insert(path.clone(), serde_json::to_value(&stats)?);
+                } else {
+                    if paths.len() > 1 {
+                        println!("==> {path} <==");
+                    }
+                    println!("{stats}");
+                }
+            }
+            if json {
+                // several boards are printed as one object keyed by path
+                let output = match paths.as_slice() {
+                    [path] => by_path.remove(path).unwrap_or_default(),
+                    _ => serde_json::Value::Object(by_path),
+                };
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            }
+            Ok(())
+        }
+        Some(Command::Flow { csv }) => {
+            let db_state = read_db_file(&paths[0])?;
+            let flow = CumulativeFlow::collect(&db_state, Utc::now().date_naive());
+            if csv {
+                flow.write_csv(&mut io::stdout().lock())?;
+            } else {
+                for line in flow.chart(60, 12) {
+                    println!("{line}");
+                }
+            }
+            Ok(())
+        }
+        Some(Command::Compact { force }) => {
+            let config = Config::load(&cli.config)?;
+            let has_peers = config.remote.is_some() || config.peer_secret().is_ok();
+            for path in &paths {
+                if !force && (has_peers || sync::has_base(path)) {
+                    return Err(anyhow!(format!(
+                        "{path} is synced with other copies, which would take renumbered items for deleted and new ones; sync and compact every copy, then pass --force"
+                    )));
+                }
+            }
+            for path in &paths {
+                let mapping = JiraDatabase::new(path)?.compact_ids()?;
+                watch::renumber_seen(path, &mapping)?;
+                let changed = mapping.iter().filter(|(old, new)| old != new).count();
+                println!("{path}: renumbered {changed} of {} items", mapping.len());
 
-mod navigator;
-use navigator::*;
+                // captured stories go to the inbox of the first board
+                if let Some(inbox) = config.inbox_epic.filter(|_| *path == paths[0]) {
+                    let renumbered = mapping.get(&inbox.0).copied().map(EpicId);
+                    if renumbered != Some(inbox) {
+                        Config::save_inbox_epic(&cli.config, renumbered)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+        Some(Command::Init { demo }) => {
+            for path in &paths {
+                let db = JiraDatabase::new(path)?;
+                if demo {
+                    demo::seed(&db)?;
+                }
+                println!("{path}: initialized");
+            }
+            Ok(())
+        }
+        Some(Command::Recur {
+            epic_id,
+            name,
+            every,
+            description,
+        }) => JiraDatabase::new(&paths[0])?.add_recurring_story(RecurringStory::new(
+            name,
+            description,
+            epic_id,
+            every,
+        )),
+        Some(Command::SetIdScheme { scheme }) => {
+            for path in &paths {
+                JiraDatabase::new(path)?.set_id_scheme(scheme)?;
+            }
+            Ok(())
+        }
+        Some(Command::StartSprint { name, capacity }) => {
+            JiraDatabase::new(&paths[0])?.start_sprint(Sprint::new(name, capacity))?;
+            Ok(())
+        }
+        Some(Command::Edit {
+            kind,
+            id,
+            name,
+            description,
+            status,
+            if_version,
+        }) => {
+            if name.is_none() && description.is_none() && status.is_none() {
+                return Err(anyhow!(
+                    "nothing to change, pass --name, --description or --status"
+                ));
+            }
 
-fn main() -> Result<()> {
-    // TODO: create database and navigator
-    let db = Rc::new(JiraDatabase::new("./db.json")?);
-    let mut nav = Navigator::new(db);
+            let db = JiraDatabase::new(&paths[0])?.with_user(user);
+            let changes = ItemChanges {
+                name,
+                description,
+                status,
+                expected_version: if_version,
+            };
+            match kind {
+                ItemKind::Epic => db.update_epic(EpicId(id), changes),
+                ItemKind::Story => db.update_story(StoryId(id), changes),
+            }
+        }
+        Some(Command::Apply { file }) => {
+            let config = Config::load(&cli.config)?;
+            let contents = std::fs::read_to_string(&file)
+                .with_context(|| format!("failed to read operations file: {file}"))?;
+            let operations = apply::parse(&contents)
+                .with_context(|| format!("failed to parse operations file: {file}"))?;
+            let db = JiraDatabase::new(&paths[0])?.with_user(user);
+            let refs = apply::apply(&db, &operations, &config.custom_fields)?;
+            println!("applied {} operations", operations.len());
+            for (reference, id) in refs {
+                println!("{reference}\t{id}");
+            }
+            Ok(())
+        }
+        Some(Command::MoveStories { from, to, status }) => {
+            let moved = JiraDatabase::new(&paths[0])?
+                .with_user(user)
+                .move_stories(from, to, status)?;
+            println!(
+                "moved {} stories from epic {from} to epic {to}",
+                moved.len()
+            );
+            Ok(())
+        }
+        Some(Command::Capture { text, description }) => {
+            let config = Config::load(&cli.config)?;
+            let db = JiraDatabase::new(&paths[0])?.with_user(user);
+            let epic_id = db.inbox_epic(config.inbox_epic)?;
+            let story_id = db.create_story(Story::new(text, description), epic_id)?;
+            println!("{}", db.read_db()?.story_label(epic_id, story_id));
+            Ok(())
+        }
+        Some(Command::Schema) => {
+            let schema = schemars::schema_for!(models::DBState);
+            println!("{}", serde_json::to_string_pretty(&schema)?);
+            Ok(())
+        }
+        Some(Command::ImportEpic { file, epic, name }) => {
+            let source = read_db_file(&file)?;
+            let db = JiraDatabase::new(&paths[0])?.with_user(user);
+            let epic_id = db.import_epic(&source, epic, name)?;
+            println!(
+                "{}: imported as epic {}",
+                paths[0],
+                db.read_db()?.epic_label(epic_id)
+            );
+            Ok(())
+        }
+        Some(Command::Import { file, format, yes }) => {
+            let mut preview = import::Preview::new(import::read(format.as_deref(), &file)?);
+            if !yes {
+                loop {
+                    print!("{preview}");
+                    println!(
+                        "Type row numbers to deselect or select them, y to import the selected rows into {} or nothing to cancel:",
+                        paths[0]
+                    );
+                    let input = get_user_input();
+                    match input.as_str() {
+                        "" => {
+                            println!("nothing imported");
+                            return Ok(());
+                        }
+                        "y" | "Y" => break,
+                        _ => {
+                            for row in input.split([' ', ',']).filter(|r| !r.is_empty()) {
+                                if let Err(e) = row
+                                    .parse()
+                                    .map_err(|_| anyhow!(format!("not a row number: {row}")))
+                                    .and_then(|row| preview.toggle(row))
+                                {
+                                    println!("{e}");
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            let draft = preview.selected();
+            draft.commit(&JiraDatabase::new(&paths[0])?.with_user(user))?;
+            println!(
+                "{}: imported {} epics and {} stories",
+                paths[0],
+                draft.epics.len(),
+                draft.story_count()
+            );
+            Ok(())
+        }
+        Some(Command::Verify { file }) => {
+            let contents = std::fs::read_to_string(&file)?;
+            let issues = verify::verify(&contents)?;
+            for issue in &issues {
+                println!("{file}: {issue}");
+            }
+            // files written before a field was added are fine
+            let problems = issues.iter().filter(|i| i.kind.is_problem()).count();
+            if problems > 0 {
+                return Err(anyhow!("{file}: found {problems} problems"));
+            }
+            println!("{file}: ok");
+            Ok(())
+        }
+        Some(Command::Diff { old, new, json }) => {
+            let new = match new {
+                Some(new) => read_db_file(&new)?,
+                None => read_db_file(&paths[0])?,
+            };
+            let old = match old {
+                Some(old) => old,
+                None => list_snapshots(&paths[0])?
+                    .pop()
+                    .map(|(_, path)| path.to_string_lossy().into_owned())
+                    .ok_or_else(|| anyhow!("{}: no snapshot to compare with", paths[0]))?,
+            };
+            let diff = BoardDiff::collect(&read_db_file(&old)?, &new);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+            } else {
+                print!("{diff}");
+            }
+            Ok(())
+        }
+        Some(Command::Export {
+            output,
+            format,
+            anonymize,
+            epic,
+        }) => {
+            let exporter = export::find_exporter(format.as_deref(), &output)?;
+            let db_state = read_db_file(&paths[0])?;
+            let db_state = match epic {
+                Some(epic_id) => export::extract_epic(&db_state, epic_id)?,
+                None => db_state,
+            };
+            let db_state = if anonymize {
+                export::anonymize(&db_state)
+            } else {
+                db_state
+            };
+            let mut file = std::fs::File::create(&output)
+                .with_context(|| format!("failed to create export file: {output}"))?;
+            exporter.export(&db_state, &mut file)
+        }
+        Some(Command::Digest { print }) => {
+            let db_state = read_db_file(&paths[0])?;
+            let digest = Digest::collect(&db_state, Utc::now().date_naive());
+            if print {
+                print!("{digest}");
+                return Ok(());
+            }
+            let config = Config::load(&cli.config)?;
+            let webhook = config.digest_webhook.as_ref().ok_or_else(|| {
+                anyhow!(
+                    "no webhook configured, add a \"digest_webhook\" section to the config file"
+                )
+            })?;
+            digest.post(webhook)?;
+            println!("{}: digest posted", paths[0]);
+            Ok(())
+        }
+        Some(Command::Report { email }) => {
+            let db_state = read_db_file(&paths[0])?;
+            if !email {
+                print!("{}", web::render(&db_state));
+                return Ok(());
+            }
+            let config = Config::load(&cli.config)?;
+            let email = config.report_email.as_ref().ok_or_else(|| {
+                anyhow!(
+                    "no mail server configured, add a \"report_email\" section to the config file"
+                )
+            })?;
+            let message = report::message(email, &paths[0], &db_state, Utc::now().date_naive())?;
+            report::send(email, &message)?;
+            println!("{}: report sent to {}", paths[0], email.to.join(", "));
+            Ok(())
+        }
+        Some(Command::View { addr }) => web::serve(&paths[0], &addr),
+        Some(Command::Sync { listen, connect }) => {
+            let merged = match (listen, connect) {
+                (Some(addr), _) => {
+                    let secret = Config::load(&cli.config)?.peer_secret()?;
+                    sync::listen(&paths[0], &addr, &secret)?
+                }
+                (_, Some(addr)) => {
+                    let secret = Config::load(&cli.config)?.peer_secret()?;
+                    sync::connect(&paths[0], &addr, &secret)?
+                }
+                (None, None) => {
+                    let config = Config::load(&cli.config)?;
+                    let remote = config.remote.as_ref().ok_or_else(|| {
+                        anyhow!("no remote configured, add a \"remote\" section to the config file")
+                    })?;
+                    let outcome =
+                        sync::sync(&paths[0], sync::open_remote(remote)?.as_ref(), Utc::now())?;
+                    println!("{}: {outcome}", paths[0]);
+                    return Ok(());
+                }
+            };
+            println!(
+                "{}: merged, {} epics and {} stories",
+                paths[0],
+                merged.epics.len(),
+                merged.stories.len()
+            );
+            Ok(())
+        }
+    }
+}
+
+fn print_listing(paths: &[String]) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    for path in paths {
+        let prefix = if paths.len() > 1 {
+            format!("{path}\t")
+        } else {
+            String::new()
+        };
+        let db_state = read_db_file(path)?;
+        match listing::write_listing(&db_state, &prefix, &mut stdout) {
+            // the reader (e.g. `head`) is done, which is not an error
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+            result => result?,
+        }
+    }
+    Ok(())
+}
+
+fn run_interactive(paths: &[String], config_path: &str) -> Result<()> {
+    let config = Arc::new(Config::load(config_path)?);
+    let mouse = config.mouse;
+    let action_log = config.action_log.clone();
+    let mut escalated = vec![];
+    for path in paths {
+        let db = JiraDatabase::new(path)?.with_user(config.acting_user());
+        db.take_due_snapshot(&config.snapshots, Utc::now())?;
+        db.create_due_recurring_stories(Utc::now())?;
+        let changes = rules::apply(&db, &config.rules, &config.custom_fields, Utc::now())?;
+        for item in rules::escalations(&changes, &db.read_db()?) {
+            escalated.push(if paths.len() > 1 {
+                format!("{path}: {item}")
+            } else {
+                item
+            });
+        }
+    }
+    signals::install()?;
+    let _screen = AlternateScreen::enter()?;
+    let mut nav = if paths.len() > 1 {
+        Navigator::with_boards(
+            paths
+                .iter()
+                .map(|p| Board::open(p, config.autosave_debounce(), config.acting_user()))
+                .collect::<Result<Vec<_>>>()?,
+            config,
+        )?
+    } else {
+        let db = Arc::new(
+            JiraDatabase::new(&paths[0])?
+                .with_debounce(config.autosave_debounce())
+                .with_user(config.acting_user()),
+        );
+        JiraDatabase::flush_on_exit(&db);
+        let sync_worker = match &config.remote {
+            Some(remote) => Some(sync::SyncWorker::start(
+                Arc::clone(&db),
+                sync::open_remote(remote)?,
+                config.sync_interval(),
+            )),
+            None => None,
+        };
+        let mut nav = Navigator::new(db, config);
+        if let Some(worker) = sync_worker {
+            nav.set_sync_worker(worker);
+        }
+        nav
+    };
+    nav.set_config_path(config_path);
+    if let Some(file_path) = &action_log {
+        nav.add_middleware(Box::new(middleware::ActionLog {
+            file_path: file_path.clone(),
+        }));
+    }
+    // watched stories are only tracked for a single board
+    let watched_board = (paths.len() == 1).then(|| paths[0].as_str());
+    if let Some(path) = watched_board {
+        let db_state = read_db_file(path)?;
+        let changes = watch::watched_changes(path, &db_state)?;
+        if !changes.is_empty() {
+            nav.dispatch(models::Action::NavigateToWatchedChanges { changes })?;
+        }
+        watch::mark_seen(path, &db_state)?;
+    }
+    if !escalated.is_empty() {
+        nav.dispatch(models::Action::NavigateToEscalatedItems { items: escalated })?;
+    }
 
-    loop {
+    let result = loop {
         // clearscreen::clear().unwrap();
+        // clicks are mapped to rows by their screen line, which needs a fresh screen
+        if mouse && !ui::take_message_shown() {
+            clear_screen();
+        }
 
         // 1. get current page from navigator. If there is no current page exit the loop.
         let page = match nav.get_current_page() {
@@ -29,16 +470,43 @@ fn main() -> Result<()> {
                 break Ok(());
             }
         };
-        // 2. render page
+        if let Some(status) = nav.sync_status() {
+            println!("{}", status.line(Utc::now()));
+        }
+        if let Some(count) = nav.recorded_actions() {
+            println!("recording macro: {count} actions");
+        }
+        // 2. render page and the footer with its shortcuts, which covers any redraw asked for until now
+        io_utils::take_redraw();
         if let Err(e) = page.draw_page() {
             eprintln!("failed to render page: {e}");
             wait_for_key_press();
             break Err(e);
         }
+        println!("{}", ui::get_footer_string(&page.shortcuts()));
         // 3. get user input
-        let input = io_utils::get_user_input();
-        // 4. pass input to page's input handler
-        let action = match page.handle_input(&input.trim()) {
+        // only scripted input arrives fast enough to benefit from coalesced writes
+        if io::stdin().is_terminal() {
+            if let Err(e) = nav.flush() {
+                eprintln!("failed to save changes: {e}");
+                wait_for_key_press();
+                break Err(e);
+            }
+        }
+        let input = io_utils::get_command_input(mouse);
+        if signals::take_interrupt() {
+            continue;
+        }
+        // the page changed in the background or the terminal was resized
+        if input.is_empty() && io_utils::take_redraw() {
+            clear_screen();
+            continue;
+        }
+        // 4. pass input to page's input handler, unless it records or replays a macro
+        let action = match nav
+            .macro_action(input.trim())
+            .map_or_else(|| page.handle_input(input.trim()), |a| Ok(Some(a)))
+        {
             Err(e) => {
                 eprintln!("failed to handle input '{input}': {e}");
                 wait_for_key_press();
@@ -49,11 +517,20 @@ fn main() -> Result<()> {
         // 5. if the page's input handler returns an action let the navigator process the action
         if let Some(a) = action {
             let action = a.clone();
-            if let Err(e) = nav.handle_action(a) {
+            if let Err(e) = nav.dispatch(a) {
+                if e.is::<signals::Cancelled>() {
+                    continue;
+                }
                 eprintln!("failed to handle action '{action:?}': {e}");
                 wait_for_key_press();
                 break Err(e);
             }
         }
+    };
+    // the user's own changes in this session aren't news next time
+    if let Some(path) = watched_board {
+        nav.flush()?;
+        watch::mark_seen(path, &read_db_file(path)?)?;
     }
+    result
 }
@@ -0,0 +1,95 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde_json::json;
+
+use crate::models::Action;
+use crate::navigator::changes_board;
+
+/// Observes the actions the navigator handles, e.g. to log them, and may veto
+/// them before they are handled.
+pub trait Middleware {
+    /// Called before the action is handled. Returning `false` skips the action
+    /// and its follow-ups.
+    fn before(&mut self, _action: &Action) -> Result<bool> {
+        Ok(true)
+    }
+
+    /// Called after the action was handled, with the error it failed with.
+    /// Errors returned here are shown but leave the action as it was handled.
+    fn after(&mut self, _action: &Action, _error: Option<&anyhow::Error>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Appends every action that changes the board to a file, one JSON object per
+/// line with the time, the action and the error it failed with.
+pub struct ActionLog {
+    pub file_path: String,
+}
+
+impl Middleware for ActionLog {
+    fn after(&mut self, action: &Action, error: Option<&anyhow::Error>) -> Result<()> {
+        if !changes_board(action) {
+            return Ok(());
+        }
+
+        let mut entry = json!({ "at": Utc::now(), "action": action });
+        if let Some(error) = error {
+            entry["error"] = json!(format!("{error:#}"));
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.file_path)
+            .with_context(|| format!("failed to open action log: {}", self.file_path))?;
+        writeln!(file, "{entry}")
+            .with_context(|| format!("failed to write action log: {}", self.file_path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EpicId, Status, StoryId};
+
+    #[test]
+    fn action_log_should_append_changes_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("actions.jsonl");
+        let mut log = ActionLog {
+            file_path: path.to_str().unwrap().to_owned(),
+        };
+
+        log.after(&Action::NavigateToEpicDetail { epic_id: EpicId(1) }, None)
+            .unwrap();
+        log.after(
+            &Action::SetStoryStatus {
+                story_id: StoryId(2),
+                status: Status::Resolved,
+            },
+            None,
+        )
+        .unwrap();
+        log.after(
+            &Action::DeleteEpic { epic_id: EpicId(3) },
+            Some(&anyhow::anyhow!("epic not found: 3")),
+        )
+        .unwrap();
+
+        let entries = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0]["action"],
+            json!({ "set_story_status": { "story_id": 2, "status": "Resolved" } })
+        );
+        assert!(entries[0].get("error").is_none());
+        assert_eq!(entries[1]["error"], "epic not found: 3");
+    }
+}
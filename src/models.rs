@@ -1,21 +1,204 @@
+use chrono::{DateTime, Duration, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    str::FromStr,
+    sync::{PoisonError, RwLock, RwLockReadGuard},
+};
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+use crate::config::DensityPage;
+use crate::diff::BoardDiff;
+use anyhow::anyhow;
+
+/// Id of an epic. Epics and stories are numbered from one counter, so the id
+/// types keep one from being passed where the other is expected.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(transparent)]
+pub struct EpicId(pub u32);
+
+/// Id of a story, see [`EpicId`].
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(transparent)]
+pub struct StoryId(pub u32);
+
+impl Display for EpicId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Display for StoryId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for EpicId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl FromStr for StoryId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+/// What a page asks the navigator to do. Actions can be recorded into macros,
+/// which are stored in the config file.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Action {
-    NavigateToEpicDetail { epic_id: u32 },
-    NavigateToStoryDetail { epic_id: u32, story_id: u32 },
+    NavigateToBoard {
+        board_index: usize,
+    },
+    NavigateToEpicDetail {
+        epic_id: EpicId,
+    },
+    NavigateToStoryDetail {
+        epic_id: EpicId,
+        story_id: StoryId,
+    },
     NavigateToPreviousPage,
+    NavigateToHelp,
+    NavigateToActivity {
+        epic_id: Option<EpicId>,
+    },
+    NavigateToRecent,
+    NavigateToFlow,
+    NavigateToInbox,
+    NavigateToSprintPlanning,
+    NavigateToRetrospectives,
+    NavigateToEstimation {
+        epic_id: EpicId,
+    },
+    NavigateToSyncQueue,
+    NavigateToFocus {
+        epic_id: EpicId,
+        story_id: StoryId,
+    },
     CreateEpic,
-    UpdateEpicStatus { epic_id: u32 },
-    DeleteEpic { epic_id: u32 },
-    CreateStory { epic_id: u32 },
-    UpdateStoryStatus { story_id: u32 },
-    DeleteStory { epic_id: u32, story_id: u32 },
+    CreateEpicAndOpen,
+    UpdateEpicStatus {
+        epic_id: EpicId,
+    },
+    SetEpicStatus {
+        epic_id: EpicId,
+        status: Status,
+    },
+    OfferCloseOut {
+        epic_id: EpicId,
+    },
+    DeleteEpic {
+        epic_id: EpicId,
+    },
+    ToggleEpicPin {
+        epic_id: EpicId,
+    },
+    ToggleStoryWatch {
+        story_id: StoryId,
+    },
+    /// Lists what changed on the watched stories since the last session.
+    #[serde(skip)]
+    NavigateToWatchedChanges {
+        changes: BoardDiff,
+    },
+    /// Lists the stories the rules escalated at startup.
+    #[serde(skip)]
+    NavigateToEscalatedItems {
+        items: Vec<String>,
+    },
+    AddEpicNote {
+        epic_id: EpicId,
+    },
+    CreateStory {
+        epic_id: EpicId,
+    },
+    CreateStoryAndOpen {
+        epic_id: EpicId,
+    },
+    CreateStories {
+        epic_id: EpicId,
+    },
+    UpdateStoryStatus {
+        story_id: StoryId,
+    },
+    SetStoryStatus {
+        story_id: StoryId,
+        status: Status,
+    },
+    DeleteStory {
+        epic_id: EpicId,
+        story_id: StoryId,
+    },
+    /// Asks what blocks a story and records it, or clears the blocker.
+    BlockStory {
+        story_id: StoryId,
+    },
+    MergeStory {
+        story_id: StoryId,
+    },
+    CloseSprint,
+    ToggleSprintStory {
+        story_id: StoryId,
+    },
+    EditStoryFields {
+        story_id: StoryId,
+    },
+    /// Checks or unchecks an acceptance criterion, counted from 0.
+    ToggleCriterion {
+        story_id: StoryId,
+        index: usize,
+    },
+    EstimateStory {
+        story_id: StoryId,
+        points: u32,
+    },
+    MoveStory {
+        story_id: StoryId,
+        to_epic_id: EpicId,
+    },
+    /// Deletes a story without leaving the current page.
+    DiscardStory {
+        epic_id: EpicId,
+        story_id: StoryId,
+    },
+    /// Syncs with the remote right away instead of at the next interval.
+    SyncNow,
+    DiscardConflictCopy {
+        path: String,
+    },
+    MoveStories {
+        from_epic_id: EpicId,
+        to_epic_id: EpicId,
+        status: Option<Status>,
+    },
+    /// Switches the density of all pages of a type and keeps it in the config file.
+    ToggleDensity {
+        page: DensityPage,
+    },
+    /// Starts recording the actions that follow, or stops and saves them as a macro.
+    ToggleMacroRecording,
+    ReplayMacro {
+        key: String,
+    },
     Exit,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub enum Status {
     Open,
     InProgress,
@@ -23,58 +206,446 @@ pub enum Status {
     Closed,
 }
 
-impl Display for Status {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Status {
+    pub const ALL: [Self; 4] = [Self::Open, Self::InProgress, Self::Resolved, Self::Closed];
+
+    /// Returns the status after this one in the workflow, if any.
+    pub fn advanced(&self) -> Option<Self> {
+        match self {
+            Self::Open => Some(Self::InProgress),
+            Self::InProgress => Some(Self::Resolved),
+            Self::Resolved => Some(Self::Closed),
+            Self::Closed => None,
+        }
+    }
+
+    /// Returns the status before this one in the workflow, if any.
+    pub fn reverted(&self) -> Option<Self> {
         match self {
-            Self::Open => write!(f, "OPEN"),
-            Self::InProgress => write!(f, "IN PROGRESS"),
-            Self::Resolved => write!(f, "RESOLVED"),
-            Self::Closed => write!(f, "Closed"),
+            Self::Open => None,
+            Self::InProgress => Some(Self::Open),
+            Self::Resolved => Some(Self::InProgress),
+            Self::Closed => Some(Self::Resolved),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+/// Shared by all threads, e.g. the sync worker and page loaders.
+/// Custom names of statuses, keyed by the status they rename.
+pub type StatusLabels = BTreeMap<Status, String>;
+
+static STATUS_LABELS: RwLock<StatusLabels> = RwLock::new(BTreeMap::new());
+
+impl Status {
+    /// Replaces the labels statuses are displayed and entered with, e.g.
+    /// `DOING` for `IN PROGRESS`. Stored values are not affected.
+    pub fn set_labels(labels: StatusLabels) {
+        *STATUS_LABELS
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = labels;
+    }
+
+    fn labels() -> RwLockReadGuard<'static, StatusLabels> {
+        STATUS_LABELS.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the label of the status in `labels`, or else its built-in name.
+    fn label(&self, labels: &StatusLabels) -> String {
+        if let Some(label) = labels.get(self) {
+            return label.clone();
+        }
+
+        match self {
+            Self::Open => "OPEN",
+            Self::InProgress => "IN PROGRESS",
+            Self::Resolved => "RESOLVED",
+            Self::Closed => "Closed",
+        }
+        .to_owned()
+    }
+
+    /// Parses a status by its label in `labels`, its name, short name or number.
+    fn parse_labelled(s: &str, labels: &StatusLabels) -> Result<Self, anyhow::Error> {
+        let normalized = s.trim().to_lowercase().replace([' ', '_'], "-");
+        let custom = Self::ALL.into_iter().find(|status| {
+            labels.get(status).is_some_and(|label| {
+                label.trim().to_lowercase().replace([' ', '_'], "-") == normalized
+            })
+        });
+        if let Some(status) = custom {
+            return Ok(status);
+        }
+
+        match normalized.as_str() {
+            "1" | "o" | "open" => Ok(Self::Open),
+            "2" | "ip" | "in-progress" | "inprogress" => Ok(Self::InProgress),
+            "3" | "r" | "resolved" => Ok(Self::Resolved),
+            "4" | "c" | "closed" => Ok(Self::Closed),
+            _ => Err(anyhow!("invalid status: {s}")),
+        }
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label(&Self::labels()))
+    }
+}
+
+impl FromStr for Status {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_labelled(s, &Self::labels())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
 pub struct Epic {
     pub name: String,
     pub description: String,
     pub status: Status,
-    pub stories: Vec<u32>,
+    pub stories: Vec<StoryId>,
+    #[serde(default)]
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub status_changed_at: DateTime<Utc>,
+    #[serde(default)]
+    pub last_story_number: u32,
+    /// Incremented on every change, 0 for epics created before versioning.
+    #[serde(default)]
+    pub version: u32,
+    /// Pinned epics are listed in their own section at the top of the home page.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Dated journal entries, oldest first. Entries are only ever appended.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<Note>,
+    /// User who made the last change, if it was made by a known user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+pub struct Note {
+    pub at: DateTime<Utc>,
+    pub text: String,
 }
 
 impl Epic {
     pub fn new(name: String, description: String) -> Self {
+        let now = Utc::now();
         Self {
             name,
             description,
             status: Status::Open,
             stories: vec![],
+            updated_at: now,
+            status_changed_at: now,
+            last_story_number: 0,
+            version: 1,
+            pinned: false,
+            notes: vec![],
+            updated_by: None,
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
 pub struct Story {
     pub name: String,
     pub description: String,
     pub status: Status,
+    #[serde(default)]
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub status_changed_at: DateTime<Utc>,
+    /// Number of the story within its epic, 0 for stories created before numbering.
+    #[serde(default)]
+    pub number: u32,
+    /// Incremented on every change, 0 for stories created before versioning.
+    #[serde(default)]
+    pub version: u32,
+    /// What keeps the story from moving on, if it is blocked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocker: Option<Blocker>,
+    /// Estimated effort in story points, if the story was estimated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub points: Option<u32>,
+    /// Values of the custom fields declared in the config, by field name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+    /// Watched stories are reported at startup when someone else changed them.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub watched: bool,
+    /// User who made the last change, if it was made by a known user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Blocker {
+    Story(StoryId),
+    Reason(String),
+}
+
+impl FromStr for Blocker {
+    type Err = anyhow::Error;
+
+    /// Parses a story id, optionally written as `:id:`, or else a free-text reason.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(anyhow!("a blocker needs a story id or a reason"));
+        }
+
+        let id = s.strip_prefix(':').and_then(|s| s.strip_suffix(':'));
+        match id.unwrap_or(s).parse() {
+            Ok(story_id) => Ok(Self::Story(story_id)),
+            Err(_) => Ok(Self::Reason(s.to_owned())),
+        }
+    }
+}
+
+impl Blocker {
+    /// Renumbers a blocking story with the ids of a copied epic. Stories left
+    /// behind can't be referenced anymore and become a reason naming them.
+    pub fn remapped(&self, ids: &HashMap<u32, u32>) -> Self {
+        match self {
+            Self::Story(story_id) => match ids.get(&story_id.0) {
+                Some(id) => Self::Story(StoryId(*id)),
+                None => Self::Reason(format!("story {story_id}")),
+            },
+            Self::Reason(_) => self.clone(),
+        }
+    }
 }
 
 impl Story {
     pub fn new(name: String, description: String) -> Self {
+        let now = Utc::now();
         Self {
             name,
             description,
             status: Status::Open,
+            updated_at: now,
+            status_changed_at: now,
+            number: 0,
+            version: 1,
+            blocker: None,
+            points: None,
+            fields: BTreeMap::new(),
+            watched: false,
+            updated_by: None,
+        }
+    }
+
+    /// Positions of the `[ ]` / `[x]` boxes in the description, which serve as
+    /// the story's acceptance criteria, and whether they are checked. Boxes may
+    /// be on separate lines or inline, as descriptions entered in the prompt are
+    /// single-line.
+    fn checklist_boxes(&self) -> Vec<(usize, bool)> {
+        self.description
+            .match_indices('[')
+            .filter_map(|(i, _)| match self.description.get(i..i + 3) {
+                Some("[ ]") => Some((i, false)),
+                Some("[x]" | "[X]") => Some((i, true)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Counts the checked and total acceptance criteria.
+    pub fn checklist_progress(&self) -> Option<(usize, usize)> {
+        let boxes = self.checklist_boxes();
+        if boxes.is_empty() {
+            return None;
+        }
+        Some((boxes.iter().filter(|(_, done)| *done).count(), boxes.len()))
+    }
+
+    /// Returns the acceptance criteria with their text, which runs up to the
+    /// next box or the end of the line.
+    pub fn checklist(&self) -> Vec<(bool, String)> {
+        let boxes = self.checklist_boxes();
+        boxes
+            .iter()
+            .enumerate()
+            .map(|(n, (i, done))| {
+                let rest = &self.description[i + 3..];
+                let end = boxes
+                    .get(n + 1)
+                    .map_or(rest.len(), |(next, _)| next - i - 3)
+                    .min(rest.find('\n').unwrap_or(rest.len()));
+                (*done, rest[..end].trim().to_owned())
+            })
+            .collect()
+    }
+
+    /// Checks or unchecks the acceptance criterion at `index` and returns
+    /// whether it is checked now, or `None` if there is no such criterion.
+    pub fn toggle_checklist_item(&mut self, index: usize) -> Option<bool> {
+        let (i, done) = *self.checklist_boxes().get(index)?;
+        let mark = if done { " " } else { "x" };
+        self.description.replace_range(i + 1..i + 2, mark);
+        Some(!done)
+    }
+}
+
+/// How ids are shown to and entered by the user. Items are always stored by their global id.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, clap::ValueEnum,
+)]
+pub enum IdScheme {
+    /// One counter for all epics and stories, e.g. 12
+    #[default]
+    Global,
+    /// Stories are numbered within their epic, e.g. E3-S1
+    PerEpic,
+}
+
+/// An epic or story opened during the session, kept for the recent items page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecentItem {
+    pub epic_id: EpicId,
+    pub story_id: Option<StoryId>,
+}
+
+/// An entry of the board's activity log. `name` keeps the item's name at the time
+/// of the change so deleted items can still be shown.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+pub struct Activity {
+    pub at: DateTime<Utc>,
+    /// Epic the item belongs to, none for a story outside of any epic.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epic_id: Option<EpicId>,
+    pub story_id: Option<StoryId>,
+    pub name: String,
+    pub event: ActivityEvent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub by: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ActivityEvent {
+    Created,
+    StatusChanged { from: Status, to: Status },
+    Deleted,
+}
+
+impl Activity {
+    pub fn new(
+        at: DateTime<Utc>,
+        epic_id: impl Into<Option<EpicId>>,
+        story_id: Option<StoryId>,
+        name: &str,
+        event: ActivityEvent,
+    ) -> Self {
+        Self {
+            at,
+            epic_id: epic_id.into(),
+            story_id,
+            name: name.to_owned(),
+            event,
+            by: None,
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// A story that is created again under `epic_id` every `interval_days` days.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RecurringStory {
+    pub name: String,
+    pub description: String,
+    pub epic_id: EpicId,
+    pub interval_days: u32,
+    #[serde(default)]
+    pub last_created_at: Option<DateTime<Utc>>,
+}
+
+impl RecurringStory {
+    pub fn new(name: String, description: String, epic_id: EpicId, interval_days: u32) -> Self {
+        Self {
+            name,
+            description,
+            epic_id,
+            interval_days,
+            last_created_at: None,
+        }
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.last_created_at.is_none_or(|last| {
+            now.signed_duration_since(last) >= Duration::days(self.interval_days.into())
+        })
+    }
+}
+
+/// A timebox the team commits to a set of stories for. Only the last sprint
+/// can be open.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+pub struct Sprint {
+    pub name: String,
+    /// Story points the team expects to complete in the sprint.
+    pub capacity: u32,
+    /// Tells sprints with the same name apart when boards are merged.
+    #[serde(default)]
+    pub started_at: DateTime<Utc>,
+    pub stories: Vec<StoryId>,
+    #[serde(default)]
+    pub closed: bool,
+    /// Entries collected when the sprint was closed.
+    #[serde(default, skip_serializing_if = "Retrospective::is_empty")]
+    pub retrospective: Retrospective,
+}
+
+impl Sprint {
+    pub fn new(name: String, capacity: u32) -> Self {
+        Self {
+            name,
+            capacity,
+            started_at: Utc::now(),
+            stories: vec![],
+            closed: false,
+            retrospective: Retrospective::default(),
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+pub struct Retrospective {
+    #[serde(default)]
+    pub went_well: Vec<String>,
+    #[serde(default)]
+    pub to_improve: Vec<String>,
+    #[serde(default)]
+    pub actions: Vec<String>,
+}
+
+impl Retrospective {
+    pub fn is_empty(&self) -> bool {
+        self.went_well.is_empty() && self.to_improve.is_empty() && self.actions.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct DBState {
     pub last_item_id: u32,
-    pub epics: HashMap<u32, Epic>,
-    pub stories: HashMap<u32, Story>,
+    pub epics: HashMap<EpicId, Epic>,
+    pub stories: HashMap<StoryId, Story>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recurring_stories: Vec<RecurringStory>,
+    #[serde(default)]
+    pub id_scheme: IdScheme,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub activity: Vec<Activity>,
+    /// Sprints in the order they were started.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sprints: Vec<Sprint>,
 }
 
 impl DBState {
@@ -83,6 +654,337 @@ impl DBState {
             last_item_id: 0,
             epics: HashMap::new(),
             stories: HashMap::new(),
+            recurring_stories: vec![],
+            id_scheme: IdScheme::Global,
+            activity: vec![],
+            sprints: vec![],
+        }
+    }
+
+    pub fn epic_label(&self, epic_id: EpicId) -> String {
+        match self.id_scheme {
+            IdScheme::Global => epic_id.to_string(),
+            IdScheme::PerEpic => format!("E{epic_id}"),
+        }
+    }
+
+    pub fn story_label(&self, epic_id: EpicId, story_id: StoryId) -> String {
+        match (self.id_scheme, self.stories.get(&story_id)) {
+            (IdScheme::PerEpic, Some(story)) if story.number > 0 => {
+                format!("E{epic_id}-S{}", story.number)
+            }
+            _ => story_id.to_string(),
         }
     }
+
+    /// Describes a blocker for list rows, e.g. `blocked by 12` or the reason.
+    pub fn blocker_label(&self, blocker: &Blocker) -> String {
+        match blocker {
+            Blocker::Story(story_id) => {
+                let label = self
+                    .story_epic(*story_id)
+                    .map_or(story_id.to_string(), |epic_id| {
+                        self.story_label(epic_id, *story_id)
+                    });
+                format!("blocked by {label}")
+            }
+            Blocker::Reason(reason) => format!("blocked: {reason}"),
+        }
+    }
+
+    /// Gives the epics and stories in `ids` the new ids they map to, along with
+    /// every reference to them. Other items keep their ids.
+    pub fn renumber(&mut self, ids: &HashMap<u32, u32>) {
+        if ids.is_empty() {
+            return;
+        }
+        let epic = |id: EpicId| EpicId(ids.get(&id.0).copied().unwrap_or(id.0));
+        let story = |id: StoryId| StoryId(ids.get(&id.0).copied().unwrap_or(id.0));
+
+        self.epics = std::mem::take(&mut self.epics)
+            .into_iter()
+            .map(|(id, mut e)| {
+                e.stories = e.stories.iter().map(|s| story(*s)).collect();
+                (epic(id), e)
+            })
+            .collect();
+        self.stories = std::mem::take(&mut self.stories)
+            .into_iter()
+            .map(|(id, mut s)| {
+                if let Some(Blocker::Story(blocking_id)) = &mut s.blocker {
+                    *blocking_id = story(*blocking_id);
+                }
+                (story(id), s)
+            })
+            .collect();
+        for recurring in self.recurring_stories.iter_mut() {
+            recurring.epic_id = epic(recurring.epic_id);
+        }
+        for activity in self.activity.iter_mut() {
+            activity.epic_id = activity.epic_id.map(epic);
+            activity.story_id = activity.story_id.map(story);
+        }
+        for sprint in self.sprints.iter_mut() {
+            sprint.stories = sprint.stories.iter().map(|s| story(*s)).collect();
+        }
+        self.last_item_id = ids.values().copied().fold(self.last_item_id, u32::max);
+    }
+
+    /// Unblocks stories whose blocking story no longer exists and removes
+    /// deleted stories from sprints.
+    pub fn clear_stale_references(&mut self) {
+        let existing = self.stories.keys().copied().collect::<Vec<_>>();
+        for story in self.stories.values_mut() {
+            if let Some(Blocker::Story(blocking_id)) = story.blocker {
+                if !existing.contains(&blocking_id) {
+                    story.blocker = None;
+                }
+            }
+        }
+        for sprint in self.sprints.iter_mut() {
+            sprint.stories.retain(|id| existing.contains(id));
+        }
+    }
+
+    /// Returns the index of the open sprint, if there is one.
+    pub fn current_sprint(&self) -> Option<usize> {
+        let last = self.sprints.last()?;
+        (!last.closed).then_some(self.sprints.len() - 1)
+    }
+
+    /// Returns the sum of the points of the stories in a sprint.
+    pub fn sprint_points(&self, sprint: &Sprint) -> u32 {
+        sprint
+            .stories
+            .iter()
+            .filter_map(|id| self.stories.get(id)?.points)
+            .sum()
+    }
+
+    /// Returns the id of the epic a story belongs to.
+    pub fn story_epic(&self, story_id: StoryId) -> Option<EpicId> {
+        self.epics
+            .iter()
+            .find(|(_, epic)| epic.stories.contains(&story_id))
+            .map(|(id, _)| *id)
+    }
+
+    /// Returns the id of the story with the given number within an epic.
+    pub fn story_by_number(&self, epic_id: EpicId, number: u32) -> Option<StoryId> {
+        self.epics
+            .get(&epic_id)?
+            .stories
+            .iter()
+            .copied()
+            .find(|id| self.stories.get(id).is_some_and(|s| s.number == number))
+    }
+
+    /// Numbers stories created before per-epic numbering in their id order.
+    pub fn number_stories(&mut self) {
+        for epic in self.epics.values_mut() {
+            let mut story_ids = epic.stories.clone();
+            story_ids.sort();
+            for story_id in story_ids {
+                if let Some(story) = self.stories.get_mut(&story_id).filter(|s| s.number == 0) {
+                    epic.last_story_number += 1;
+                    story.number = epic.last_story_number;
+                }
+            }
+        }
+    }
+
+    /// Returns the most recent modification time of an epic or any of its stories.
+    pub fn epic_last_activity(&self, epic_id: &EpicId) -> Option<DateTime<Utc>> {
+        let epic = self.epics.get(epic_id)?;
+        epic.stories
+            .iter()
+            .filter_map(|id| self.stories.get(id))
+            .map(|s| s.updated_at)
+            .chain(std::iter::once(epic.updated_at))
+            .max()
+    }
+
+    /// Counts the stories of an epic that are neither resolved nor closed.
+    pub fn remaining_stories(&self, epic_id: &EpicId) -> usize {
+        self.epics.get(epic_id).map_or(0, |epic| {
+            epic.stories
+                .iter()
+                .filter_map(|id| self.stories.get(id))
+                .filter(|s| !matches!(s.status, Status::Resolved | Status::Closed))
+                .count()
+        })
+    }
+
+    /// Returns the configured inbox epic or the first epic named `name`.
+    pub fn inbox_epic(&self, configured: Option<EpicId>, name: &str) -> Option<EpicId> {
+        match configured {
+            Some(epic_id) => self.epics.contains_key(&epic_id).then_some(epic_id),
+            None => self
+                .epics
+                .iter()
+                .filter(|(_, epic)| epic.name == name)
+                .map(|(id, _)| *id)
+                .min(),
+        }
+    }
+
+    /// Returns the time of the most recent change to any item.
+    pub fn last_modified(&self) -> Option<DateTime<Utc>> {
+        self.epics
+            .values()
+            .map(|e| e.updated_at)
+            .chain(self.stories.values().map(|s| s.updated_at))
+            .chain(self.activity.iter().map(|a| a.at))
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checklist_progress_should_count_checked_items() {
+        let story = Story::new(
+            "".to_owned(),
+            "Acceptance:\n- [x] login\n  - [ ] logout\n* [X] reset [ ] audit [y] [a".to_owned(),
+        );
+        assert_eq!(story.checklist_progress(), Some((2, 4)));
+        assert_eq!(
+            Story::new("".to_owned(), "plain".to_owned()).checklist_progress(),
+            None
+        );
+    }
+
+    #[test]
+    fn checklist_should_list_and_toggle_criteria() {
+        let mut story = Story::new(
+            "".to_owned(),
+            "Acceptance:\n- [x] login\n  - [ ] logout\n* [X] reset [ ] audit".to_owned(),
+        );
+        assert_eq!(
+            story.checklist(),
+            vec![
+                (true, "login".to_owned()),
+                (false, "logout".to_owned()),
+                (true, "reset".to_owned()),
+                (false, "audit".to_owned()),
+            ]
+        );
+
+        assert_eq!(story.toggle_checklist_item(1), Some(true));
+        assert_eq!(story.toggle_checklist_item(2), Some(false));
+        assert_eq!(story.toggle_checklist_item(4), None);
+        assert_eq!(
+            story.description,
+            "Acceptance:\n- [x] login\n  - [x] logout\n* [ ] reset [ ] audit"
+        );
+    }
+
+    #[test]
+    fn remaining_stories_should_skip_resolved_and_closed() {
+        let mut db_state = DBState::new();
+        let mut epic = Epic::new("".to_owned(), "".to_owned());
+        epic.stories = vec![StoryId(2), StoryId(3), StoryId(4)];
+        db_state.epics.insert(EpicId(1), epic);
+        for (id, status) in [
+            (2, Status::Open),
+            (3, Status::InProgress),
+            (4, Status::Closed),
+        ] {
+            let mut story = Story::new("".to_owned(), "".to_owned());
+            story.status = status;
+            db_state.stories.insert(StoryId(id), story);
+        }
+
+        assert_eq!(db_state.remaining_stories(&EpicId(1)), 2);
+        assert_eq!(db_state.remaining_stories(&EpicId(9)), 0);
+    }
+
+    #[test]
+    fn schema_should_describe_the_database_file() {
+        let schema = serde_json::to_value(schemars::schema_for!(DBState)).unwrap();
+
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["last_item_id", "epics", "stories"])
+        );
+        assert_eq!(
+            schema["$defs"]["Status"]["enum"],
+            serde_json::json!(["Open", "InProgress", "Resolved", "Closed"])
+        );
+        assert!(schema["$defs"]["Epic"]["properties"]["pinned"].is_object());
+    }
+
+    #[test]
+    fn status_should_parse_digits_names_and_abbreviations() {
+        assert_eq!("1".parse::<Status>().unwrap(), Status::Open);
+        assert_eq!("open".parse::<Status>().unwrap(), Status::Open);
+        assert_eq!("2".parse::<Status>().unwrap(), Status::InProgress);
+        assert_eq!("ip".parse::<Status>().unwrap(), Status::InProgress);
+        assert_eq!("In-Progress".parse::<Status>().unwrap(), Status::InProgress);
+        assert_eq!("in progress".parse::<Status>().unwrap(), Status::InProgress);
+        assert_eq!("InProgress".parse::<Status>().unwrap(), Status::InProgress);
+        assert_eq!("3".parse::<Status>().unwrap(), Status::Resolved);
+        assert_eq!("R".parse::<Status>().unwrap(), Status::Resolved);
+        assert_eq!("4".parse::<Status>().unwrap(), Status::Closed);
+        assert_eq!(" CLOSED ".parse::<Status>().unwrap(), Status::Closed);
+        assert_eq!("c".parse::<Status>().unwrap(), Status::Closed);
+
+        assert!("".parse::<Status>().is_err());
+        assert!("5".parse::<Status>().is_err());
+        assert!("done".parse::<Status>().is_err());
+    }
+
+    #[test]
+    fn status_should_use_custom_labels() {
+        let labels = StatusLabels::from([(Status::InProgress, "Doing".to_owned())]);
+
+        assert_eq!(Status::InProgress.label(&labels), "Doing");
+        assert_eq!(Status::Open.label(&labels), "OPEN");
+        assert_eq!(
+            Status::parse_labelled("doing", &labels).unwrap(),
+            Status::InProgress
+        );
+        assert_eq!(
+            Status::parse_labelled("ip", &labels).unwrap(),
+            Status::InProgress
+        );
+        assert!(Status::parse_labelled("doing", &StatusLabels::new()).is_err());
+        assert_eq!(
+            Status::InProgress.label(&StatusLabels::new()),
+            "IN PROGRESS"
+        );
+        assert_eq!(
+            serde_json::to_string(&Status::InProgress).unwrap(),
+            "\"InProgress\""
+        );
+    }
+
+    #[test]
+    fn blocker_should_parse_story_ids_and_reasons() {
+        assert_eq!(
+            "12".parse::<Blocker>().unwrap(),
+            Blocker::Story(StoryId(12))
+        );
+        assert_eq!(
+            " :12: ".parse::<Blocker>().unwrap(),
+            Blocker::Story(StoryId(12))
+        );
+        assert_eq!(
+            "waiting for legal".parse::<Blocker>().unwrap(),
+            Blocker::Reason("waiting for legal".to_owned())
+        );
+        assert!(" ".parse::<Blocker>().is_err());
+
+        let ids = HashMap::from([(12, 3)]);
+        assert_eq!(
+            Blocker::Story(StoryId(12)).remapped(&ids),
+            Blocker::Story(StoryId(3))
+        );
+        assert_eq!(
+            Blocker::Story(StoryId(7)).remapped(&ids),
+            Blocker::Reason("story 7".to_owned())
+        );
+    }
 }
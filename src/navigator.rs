@@ -1,183 +1,851 @@
 #[allow(unused_imports)]
 use anyhow::{anyhow, Context, Ok, Result};
-use std::rc::Rc;
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::sync::Arc;
 
 use crate::{
-    db::JiraDatabase,
-    models::Action,
-    ui::{EpicDetail, HomePage, Page, Prompts, StoryDetail},
+    closeout::CloseOut,
+    config::{ChangeKind, Config},
+    db::{exclusive_access, Board, JiraDatabase},
+    middleware::Middleware,
+    models::{Action, EpicId, RecentItem, Status},
+    sync::{SyncStatus, SyncWorker},
+    ui::{
+        reject_input, ActivityPage, CloseOutPage, EpicDetail, EscalatedItemsPage, EstimationPage,
+        FlowPage, FocusPage, HelpPage, HomePage, InteractivePrompts, Loader, Page, PromptProvider,
+        RecentPage, RetrospectivesPage, SprintPlanningPage, StoryDetail, SyncQueuePage, TriagePage,
+        ViewKey, ViewState, WatchedChangesPage, WorkspacePage,
+    },
 };
 
+const RECENT_LIMIT: usize = 10;
+
 pub struct Navigator {
     pages: Vec<Box<dyn Page>>,
-    prompts: Prompts,
-    db: Rc<JiraDatabase>,
+    prompts: Box<dyn PromptProvider>,
+    db: Arc<JiraDatabase>,
+    boards: Arc<Vec<Board>>,
+    config: Arc<Config>,
+    /// Epics and stories opened on the current board, most recent first.
+    recent: Vec<RecentItem>,
+    sync: Option<SyncWorker>,
+    macros: BTreeMap<String, Vec<Action>>,
+    /// Actions dispatched since recording a macro was started.
+    recording: Option<Vec<Action>>,
+    /// Config file new macros are saved to.
+    config_path: Option<String>,
+    middleware: Vec<Box<dyn Middleware>>,
+    /// View state of the pages of the current board, restored when they are
+    /// shown again.
+    view_states: HashMap<ViewKey, ViewState>,
 }
 
 impl Navigator {
-    pub fn new(db: Rc<JiraDatabase>) -> Self {
+    pub fn new(db: Arc<JiraDatabase>, config: Arc<Config>) -> Self {
         Self {
-            pages: vec![Box::new(HomePage::new(Rc::clone(&db)))],
-            prompts: Prompts::new(),
+            pages: vec![Box::new(HomePage::new(
+                Arc::clone(&db),
+                Arc::clone(&config),
+            ))],
+            prompts: Box::new(InteractivePrompts {
+                templates: config.templates.clone(),
+            }),
             db,
+            boards: Arc::new(vec![]),
+            macros: config.macros.clone(),
+            config,
+            recent: vec![],
+            sync: None,
+            recording: None,
+            config_path: None,
+            middleware: vec![],
+            view_states: HashMap::new(),
+        }
+    }
+
+    pub fn with_boards(boards: Vec<Board>, config: Arc<Config>) -> Result<Self> {
+        let db = boards
+            .first()
+            .map(|b| Arc::clone(&b.db))
+            .ok_or_else(|| anyhow!("no boards registered"))?;
+        let boards = Arc::new(boards);
+
+        Ok(Self {
+            pages: vec![Box::new(WorkspacePage {
+                boards: Arc::clone(&boards),
+                config: Arc::clone(&config),
+            })],
+            prompts: Box::new(InteractivePrompts {
+                templates: config.templates.clone(),
+            }),
+            db,
+            boards,
+            macros: config.macros.clone(),
+            config,
+            recent: vec![],
+            sync: None,
+            recording: None,
+            config_path: None,
+            middleware: vec![],
+            view_states: HashMap::new(),
+        })
+    }
+
+    pub fn get_current_page(&self) -> Option<&dyn Page> {
+        self.pages.last().map(|p| p.as_ref())
+    }
+
+    /// Returns the action for input that works on every page: recording a
+    /// macro or replaying one.
+    pub fn macro_action(&self, input: &str) -> Option<Action> {
+        if input == self.config.keymap.record_macro {
+            return Some(Action::ToggleMacroRecording);
         }
+        self.macros
+            .contains_key(input)
+            .then(|| Action::ReplayMacro {
+                key: input.to_owned(),
+            })
+    }
+
+    /// Returns the number of actions recorded so far while a macro is recorded.
+    pub fn recorded_actions(&self) -> Option<usize> {
+        self.recording.as_ref().map(Vec::len)
+    }
+
+    /// Adds a middleware that sees every action after those added before it.
+    pub fn add_middleware(&mut self, middleware: Box<dyn Middleware>) {
+        self.middleware.push(middleware);
     }
 
-    pub fn get_current_page(&self) -> Option<&Box<dyn Page>> {
-        self.pages.last()
+    pub fn set_config_path(&mut self, config_path: &str) {
+        self.config_path = Some(config_path.to_owned());
     }
 
-    pub fn handle_action(&mut self, action: Action) -> Result<()> {
+    pub fn dispatch(&mut self, action: Action) -> Result<()> {
+        // a background sync must not replace the database between the reads and writes of an action
+        let _access = exclusive_access();
+        if let Some(recording) = &mut self.recording {
+            match &action {
+                Action::ToggleMacroRecording | Action::Exit => {}
+                // a macro replayed while recording is recorded by its actions
+                Action::ReplayMacro { key } => {
+                    recording.extend(self.macros.get(key).into_iter().flatten().cloned())
+                }
+                action => recording.push(action.clone()),
+            }
+        }
+        // follow-up actions run depth-first, before any remaining queued actions
+        let mut pending = vec![action];
+
+        while let Some(action) = pending.pop() {
+            let follow_ups = self.handle_action(action)?;
+            pending.extend(follow_ups.into_iter().rev());
+        }
+
+        Ok(())
+    }
+
+    /// Handles the action unless a middleware vetoes it or it isn't confirmed,
+    /// and returns its follow-up actions.
+    pub fn handle_action(&mut self, action: Action) -> Result<Vec<Action>> {
+        for middleware in &mut self.middleware {
+            if !middleware.before(&action)? {
+                return Ok(vec![]);
+            }
+        }
+        if !self.confirmed(&action)? {
+            return Ok(vec![]);
+        }
+
+        let result = self.run_action(action.clone());
+        for middleware in &mut self.middleware {
+            if let Err(e) = middleware.after(&action, result.as_ref().err()) {
+                reject_input(Some(&format!("{e:#}")), self.config.invalid_input_feedback);
+            }
+        }
+        result
+    }
+
+    fn run_action(&mut self, action: Action) -> Result<Vec<Action>> {
+        let mut follow_ups = vec![];
         match action {
+            Action::NavigateToBoard { board_index } => {
+                // switch the active database and open the board's home page
+                let board = self
+                    .boards
+                    .get(board_index)
+                    .ok_or_else(|| anyhow!("board not found: {board_index}"))?;
+                self.db = Arc::clone(&board.db);
+                self.recent.clear();
+                self.view_states.clear();
+                self.open_page(Box::new(HomePage::new(
+                    Arc::clone(&self.db),
+                    Arc::clone(&self.config),
+                )));
+            }
             Action::NavigateToEpicDetail { epic_id } => {
                 // create a new EpicDetail instance and add it to the pages vector
-                self.pages.push(Box::new(EpicDetail {
+                self.remember(RecentItem {
                     epic_id,
-                    db: Rc::clone(&self.db),
-                }));
+                    story_id: None,
+                });
+                self.open_page(Box::new(EpicDetail::new(
+                    epic_id,
+                    Arc::clone(&self.db),
+                    Arc::clone(&self.config),
+                )));
             }
             Action::NavigateToStoryDetail { epic_id, story_id } => {
                 // create a new StoryDetail instance and add it to the pages vector
-                self.pages.push(Box::new(StoryDetail {
+                self.remember(RecentItem {
+                    epic_id,
+                    story_id: Some(story_id),
+                });
+                self.open_page(Box::new(StoryDetail {
                     epic_id,
                     story_id,
-                    db: Rc::clone(&self.db),
+                    db: Arc::clone(&self.db),
+                    config: Arc::clone(&self.config),
+                    loader: Loader::default(),
+                }));
+            }
+            Action::NavigateToHelp => {
+                // create a new HelpPage instance and add it to the pages vector
+                self.open_page(Box::new(HelpPage {
+                    config: Arc::clone(&self.config),
+                }));
+            }
+            Action::NavigateToActivity { epic_id } => {
+                // create a new ActivityPage instance for the board or a single epic
+                self.open_page(Box::new(ActivityPage {
+                    epic_id,
+                    db: Arc::clone(&self.db),
+                    config: Arc::clone(&self.config),
+                }));
+            }
+            Action::NavigateToRecent => {
+                // create a new RecentPage with the items opened so far
+                self.open_page(Box::new(RecentPage {
+                    items: self.recent.clone(),
+                    db: Arc::clone(&self.db),
+                    config: Arc::clone(&self.config),
+                }));
+            }
+            Action::NavigateToInbox => {
+                // create a new TriagePage for the stories in the inbox epic
+                self.open_page(Box::new(TriagePage::new(
+                    Arc::clone(&self.db),
+                    Arc::clone(&self.config),
+                )));
+            }
+            Action::NavigateToSyncQueue => {
+                // create a new SyncQueuePage with the changes waiting to be synced
+                self.open_page(Box::new(SyncQueuePage::new(
+                    Arc::clone(&self.db),
+                    Arc::clone(&self.config),
+                )));
+            }
+            Action::NavigateToFocus { epic_id, story_id } => {
+                // create a new FocusPage for working on the story
+                self.open_page(Box::new(FocusPage::new(
+                    epic_id,
+                    story_id,
+                    Arc::clone(&self.db),
+                    Arc::clone(&self.config),
+                )));
+            }
+            Action::NavigateToWatchedChanges { changes } => {
+                // create a new WatchedChangesPage with the changes found at startup
+                self.open_page(Box::new(WatchedChangesPage {
+                    changes,
+                    config: Arc::clone(&self.config),
+                }));
+            }
+            Action::NavigateToEscalatedItems { items } => {
+                // create a new EscalatedItemsPage with the escalations made at startup
+                self.open_page(Box::new(EscalatedItemsPage {
+                    items,
+                    config: Arc::clone(&self.config),
+                }));
+            }
+            Action::NavigateToEstimation { epic_id } => {
+                // create a new EstimationPage for the unestimated stories of the epic
+                self.open_page(Box::new(EstimationPage::new(
+                    epic_id,
+                    Arc::clone(&self.db),
+                    Arc::clone(&self.config),
+                )));
+            }
+            Action::NavigateToSprintPlanning => {
+                // create a new SprintPlanningPage for the open sprint
+                self.open_page(Box::new(SprintPlanningPage {
+                    db: Arc::clone(&self.db),
+                    config: Arc::clone(&self.config),
+                }));
+            }
+            Action::NavigateToRetrospectives => {
+                // create a new RetrospectivesPage with the closed sprints
+                self.open_page(Box::new(RetrospectivesPage {
+                    db: Arc::clone(&self.db),
+                    config: Arc::clone(&self.config),
+                }));
+            }
+            Action::NavigateToFlow => {
+                // create a new FlowPage with the cumulative flow chart of the board
+                self.open_page(Box::new(FlowPage {
+                    db: Arc::clone(&self.db),
+                    config: Arc::clone(&self.config),
                 }));
             }
             Action::NavigateToPreviousPage => {
-                // remove the last page from the pages vector
-                if !self.pages.is_empty() {
-                    self.pages.pop();
+                // remove the last page from the pages vector, leaving the root page exits
+                if self.pages.len() > 1 || self.confirm_exit()? {
+                    self.close_page();
                 }
             }
             Action::CreateEpic => {
                 // prompt the user to create a new epic and persist it in the database
                 self.db
-                    .create_epic((self.prompts.create_epic)())
-                    .with_context(|| format!("failed to create epic"))?;
+                    .create_epic(self.prompts.create_epic()?)
+                    .with_context(|| "failed to create epic".to_string())?;
+            }
+            Action::CreateEpicAndOpen => {
+                // create a new epic like CreateEpic and navigate to it afterwards
+                let epic_id = self
+                    .db
+                    .create_epic(self.prompts.create_epic()?)
+                    .with_context(|| "failed to create epic".to_string())?;
+                follow_ups.push(Action::NavigateToEpicDetail { epic_id });
             }
             Action::UpdateEpicStatus { epic_id } => {
                 // prompt the user to update status and persist it in the database
-                let status = (self.prompts.update_status)()
+                let status = self
+                    .prompts
+                    .update_status()?
                     .with_context(|| format!("invalid status: {epic_id}"))?;
-                self.db.update_epic_status(epic_id, status)?;
+                // the middleware already ran for this action, only the chosen status is confirmed
+                let set_status = Action::SetEpicStatus {
+                    epic_id,
+                    status: status.clone(),
+                };
+                if self.confirmed(&set_status)? {
+                    follow_ups.extend(self.set_epic_status(epic_id, status)?);
+                }
+            }
+            Action::SetEpicStatus { epic_id, status } => {
+                follow_ups.extend(self.set_epic_status(epic_id, status)?);
+            }
+            Action::OfferCloseOut { epic_id } => {
+                // prompt the user to write a report about the closed epic and show it
+                if self.prompts.create_close_out_report()? {
+                    let report = CloseOut::collect(&self.db.read_db()?, epic_id, Utc::now())?;
+                    let saved_to = match self.db.location() {
+                        Some(db_path) => {
+                            let path = CloseOut::path_for(db_path, epic_id);
+                            fs::write(&path, report.to_string()).with_context(|| {
+                                format!("failed to save close-out report: {}", path.display())
+                            })?;
+                            Some(path)
+                        }
+                        None => None,
+                    };
+                    self.open_page(Box::new(CloseOutPage {
+                        report,
+                        saved_to,
+                        config: Arc::clone(&self.config),
+                    }));
+                }
             }
             Action::DeleteEpic { epic_id } => {
-                // prompt the user to delete the epic and persist it in the database
-                if (self.prompts.delete_epic)() {
+                // delete the epic, confirmed according to the policy, and leave its page
+                self.db
+                    .delete_epic(epic_id)
+                    .with_context(|| format!("failed to delete epic: {epic_id}"))?;
+                self.close_page();
+            }
+            Action::ToggleEpicPin { epic_id } => {
+                // pin or unpin the epic on the home page
+                self.db
+                    .toggle_epic_pin(epic_id)
+                    .with_context(|| format!("failed to pin epic: {epic_id}"))?;
+            }
+            Action::ToggleCriterion { story_id, index } => {
+                // check or uncheck the box in the story's description
+                self.db
+                    .toggle_story_criterion(story_id, index)
+                    .with_context(|| format!("failed to update story: {story_id}"))?;
+            }
+            Action::ToggleDensity { page } => {
+                // every page of the type reads the same setting
+                self.config.density.of(page).toggle();
+                if let Some(path) = &self.config_path {
+                    Config::save_density(path, &self.config.density)?;
+                }
+            }
+            Action::ToggleMacroRecording => match self.recording.take() {
+                None => self.recording = Some(vec![]),
+                Some(actions) if actions.is_empty() => {
+                    reject_input(Some("nothing recorded"), self.config.invalid_input_feedback)
+                }
+                Some(actions) => {
+                    // ask for the key to replay it with and keep it for later sessions
+                    let key = self.prompts.macro_key()?;
+                    if key.is_empty() {
+                        // discarded
+                    } else if let Err(e) = self.config.keymap.check_macro_key(&key) {
+                        reject_input(Some(&e.to_string()), self.config.invalid_input_feedback);
+                    } else {
+                        if let Some(path) = &self.config_path {
+                            Config::save_macro(path, &key, &actions)?;
+                        }
+                        self.macros.insert(key, actions);
+                    }
+                }
+            },
+            Action::ReplayMacro { key } => {
+                // run the recorded actions in order, as if they were entered again
+                let actions = self
+                    .macros
+                    .get(&key)
+                    .ok_or_else(|| anyhow!("no macro bound to '{key}'"))?;
+                follow_ups.extend(actions.iter().cloned());
+            }
+            Action::ToggleStoryWatch { story_id } => {
+                // watch or unwatch the story for changes made elsewhere
+                self.db
+                    .toggle_story_watch(story_id)
+                    .with_context(|| format!("failed to watch story: {story_id}"))?;
+            }
+            Action::AddEpicNote { epic_id } => {
+                // prompt the user for a note and append it to the epic's notes
+                let text = self.prompts.add_note()?;
+                if !text.is_empty() {
                     self.db
-                        .delete_epic(epic_id)
-                        .with_context(|| format!("failed to delete epic: {epic_id}"))?;
-                    self.pages.pop();
+                        .add_epic_note(epic_id, text)
+                        .with_context(|| format!("failed to add note to epic: {epic_id}"))?;
                 }
             }
             Action::CreateStory { epic_id } => {
                 // prompt the user to create a new story and persist it in the database
                 self.db
-                    .create_story((self.prompts.create_story)(), epic_id)
+                    .create_story(self.prompts.create_story()?, epic_id)
+                    .with_context(|| format!("failed to create story: {epic_id}"))?;
+            }
+            Action::CreateStoryAndOpen { epic_id } => {
+                // create a new story like CreateStory and navigate to it afterwards
+                let story_id = self
+                    .db
+                    .create_story(self.prompts.create_story()?, epic_id)
                     .with_context(|| format!("failed to create story: {epic_id}"))?;
+                follow_ups.push(Action::NavigateToStoryDetail { epic_id, story_id });
+            }
+            Action::CreateStories { epic_id } => {
+                // prompt the user for several stories and persist them in one write
+                let stories = self.prompts.create_stories()?;
+                if !stories.is_empty() {
+                    self.db
+                        .create_stories(stories, epic_id)
+                        .with_context(|| format!("failed to create stories: {epic_id}"))?;
+                }
             }
             Action::UpdateStoryStatus { story_id } => {
                 // prompt the user to update status and persist it in the database
-                if let Some(status) = (self.prompts.update_status)() {
-                    let s = status.clone();
-                    self.db
-                        .update_story_status(story_id, status)
-                        .with_context(|| format!("invalid status: {s}"))?;
+                if let Some(status) = self.prompts.update_status()? {
+                    // the middleware already ran for this action, only the chosen status is confirmed
+                    let set_status = Action::SetStoryStatus {
+                        story_id,
+                        status: status.clone(),
+                    };
+                    if self.confirmed(&set_status)? {
+                        self.db
+                            .update_story_status(story_id, status.clone())
+                            .with_context(|| format!("invalid status: {status}"))?;
+                    }
                 }
             }
+            Action::SetStoryStatus { story_id, status } => {
+                self.db.update_story_status(story_id, status)?;
+            }
             Action::DeleteStory { epic_id, story_id } => {
-                // prompt the user to delete the story and persist it in the database
-                if (self.prompts.delete_story)() {
+                // delete the story, confirmed according to the policy, and leave its page
+                self.db
+                    .delete_story(epic_id, story_id)
+                    .with_context(|| format!("failed to delete story: {story_id}"))?;
+                self.close_page();
+            }
+            Action::SyncNow => match &self.sync {
+                Some(worker) => worker.request_sync(),
+                None => reject_input(
+                    Some("no remote configured, add a \"remote\" section to the config file"),
+                    self.config.invalid_input_feedback,
+                ),
+            },
+            Action::DiscardConflictCopy { path } => {
+                // discard a copy kept from a conflicting sync
+                fs::remove_file(&path)
+                    .with_context(|| format!("failed to discard conflict copy: {path}"))?;
+            }
+            Action::CloseSprint => {
+                // prompt the user for the retrospective and close the open sprint
+                let retrospective = self.prompts.retrospective()?;
+                self.db
+                    .close_sprint(retrospective)
+                    .with_context(|| "failed to close sprint".to_string())?;
+            }
+            Action::ToggleSprintStory { story_id } => {
+                // move the story into the open sprint or back to the backlog
+                self.db
+                    .toggle_sprint_story(story_id)
+                    .with_context(|| format!("failed to plan story: {story_id}"))?;
+            }
+            Action::EditStoryFields { story_id } => {
+                // prompt for each custom field, empty input keeps the current value
+                for field in &self.config.custom_fields {
+                    let current = self
+                        .db
+                        .read_db()?
+                        .stories
+                        .get(&story_id)
+                        .and_then(|story| story.fields.get(&field.name).cloned());
+                    let input = self.prompts.custom_field(field, current.as_deref())?;
+                    let value = match input.trim() {
+                        "" => continue,
+                        "-" => None,
+                        input => {
+                            let parsed = field.parse(input);
+                            if let Err(e) = &parsed {
+                                reject_input(
+                                    Some(&e.to_string()),
+                                    self.config.invalid_input_feedback,
+                                );
+                            }
+                            let Some(value) = parsed.ok() else {
+                                continue;
+                            };
+                            Some(value)
+                        }
+                    };
                     self.db
-                        .delete_story(epic_id, story_id)
-                        .with_context(|| format!("failed to delete story: {story_id}"))?;
-                    self.pages.pop();
+                        .set_story_field(story_id, &field.name, value)
+                        .with_context(|| format!("failed to update story: {story_id}"))?;
                 }
             }
+            Action::EstimateStory { story_id, points } => {
+                // persist the estimate, the estimation page moves on to the next story
+                self.db
+                    .set_story_points(story_id, Some(points))
+                    .with_context(|| format!("failed to estimate story: {story_id}"))?;
+            }
+            Action::MergeStory { story_id } => {
+                // prompt the user for a duplicate and merge it into the story
+                if let Some(duplicate_id) = self.prompts.merge_story()? {
+                    self.db
+                        .merge_stories(story_id, duplicate_id)
+                        .with_context(|| {
+                            format!("failed to merge story {duplicate_id} into {story_id}")
+                        })?;
+                }
+            }
+            Action::BlockStory { story_id } => {
+                // prompt the user for what blocks the story, an empty answer unblocks it
+                let blocker = self.prompts.block_story()?;
+                self.db
+                    .set_story_blocker(story_id, blocker)
+                    .with_context(|| format!("failed to block story: {story_id}"))?;
+            }
+            Action::MoveStory {
+                story_id,
+                to_epic_id,
+            } => {
+                self.db
+                    .move_story(story_id, to_epic_id)
+                    .with_context(|| format!("failed to move story: {story_id}"))?;
+            }
+            Action::DiscardStory { epic_id, story_id } => {
+                // delete the story and stay on the current page
+                self.db
+                    .delete_story(epic_id, story_id)
+                    .with_context(|| format!("failed to delete story: {story_id}"))?;
+            }
+            Action::MoveStories {
+                from_epic_id,
+                to_epic_id,
+                status,
+            } => {
+                self.db
+                    .move_stories(from_epic_id, to_epic_id, status)
+                    .with_context(|| format!("failed to move stories to epic: {to_epic_id}"))?;
+            }
             Action::Exit => {
                 // remove all pages from the pages vector
-                self.pages.clear();
+                if self.confirm_exit()? {
+                    self.pages.clear();
+                }
             }
         }
 
+        Ok(follow_ups)
+    }
+
+    /// Shows `page` on top of the current one. Both keep their view state.
+    fn open_page(&mut self, page: Box<dyn Page>) {
+        self.save_view_state();
+        if let Some(state) = page
+            .view_state()
+            .and_then(|current| self.view_states.get(&current.key))
+        {
+            page.restore_view_state(state);
+        }
+        self.pages.push(page);
+    }
+
+    /// Leaves the current page for the one below it, which is brought up to
+    /// date with its view state.
+    fn close_page(&mut self) {
+        self.save_view_state();
+        self.pages.pop();
+        if let Some(page) = self.pages.last() {
+            if let Some(state) = page
+                .view_state()
+                .and_then(|current| self.view_states.get(&current.key))
+            {
+                page.restore_view_state(state);
+            }
+        }
+    }
+
+    fn save_view_state(&mut self) {
+        if let Some(state) = self.pages.last().and_then(|page| page.view_state()) {
+            self.view_states.insert(state.key, state);
+        }
+    }
+
+    fn remember(&mut self, item: RecentItem) {
+        self.recent.retain(|i| *i != item);
+        self.recent.insert(0, item);
+        self.recent.truncate(RECENT_LIMIT);
+    }
+
+    /// Writes pending changes of all open databases.
+    pub fn flush(&self) -> Result<()> {
+        self.db.flush()?;
+        for board in self.boards.iter() {
+            board.db.flush()?;
+        }
         Ok(())
     }
 
+    /// Updates the status of an epic and returns the close-out offer if this
+    /// closed it.
+    fn set_epic_status(&self, epic_id: EpicId, status: Status) -> Result<Option<Action>> {
+        let was_closed = self
+            .db
+            .read_db()?
+            .epics
+            .get(&epic_id)
+            .is_some_and(|e| e.status == Status::Closed);
+        let closed = status == Status::Closed;
+        self.db.update_epic_status(epic_id, status)?;
+
+        Ok((closed && !was_closed).then_some(Action::OfferCloseOut { epic_id }))
+    }
+
+    fn confirm_exit(&self) -> Result<bool> {
+        self.flush()?;
+        let unsaved =
+            self.db.has_unsaved_changes() || self.boards.iter().any(|b| b.db.has_unsaved_changes());
+
+        if unsaved {
+            self.prompts.confirm_exit()
+        } else {
+            Ok(true)
+        }
+    }
+
+    /// Asks for confirmation if the config's policy requires it for the action.
+    fn confirmed(&self, action: &Action) -> Result<bool> {
+        match confirmation(action) {
+            Some((kind, question)) if self.config.confirm.requires(kind) => {
+                self.prompts.confirm(question)
+            }
+            _ => Ok(true),
+        }
+    }
+
     // Private functions used for testing
     #[allow(dead_code)]
     fn get_page_count(&self) -> usize {
         self.pages.len()
     }
-    #[allow(dead_code)]
-    fn set_prompts(&mut self, prompts: Prompts) {
+    /// Replaces the interactive prompts, e.g. with [`ScriptedPrompts`](crate::ui::ScriptedPrompts)
+    /// to drive the navigator from a script.
+    pub fn set_prompts(&mut self, prompts: Box<dyn PromptProvider>) {
         self.prompts = prompts;
     }
+
+    pub fn set_sync_worker(&mut self, worker: SyncWorker) {
+        self.sync = Some(worker);
+    }
+
+    pub fn sync_status(&self) -> Option<SyncStatus> {
+        self.sync.as_ref().map(SyncWorker::status)
+    }
+}
+
+/// Returns whether the action changes the board, rather than navigating or
+/// asking for the change first.
+pub(crate) fn changes_board(action: &Action) -> bool {
+    confirmation(action).is_some()
+}
+
+/// Returns what kind of change an action makes and the question to confirm it
+/// with, or `None` if it doesn't change the board. Actions that prompt for the
+/// change first are confirmed once it is known.
+fn confirmation(action: &Action) -> Option<(ChangeKind, &'static str)> {
+    const CHANGE: &str = "Are you sure you want to make this change? [Y/n]:";
+    Some(match action {
+        Action::DeleteEpic { .. } => (
+            ChangeKind::Delete,
+            "Are you sure you want to delete this epic? All stories in this epic will also be deleted [Y/n]:",
+        ),
+        Action::DeleteStory { .. } | Action::DiscardStory { .. } => (
+            ChangeKind::Delete,
+            "Are you sure you want to delete this story? [Y/n]:",
+        ),
+        Action::DiscardConflictCopy { .. } => (
+            ChangeKind::Delete,
+            "Are you sure you want to discard this copy? Its changes will be lost [Y/n]:",
+        ),
+        Action::SetEpicStatus {
+            status: Status::Closed,
+            ..
+        } => (
+            ChangeKind::Close,
+            "Are you sure you want to close this epic? [Y/n]:",
+        ),
+        Action::SetStoryStatus {
+            status: Status::Closed,
+            ..
+        } => (
+            ChangeKind::Close,
+            "Are you sure you want to close this story? [Y/n]:",
+        ),
+        Action::CloseSprint => (
+            ChangeKind::Close,
+            "Are you sure you want to close the sprint? [Y/n]:",
+        ),
+        Action::CreateEpic
+        | Action::CreateEpicAndOpen
+        | Action::CreateStory { .. }
+        | Action::CreateStoryAndOpen { .. }
+        | Action::CreateStories { .. }
+        | Action::SetEpicStatus { .. }
+        | Action::SetStoryStatus { .. }
+        | Action::ToggleEpicPin { .. }
+        | Action::ToggleStoryWatch { .. }
+        | Action::AddEpicNote { .. }
+        | Action::BlockStory { .. }
+        | Action::MergeStory { .. }
+        | Action::ToggleSprintStory { .. }
+        | Action::EditStoryFields { .. }
+        | Action::EstimateStory { .. }
+        | Action::ToggleCriterion { .. }
+        | Action::MoveStory { .. }
+        | Action::MoveStories { .. } => (ChangeKind::Other, CHANGE),
+        Action::NavigateToBoard { .. }
+        | Action::NavigateToEpicDetail { .. }
+        | Action::NavigateToStoryDetail { .. }
+        | Action::NavigateToPreviousPage
+        | Action::NavigateToHelp
+        | Action::NavigateToActivity { .. }
+        | Action::NavigateToRecent
+        | Action::NavigateToFlow
+        | Action::NavigateToInbox
+        | Action::NavigateToSprintPlanning
+        | Action::NavigateToRetrospectives
+        | Action::NavigateToEstimation { .. }
+        | Action::NavigateToSyncQueue
+        | Action::NavigateToFocus { .. }
+        | Action::NavigateToWatchedChanges { .. }
+        | Action::NavigateToEscalatedItems { .. }
+        | Action::UpdateEpicStatus { .. }
+        | Action::UpdateStoryStatus { .. }
+        | Action::OfferCloseOut { .. }
+        | Action::SyncNow
+        | Action::ToggleDensity { .. }
+        | Action::ToggleMacroRecording
+        | Action::ReplayMacro { .. }
+        | Action::Exit => return None,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        db::test_utils::MockDB,
-        models::{Epic, Status, Story},
+        db::{test_utils::MockDB, Database},
+        models::{Blocker, DBState, Epic, Retrospective, Sprint, Status, Story, StoryId},
+        ui::{ScriptedPrompts, ScriptedResponse},
     };
 
     #[test]
     fn should_start_on_home_page() {
-        let db = Rc::new(JiraDatabase {
+        let db = Arc::new(JiraDatabase {
             database: Box::new(MockDB::new()),
         });
-        let nav = Navigator::new(db);
+        let nav = Navigator::new(db, Arc::new(Config::default()));
 
         assert_eq!(nav.get_page_count(), 1);
 
         let current_page = nav.get_current_page().unwrap();
         let home_page = current_page.as_any().downcast_ref::<HomePage>();
 
-        assert_eq!(home_page.is_some(), true);
+        assert!(home_page.is_some());
     }
 
     #[test]
     fn handle_action_should_navigate_pages() {
-        let db = Rc::new(JiraDatabase {
+        let db = Arc::new(JiraDatabase {
             database: Box::new(MockDB::new()),
         });
 
-        let mut nav = Navigator::new(db);
+        let mut nav = Navigator::new(db, Arc::new(Config::default()));
 
-        nav.handle_action(Action::NavigateToEpicDetail { epic_id: 1 })
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id: EpicId(1) })
             .unwrap();
         assert_eq!(nav.get_page_count(), 2);
 
         let current_page = nav.get_current_page().unwrap();
         let epic_detail_page = current_page.as_any().downcast_ref::<EpicDetail>();
-        assert_eq!(epic_detail_page.is_some(), true);
+        assert!(epic_detail_page.is_some());
 
         nav.handle_action(Action::NavigateToStoryDetail {
-            epic_id: 1,
-            story_id: 2,
+            epic_id: EpicId(1),
+            story_id: StoryId(2),
         })
         .unwrap();
         assert_eq!(nav.get_page_count(), 3);
 
         let current_page = nav.get_current_page().unwrap();
         let story_detail_page = current_page.as_any().downcast_ref::<StoryDetail>();
-        assert_eq!(story_detail_page.is_some(), true);
+        assert!(story_detail_page.is_some());
 
         nav.handle_action(Action::NavigateToPreviousPage).unwrap();
         assert_eq!(nav.get_page_count(), 2);
 
         let current_page = nav.get_current_page().unwrap();
         let epic_detail_page = current_page.as_any().downcast_ref::<EpicDetail>();
-        assert_eq!(epic_detail_page.is_some(), true);
+        assert!(epic_detail_page.is_some());
 
         nav.handle_action(Action::NavigateToPreviousPage).unwrap();
         assert_eq!(nav.get_page_count(), 1);
 
         let current_page = nav.get_current_page().unwrap();
         let home_page = current_page.as_any().downcast_ref::<HomePage>();
-        assert_eq!(home_page.is_some(), true);
+        assert!(home_page.is_some());
 
         nav.handle_action(Action::NavigateToPreviousPage).unwrap();
         assert_eq!(nav.get_page_count(), 0);
@@ -186,19 +854,113 @@ mod tests {
         assert_eq!(nav.get_page_count(), 0);
     }
 
+    #[test]
+    fn handle_action_should_remember_recent_items() {
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+        let mut nav = Navigator::new(db, Arc::new(Config::default()));
+
+        for epic_id in 1..=RECENT_LIMIT as u32 + 1 {
+            nav.handle_action(Action::NavigateToEpicDetail {
+                epic_id: EpicId(epic_id),
+            })
+            .unwrap();
+        }
+        nav.handle_action(Action::NavigateToStoryDetail {
+            epic_id: EpicId(5),
+            story_id: StoryId(20),
+        })
+        .unwrap();
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id: EpicId(5) })
+            .unwrap();
+        nav.handle_action(Action::NavigateToRecent).unwrap();
+
+        let page = nav.get_current_page().unwrap();
+        let recent_page = page.as_any().downcast_ref::<RecentPage>().unwrap();
+        assert_eq!(recent_page.items.len(), RECENT_LIMIT);
+        assert_eq!(
+            recent_page.items[..3],
+            [
+                RecentItem {
+                    epic_id: EpicId(5),
+                    story_id: None
+                },
+                RecentItem {
+                    epic_id: EpicId(5),
+                    story_id: Some(StoryId(20))
+                },
+                RecentItem {
+                    epic_id: EpicId(11),
+                    story_id: None
+                },
+            ]
+        );
+        assert!(!recent_page.items.contains(&RecentItem {
+            epic_id: EpicId(1),
+            story_id: None
+        }));
+    }
+
+    #[test]
+    fn handle_action_should_navigate_into_boards() {
+        let boards = vec![
+            Board {
+                name: "one".to_owned(),
+                db: Arc::new(JiraDatabase {
+                    database: Box::new(MockDB::new()),
+                }),
+            },
+            Board {
+                name: "two".to_owned(),
+                db: Arc::new(JiraDatabase {
+                    database: Box::new(MockDB::new()),
+                }),
+            },
+        ];
+        let second = Arc::clone(&boards[1].db);
+
+        let mut nav = Navigator::with_boards(boards, Arc::new(Config::default())).unwrap();
+
+        let current_page = nav.get_current_page().unwrap();
+        let workspace_page = current_page.as_any().downcast_ref::<WorkspacePage>();
+        assert!(workspace_page.is_some());
+
+        nav.handle_action(Action::NavigateToBoard { board_index: 1 })
+            .unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+
+        let current_page = nav.get_current_page().unwrap();
+        let home_page = current_page.as_any().downcast_ref::<HomePage>().unwrap();
+        assert!(Arc::ptr_eq(&home_page.db, &second));
+
+        let prompts = ScriptedPrompts::new([ScriptedResponse::Epic(Epic::new(
+            "name".to_owned(),
+            "description".to_owned(),
+        ))]);
+        nav.set_prompts(Box::new(prompts));
+
+        nav.handle_action(Action::CreateEpic).unwrap();
+        assert_eq!(second.read_db().unwrap().epics.len(), 1);
+
+        assert!(nav
+            .handle_action(Action::NavigateToBoard { board_index: 2 })
+            .is_err());
+    }
+
     #[test]
     fn handle_action_should_clear_pages_on_exit() {
-        let db = Rc::new(JiraDatabase {
+        let db = Arc::new(JiraDatabase {
             database: Box::new(MockDB::new()),
         });
 
-        let mut nav = Navigator::new(db);
+        let mut nav = Navigator::new(db, Arc::new(Config::default()));
 
-        nav.handle_action(Action::NavigateToEpicDetail { epic_id: 1 })
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id: EpicId(1) })
             .unwrap();
         nav.handle_action(Action::NavigateToStoryDetail {
-            epic_id: 1,
-            story_id: 2,
+            epic_id: EpicId(1),
+            story_id: StoryId(2),
         })
         .unwrap();
         nav.handle_action(Action::Exit).unwrap();
@@ -206,18 +968,57 @@ mod tests {
         assert_eq!(nav.get_page_count(), 0);
     }
 
+    #[test]
+    fn handle_action_should_confirm_exit_with_unsaved_changes() {
+        struct UnsavedDB(MockDB);
+
+        impl Database for UnsavedDB {
+            fn read_db(&self) -> Result<DBState> {
+                self.0.read_db()
+            }
+            fn write_db(&self, db_state: &DBState) -> Result<()> {
+                self.0.write_db(db_state)
+            }
+            fn has_unsaved_changes(&self) -> bool {
+                true
+            }
+        }
+
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(UnsavedDB(MockDB::new())),
+        });
+
+        let mut nav = Navigator::new(db, Arc::new(Config::default()));
+        nav.set_prompts(Box::new(ScriptedPrompts::new([
+            ScriptedResponse::Confirm(false),
+            ScriptedResponse::Confirm(false),
+            ScriptedResponse::Confirm(true),
+        ])));
+
+        nav.handle_action(Action::Exit).unwrap();
+        assert_eq!(nav.get_page_count(), 1);
+
+        nav.handle_action(Action::NavigateToPreviousPage).unwrap();
+        assert_eq!(nav.get_page_count(), 1);
+
+        nav.handle_action(Action::Exit).unwrap();
+        assert_eq!(nav.get_page_count(), 0);
+    }
+
     #[test]
     fn handle_action_should_handle_create_epic() {
-        let db = Rc::new(JiraDatabase {
+        let db = Arc::new(JiraDatabase {
             database: Box::new(MockDB::new()),
         });
 
-        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
 
-        let mut prompts = Prompts::new();
-        prompts.create_epic = Box::new(|| Epic::new("name".to_owned(), "description".to_owned()));
+        let prompts = ScriptedPrompts::new([ScriptedResponse::Epic(Epic::new(
+            "name".to_owned(),
+            "description".to_owned(),
+        ))]);
 
-        nav.set_prompts(prompts);
+        nav.set_prompts(Box::new(prompts));
 
         nav.handle_action(Action::CreateEpic).unwrap();
 
@@ -231,19 +1032,18 @@ mod tests {
 
     #[test]
     fn handle_action_should_handle_update_epic() {
-        let db = Rc::new(JiraDatabase {
+        let db = Arc::new(JiraDatabase {
             database: Box::new(MockDB::new()),
         });
         let epic_id = db
             .create_epic(Epic::new("".to_owned(), "".to_owned()))
             .unwrap();
 
-        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
 
-        let mut prompts = Prompts::new();
-        prompts.update_status = Box::new(|| Some(Status::InProgress));
+        let prompts = ScriptedPrompts::new([ScriptedResponse::Status(Some(Status::InProgress))]);
 
-        nav.set_prompts(prompts);
+        nav.set_prompts(Box::new(prompts));
 
         nav.handle_action(Action::UpdateEpicStatus { epic_id })
             .unwrap();
@@ -257,19 +1057,18 @@ mod tests {
 
     #[test]
     fn handle_action_should_handle_delete_epic() {
-        let db = Rc::new(JiraDatabase {
+        let db = Arc::new(JiraDatabase {
             database: Box::new(MockDB::new()),
         });
         let epic_id = db
             .create_epic(Epic::new("".to_owned(), "".to_owned()))
             .unwrap();
 
-        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
 
-        let mut prompts = Prompts::new();
-        prompts.delete_epic = Box::new(|| true);
+        let prompts = ScriptedPrompts::new([ScriptedResponse::Confirm(true)]);
 
-        nav.set_prompts(prompts);
+        nav.set_prompts(Box::new(prompts));
 
         nav.handle_action(Action::DeleteEpic { epic_id }).unwrap();
 
@@ -279,19 +1078,21 @@ mod tests {
 
     #[test]
     fn handle_action_should_handle_create_story() {
-        let db = Rc::new(JiraDatabase {
+        let db = Arc::new(JiraDatabase {
             database: Box::new(MockDB::new()),
         });
         let epic_id = db
             .create_epic(Epic::new("".to_owned(), "".to_owned()))
             .unwrap();
 
-        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
 
-        let mut prompts = Prompts::new();
-        prompts.create_story = Box::new(|| Story::new("name".to_owned(), "description".to_owned()));
+        let prompts = ScriptedPrompts::new([ScriptedResponse::Story(Story::new(
+            "name".to_owned(),
+            "description".to_owned(),
+        ))]);
 
-        nav.set_prompts(prompts);
+        nav.set_prompts(Box::new(prompts));
 
         nav.handle_action(Action::CreateStory { epic_id }).unwrap();
 
@@ -303,9 +1104,99 @@ mod tests {
         assert_eq!(story.description, "description".to_owned());
     }
 
+    #[test]
+    fn dispatch_should_create_and_open_story() {
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
+
+        let prompts = ScriptedPrompts::new([ScriptedResponse::Story(Story::new(
+            "name".to_owned(),
+            "description".to_owned(),
+        ))]);
+        nav.set_prompts(Box::new(prompts));
+
+        let follow_ups = nav
+            .handle_action(Action::CreateStoryAndOpen { epic_id })
+            .unwrap();
+        let story_id = *db.read_db().unwrap().stories.keys().next().unwrap();
+        assert_eq!(
+            follow_ups,
+            vec![Action::NavigateToStoryDetail { epic_id, story_id }]
+        );
+
+        nav.set_prompts(Box::new(ScriptedPrompts::new([ScriptedResponse::Story(
+            Story::new("name".to_owned(), "description".to_owned()),
+        )])));
+        nav.dispatch(Action::CreateStoryAndOpen { epic_id })
+            .unwrap();
+
+        assert_eq!(db.read_db().unwrap().stories.len(), 2);
+        assert_eq!(nav.get_page_count(), 2);
+
+        let current_page = nav.get_current_page().unwrap();
+        let story_detail_page = current_page.as_any().downcast_ref::<StoryDetail>();
+        assert!(story_detail_page.is_some());
+    }
+
+    #[test]
+    fn dispatch_should_create_and_open_epic() {
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
+        nav.set_prompts(Box::new(ScriptedPrompts::new([ScriptedResponse::Epic(
+            Epic::new("name".to_owned(), "description".to_owned()),
+        )])));
+
+        nav.dispatch(Action::CreateEpicAndOpen).unwrap();
+
+        assert_eq!(db.read_db().unwrap().epics.len(), 1);
+
+        let current_page = nav.get_current_page().unwrap();
+        let epic_detail_page = current_page.as_any().downcast_ref::<EpicDetail>();
+        assert!(epic_detail_page.is_some());
+    }
+
+    #[test]
+    fn handle_action_should_handle_create_stories() {
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
+
+        let prompts = ScriptedPrompts::new([
+            ScriptedResponse::Stories(vec![
+                Story::new("one".to_owned(), "".to_owned()),
+                Story::new("two".to_owned(), "".to_owned()),
+            ]),
+            ScriptedResponse::Stories(vec![]),
+        ]);
+        nav.set_prompts(Box::new(prompts));
+
+        nav.handle_action(Action::CreateStories { epic_id })
+            .unwrap();
+        nav.handle_action(Action::CreateStories { epic_id })
+            .unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories.len(), 2);
+        assert_eq!(db_state.epics.get(&epic_id).unwrap().stories.len(), 2);
+    }
+
     #[test]
     fn handle_action_should_handle_update_story() {
-        let db = Rc::new(JiraDatabase {
+        let db = Arc::new(JiraDatabase {
             database: Box::new(MockDB::new()),
         });
         let epic_id = db
@@ -315,13 +1206,23 @@ mod tests {
             .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
             .unwrap();
 
-        let mut nav = Navigator::new(Rc::clone(&db));
+        let config = Config {
+            confirm: crate::config::ConfirmPolicy::DeleteAndClose,
+            ..Config::default()
+        };
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(config));
 
-        let mut prompts = Prompts::new();
-        prompts.update_status = Box::new(|| Some(Status::InProgress));
+        let prompts = ScriptedPrompts::new([
+            ScriptedResponse::Status(Some(Status::InProgress)),
+            ScriptedResponse::Status(Some(Status::Closed)),
+            ScriptedResponse::Confirm(false),
+        ]);
 
-        nav.set_prompts(prompts);
+        nav.set_prompts(Box::new(prompts));
 
+        nav.handle_action(Action::UpdateStoryStatus { story_id })
+            .unwrap();
+        // closing is confirmed once, and declined here
         nav.handle_action(Action::UpdateStoryStatus { story_id })
             .unwrap();
 
@@ -332,9 +1233,121 @@ mod tests {
         );
     }
 
+    #[test]
+    fn handle_action_should_set_story_status_without_prompting() {
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
+        nav.set_prompts(Box::new(ScriptedPrompts::new([])));
+
+        nav.handle_action(Action::SetStoryStatus {
+            story_id,
+            status: Status::Resolved,
+        })
+        .unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories[&story_id].status, Status::Resolved);
+    }
+
+    #[test]
+    fn handle_action_should_block_and_unblock_story() {
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
+        nav.set_prompts(Box::new(ScriptedPrompts::new([
+            ScriptedResponse::Text("waiting for the API key".to_owned()),
+            ScriptedResponse::Text("".to_owned()),
+        ])));
+
+        nav.handle_action(Action::BlockStory { story_id }).unwrap();
+        assert_eq!(
+            db.read_db().unwrap().stories[&story_id].blocker,
+            Some(Blocker::Reason("waiting for the API key".to_owned()))
+        );
+
+        nav.handle_action(Action::BlockStory { story_id }).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].blocker, None);
+    }
+
+    #[test]
+    fn handle_action_should_add_epic_note() {
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
+        nav.set_prompts(Box::new(ScriptedPrompts::new([
+            ScriptedResponse::Text("went with option b".to_owned()),
+            ScriptedResponse::Text("".to_owned()),
+        ])));
+
+        nav.handle_action(Action::AddEpicNote { epic_id }).unwrap();
+        nav.handle_action(Action::AddEpicNote { epic_id }).unwrap();
+
+        let notes = &db.read_db().unwrap().epics[&epic_id].notes;
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].text, "went with option b");
+    }
+
+    #[test]
+    fn handle_action_should_offer_close_out_report_when_closing_epic() {
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
+        nav.set_prompts(Box::new(ScriptedPrompts::new([ScriptedResponse::Confirm(
+            true,
+        )])));
+
+        nav.dispatch(Action::SetEpicStatus {
+            epic_id,
+            status: Status::Closed,
+        })
+        .unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+        assert!(nav
+            .get_current_page()
+            .unwrap()
+            .as_any()
+            .downcast_ref::<CloseOutPage>()
+            .is_some());
+
+        // closing an already closed epic does not ask again
+        nav.dispatch(Action::SetEpicStatus {
+            epic_id,
+            status: Status::Closed,
+        })
+        .unwrap();
+        assert_eq!(nav.get_page_count(), 2);
+    }
+
     #[test]
     fn handle_action_should_handle_delete_story() {
-        let db = Rc::new(JiraDatabase {
+        let db = Arc::new(JiraDatabase {
             database: Box::new(MockDB::new()),
         });
         let epic_id = db
@@ -344,12 +1357,11 @@ mod tests {
             .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
             .unwrap();
 
-        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
 
-        let mut prompts = Prompts::new();
-        prompts.delete_story = Box::new(|| true);
+        let prompts = ScriptedPrompts::new([ScriptedResponse::Confirm(true)]);
 
-        nav.set_prompts(prompts);
+        nav.set_prompts(Box::new(prompts));
 
         nav.handle_action(Action::DeleteStory { epic_id, story_id })
             .unwrap();
@@ -357,4 +1369,348 @@ mod tests {
         let db_state = db.read_db().unwrap();
         assert_eq!(db_state.stories.len(), 0);
     }
+
+    #[test]
+    fn handle_action_should_handle_merge_story() {
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let duplicate_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
+        nav.set_prompts(Box::new(ScriptedPrompts::new([
+            ScriptedResponse::Text("".to_owned()),
+            ScriptedResponse::Text(format!(":{duplicate_id}:")),
+        ])));
+
+        nav.handle_action(Action::MergeStory { story_id }).unwrap();
+        assert_eq!(db.read_db().unwrap().stories.len(), 2);
+
+        nav.handle_action(Action::MergeStory { story_id }).unwrap();
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories.keys().collect::<Vec<_>>(), vec![&story_id]);
+    }
+
+    #[test]
+    fn handle_action_should_close_sprint_with_retrospective() {
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+        db.start_sprint(Sprint::new("Sprint 1".to_owned(), 10))
+            .unwrap();
+
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
+        nav.set_prompts(Box::new(ScriptedPrompts::new([
+            ScriptedResponse::Text("pairing\nshort standups".to_owned()),
+            ScriptedResponse::Text("".to_owned()),
+            ScriptedResponse::Text("write down decisions".to_owned()),
+        ])));
+
+        nav.handle_action(Action::CloseSprint).unwrap();
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.current_sprint(), None);
+        assert_eq!(
+            db_state.sprints[0].retrospective,
+            Retrospective {
+                went_well: vec!["pairing".to_owned(), "short standups".to_owned()],
+                to_improve: vec![],
+                actions: vec!["write down decisions".to_owned()],
+            }
+        );
+    }
+
+    #[test]
+    fn handle_action_should_edit_custom_fields() {
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        db.set_story_field(story_id, "owner", Some("ana".to_owned()))
+            .unwrap();
+
+        let field = |name: &str, kind| crate::config::CustomField {
+            name: name.to_owned(),
+            kind,
+        };
+        let config = Config {
+            custom_fields: vec![
+                field("due", crate::config::FieldKind::Date),
+                field("cost", crate::config::FieldKind::Number),
+                field("owner", crate::config::FieldKind::String),
+                field("team", crate::config::FieldKind::String),
+            ],
+            ..Config::default()
+        };
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(config));
+        nav.set_prompts(Box::new(ScriptedPrompts::new([
+            ScriptedResponse::Text("2024-05-31".to_owned()),
+            ScriptedResponse::Text("a lot".to_owned()),
+            ScriptedResponse::Text("-".to_owned()),
+            ScriptedResponse::Text("".to_owned()),
+        ])));
+
+        nav.handle_action(Action::EditStoryFields { story_id })
+            .unwrap();
+        let fields = &db.read_db().unwrap().stories[&story_id].fields;
+        assert_eq!(
+            fields.iter().collect::<Vec<_>>(),
+            vec![(&"due".to_owned(), &"2024-05-31".to_owned())]
+        );
+    }
+
+    #[test]
+    fn dispatch_should_toggle_density_and_save_it() {
+        use crate::config::{Density, DensityPage};
+
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.json");
+        let config_path = config_path.to_str().unwrap();
+        let mut nav = Navigator::new(db, Arc::new(Config::default()));
+        nav.set_config_path(config_path);
+
+        nav.dispatch(Action::ToggleDensity {
+            page: DensityPage::Home,
+        })
+        .unwrap();
+
+        assert_eq!(nav.config.density.home.get(), Density::Detailed);
+        let saved = Config::load(config_path).unwrap().density;
+        assert_eq!(saved.home.get(), Density::Detailed);
+        assert_eq!(saved.epic_detail.get(), Density::Compact);
+    }
+
+    #[test]
+    fn dispatch_should_record_and_replay_macros() {
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.json");
+        let config_path = config_path.to_str().unwrap();
+
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
+        nav.set_config_path(config_path);
+        nav.set_prompts(Box::new(ScriptedPrompts::new([
+            ScriptedResponse::Story(Story::new("recorded".to_owned(), "".to_owned())),
+            ScriptedResponse::Text("g".to_owned()),
+            ScriptedResponse::Story(Story::new("replayed".to_owned(), "".to_owned())),
+        ])));
+
+        assert_eq!(nav.macro_action("@"), Some(Action::ToggleMacroRecording));
+        nav.dispatch(Action::ToggleMacroRecording).unwrap();
+        nav.dispatch(Action::NavigateToEpicDetail { epic_id })
+            .unwrap();
+        nav.dispatch(Action::CreateStory { epic_id }).unwrap();
+        assert_eq!(nav.recorded_actions(), Some(2));
+        nav.dispatch(Action::ToggleMacroRecording).unwrap();
+        assert_eq!(nav.recorded_actions(), None);
+
+        let saved = Config::load(config_path).unwrap().macros;
+        assert_eq!(
+            saved["g"],
+            vec![
+                Action::NavigateToEpicDetail { epic_id },
+                Action::CreateStory { epic_id }
+            ]
+        );
+
+        let action = nav.macro_action("g").unwrap();
+        nav.dispatch(action).unwrap();
+        assert_eq!(nav.get_page_count(), 3);
+        let mut names = db
+            .read_db()
+            .unwrap()
+            .stories
+            .values()
+            .map(|s| s.name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["recorded", "replayed"]);
+        assert_eq!(nav.macro_action("h"), None);
+    }
+
+    #[test]
+    fn handle_action_should_confirm_according_to_policy() {
+        use crate::config::ConfirmPolicy;
+
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let nav_with = |confirm| {
+            let config = Config {
+                confirm,
+                ..Config::default()
+            };
+            Navigator::new(Arc::clone(&db), Arc::new(config))
+        };
+        let close = Action::SetStoryStatus {
+            story_id,
+            status: Status::Closed,
+        };
+
+        // no scripted responses, so any confirmation prompt fails
+        let mut nav = nav_with(ConfirmPolicy::Delete);
+        nav.handle_action(close.clone()).unwrap();
+        assert_eq!(
+            db.read_db().unwrap().stories[&story_id].status,
+            Status::Closed
+        );
+
+        let mut nav = nav_with(ConfirmPolicy::DeleteAndClose);
+        nav.handle_action(Action::SetStoryStatus {
+            story_id,
+            status: Status::Open,
+        })
+        .unwrap();
+        nav.set_prompts(Box::new(ScriptedPrompts::new([ScriptedResponse::Confirm(
+            false,
+        )])));
+        nav.handle_action(close).unwrap();
+        assert_eq!(
+            db.read_db().unwrap().stories[&story_id].status,
+            Status::Open
+        );
+
+        let mut nav = nav_with(ConfirmPolicy::Everything);
+        nav.set_prompts(Box::new(ScriptedPrompts::new([ScriptedResponse::Confirm(
+            false,
+        )])));
+        nav.handle_action(Action::ToggleStoryWatch { story_id })
+            .unwrap();
+        assert!(!db.read_db().unwrap().stories[&story_id].watched);
+
+        let mut nav = nav_with(ConfirmPolicy::Nothing);
+        nav.handle_action(Action::DiscardStory { epic_id, story_id })
+            .unwrap();
+        assert!(db.read_db().unwrap().stories.is_empty());
+    }
+
+    #[test]
+    fn handle_action_should_restore_view_state_of_epic_pages() {
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_ids = (0..3)
+            .map(|_| {
+                db.create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
+        let selected_story = |nav: &Navigator| {
+            nav.get_current_page()
+                .unwrap()
+                .view_state()
+                .unwrap()
+                .selected_item
+        };
+
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id })
+            .unwrap();
+        for input in ["j", "j", "j"] {
+            nav.get_current_page().unwrap().handle_input(input).unwrap();
+        }
+        assert_eq!(selected_story(&nav), Some(story_ids[2]));
+
+        // the selection follows the story when the list changed meanwhile
+        nav.handle_action(Action::NavigateToStoryDetail {
+            epic_id,
+            story_id: story_ids[2],
+        })
+        .unwrap();
+        db.delete_story(epic_id, story_ids[0]).unwrap();
+        nav.handle_action(Action::NavigateToPreviousPage).unwrap();
+        assert_eq!(selected_story(&nav), Some(story_ids[2]));
+
+        // and is brought back when the epic is opened again
+        nav.handle_action(Action::NavigateToPreviousPage).unwrap();
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id })
+            .unwrap();
+        assert_eq!(selected_story(&nav), Some(story_ids[2]));
+    }
+
+    #[test]
+    fn handle_action_should_run_middleware_around_actions() {
+        use std::sync::Mutex;
+
+        struct NoDeletes(Arc<Mutex<Vec<String>>>);
+
+        impl Middleware for NoDeletes {
+            fn before(&mut self, action: &Action) -> Result<bool> {
+                Ok(!matches!(action, Action::DeleteStory { .. }))
+            }
+            fn after(&mut self, action: &Action, error: Option<&anyhow::Error>) -> Result<()> {
+                let outcome = error.map_or("ok".to_owned(), |e| e.to_string());
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("{action:?}: {outcome}"));
+                Ok(())
+            }
+        }
+
+        let db = Arc::new(JiraDatabase {
+            database: Box::new(MockDB::new()),
+        });
+        let epic_id = db
+            .create_epic(Epic::new("".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let seen = Arc::new(Mutex::new(vec![]));
+        let mut nav = Navigator::new(Arc::clone(&db), Arc::new(Config::default()));
+        nav.add_middleware(Box::new(NoDeletes(Arc::clone(&seen))));
+        nav.set_prompts(Box::new(ScriptedPrompts::new([ScriptedResponse::Status(
+            Some(Status::Resolved),
+        )])));
+
+        nav.handle_action(Action::DeleteStory { epic_id, story_id })
+            .unwrap();
+        nav.handle_action(Action::UpdateStoryStatus { story_id })
+            .unwrap();
+        assert!(nav
+            .handle_action(Action::ToggleStoryWatch {
+                story_id: StoryId(99)
+            })
+            .is_err());
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories[&story_id].status, Status::Resolved);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                format!("UpdateStoryStatus {{ story_id: {story_id:?} }}: ok"),
+                "ToggleStoryWatch { story_id: StoryId(99) }: failed to watch story: 99".to_owned(),
+            ]
+        );
+    }
 }
@@ -0,0 +1,111 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::config::Config;
+use crate::db::JiraDatabase;
+use crate::io_utils::get_user_input;
+use crate::models::Epic;
+
+const DEFAULT_DB_PATH: &str = "./db.json";
+
+/// Answers of the first-run wizard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Onboarding {
+    pub database: String,
+    pub mouse: bool,
+    pub first_epic: Option<String>,
+}
+
+impl Onboarding {
+    /// Returns whether neither the config file nor the default database exist,
+    /// i.e. the app has not been used from this directory yet.
+    pub fn is_first_run(config_path: &str) -> bool {
+        !Path::new(config_path).exists() && !Path::new(DEFAULT_DB_PATH).exists()
+    }
+
+    pub fn ask() -> Self {
+        println!("Welcome! No database or config file was found, so let's set them up.");
+        println!();
+
+        println!("Where should the database be stored? [{DEFAULT_DB_PATH}]:");
+        let database = Some(get_user_input())
+            .filter(|path| !path.is_empty())
+            .unwrap_or_else(|| DEFAULT_DB_PATH.to_owned());
+
+        println!(
+            "Open list rows by clicking them? This keeps the terminal from selecting text [y/N]:"
+        );
+        let mouse = matches!(get_user_input().as_str(), "y" | "Y");
+
+        println!("Name of your first epic (empty to skip):");
+        let first_epic = Some(get_user_input()).filter(|name| !name.is_empty());
+
+        Self {
+            database,
+            mouse,
+            first_epic,
+        }
+    }
+
+    /// Writes the config file, creates the database with the first epic and
+    /// returns the database path.
+    pub fn apply(self, config_path: &str) -> Result<String> {
+        let config = Config {
+            database: Some(self.database.clone()),
+            mouse: self.mouse,
+            ..Config::default()
+        };
+        fs::write(config_path, serde_json::to_string_pretty(&config)?)
+            .with_context(|| format!("failed to write config file: {config_path}"))?;
+
+        if let Some(parent) = Path::new(&self.database).parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("failed to create database directory: {}", parent.display())
+            })?;
+        }
+        let db = JiraDatabase::new(&self.database)?;
+        if let Some(name) = self.first_epic {
+            db.create_epic(Epic::new(name, String::new()))?;
+        }
+
+        Ok(self.database)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_should_write_config_and_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.json");
+        let config_path = config_path.to_str().unwrap();
+        let database = dir.path().join("boards/work.json");
+
+        let path = Onboarding {
+            database: database.to_str().unwrap().to_owned(),
+            mouse: true,
+            first_epic: Some("Launch".to_owned()),
+        }
+        .apply(config_path)
+        .unwrap();
+
+        let config = Config::load(config_path).unwrap();
+        assert_eq!(config.database.as_deref(), Some(path.as_str()));
+        assert!(config.mouse);
+        assert!(!Onboarding::is_first_run(config_path));
+
+        let db_state = JiraDatabase::new(&path).unwrap().read_db().unwrap();
+        assert_eq!(
+            db_state
+                .epics
+                .values()
+                .map(|e| e.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Launch"]
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDate;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+
+use crate::config::EmailConfig;
+use crate::models::DBState;
+use crate::web;
+
+/// Port of SMTP submission with STARTTLS.
+const SUBMISSION_PORT: u16 = 587;
+
+/// Builds the status report mail, the page served by `view` as an HTML body.
+pub fn message(
+    email: &EmailConfig,
+    board: &str,
+    db_state: &DBState,
+    today: NaiveDate,
+) -> Result<Message> {
+    if email.to.is_empty() {
+        return Err(anyhow!("no recipients configured for the report"));
+    }
+    let mut builder = Message::builder()
+        .from(
+            email
+                .from
+                .parse()
+                .with_context(|| format!("invalid sender address '{}'", email.from))?,
+        )
+        .subject(format!("Status report of {board} for {today}"))
+        .header(ContentType::TEXT_HTML);
+    for to in &email.to {
+        builder = builder.to(to
+            .parse()
+            .with_context(|| format!("invalid recipient address '{to}'"))?);
+    }
+    builder
+        .body(web::render(db_state))
+        .map_err(|e| anyhow!(format!("failed to build the report mail: {e}")))
+}
+
+/// Sends the mail through the configured SMTP server.
+pub fn send(email: &EmailConfig, message: &Message) -> Result<()> {
+    let mut transport = SmtpTransport::starttls_relay(&email.smtp_host)
+        .with_context(|| format!("invalid SMTP host '{}'", email.smtp_host))?
+        .port(email.port.unwrap_or(SUBMISSION_PORT));
+    let password = email
+        .password
+        .clone()
+        .or_else(|| std::env::var("SCRUMTASK_SMTP_PASSWORD").ok());
+    if let (Some(username), Some(password)) = (&email.username, password) {
+        transport = transport.credentials(Credentials::new(username.clone(), password));
+    }
+    transport.build().send(message).map_err(|e| {
+        anyhow!(format!(
+            "sending the report via {} failed: {e}",
+            email.smtp_host
+        ))
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Epic, EpicId};
+
+    fn email(to: &[&str]) -> EmailConfig {
+        EmailConfig {
+            smtp_host: "smtp.example.com".to_owned(),
+            port: None,
+            username: None,
+            password: None,
+            from: "Board <board@example.com>".to_owned(),
+            to: to.iter().map(|to| to.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn message_should_mail_the_board_page_to_every_recipient() {
+        let mut db_state = DBState::new();
+        db_state
+            .epics
+            .insert(EpicId(1), Epic::new("Checkout".to_owned(), "".to_owned()));
+        let today = NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+
+        let sent = message(
+            &email(&["a@example.com", "b@example.com"]),
+            "db.json",
+            &db_state,
+            today,
+        )
+        .unwrap();
+
+        let mail = String::from_utf8(sent.formatted()).unwrap();
+        assert!(mail.contains("Subject: Status report of db.json for 2024-05-10"));
+        assert!(mail.contains("To: a@example.com, b@example.com"));
+        assert!(mail.contains("Content-Type: text/html; charset=utf-8"));
+        assert!(mail.contains("Checkout"));
+        assert!(message(&email(&[]), "db.json", &db_state, today).is_err());
+        assert!(message(&email(&["not an address"]), "db.json", &db_state, today).is_err());
+    }
+}
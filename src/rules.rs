@@ -0,0 +1,321 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+
+use crate::config::{CustomField, FieldKind, Rule, RuleAction, RuleItems};
+use crate::db::JiraDatabase;
+use crate::models::{DBState, EpicId, Status, StoryId};
+
+/// A change a rule makes to one item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleChange {
+    EpicStatus {
+        epic_id: EpicId,
+        status: Status,
+    },
+    StoryStatus {
+        story_id: StoryId,
+        status: Status,
+    },
+    StoryField {
+        story_id: StoryId,
+        name: String,
+        value: String,
+    },
+    StoryEscalated {
+        story_id: StoryId,
+        name: String,
+        from: Option<String>,
+        to: String,
+    },
+}
+
+/// Returns the changes the rules make to the board, in rule order. Items that
+/// already are as a rule would make them are left out, so evaluating the rules
+/// again after applying the changes finds nothing to do.
+pub fn evaluate(
+    rules: &[Rule],
+    custom_fields: &[CustomField],
+    db_state: &DBState,
+    now: DateTime<Utc>,
+) -> Vec<RuleChange> {
+    let mut changes = vec![];
+    for rule in rules {
+        match rule.items {
+            RuleItems::Epics => {
+                for (epic_id, epic) in db_state.epics.iter().sorted_by_key(|(id, _)| **id) {
+                    if !rule.matches(&epic.status, epic.status_changed_at, now) {
+                        continue;
+                    }
+                    if let RuleAction::SetStatus(status) = &rule.action {
+                        if *status != epic.status {
+                            changes.push(RuleChange::EpicStatus {
+                                epic_id: *epic_id,
+                                status: status.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+            RuleItems::Stories => {
+                for (story_id, story) in db_state.stories.iter().sorted_by_key(|(id, _)| **id) {
+                    if !rule.matches(&story.status, story.status_changed_at, now) {
+                        continue;
+                    }
+                    match &rule.action {
+                        RuleAction::SetStatus(status) if *status != story.status => {
+                            changes.push(RuleChange::StoryStatus {
+                                story_id: *story_id,
+                                status: status.clone(),
+                            })
+                        }
+                        RuleAction::SetField { name, value }
+                            if story.fields.get(name) != Some(value) =>
+                        {
+                            changes.push(RuleChange::StoryField {
+                                story_id: *story_id,
+                                name: name.clone(),
+                                value: value.clone(),
+                            })
+                        }
+                        RuleAction::Escalate(name)
+                            if now.signed_duration_since(story.updated_at).num_days()
+                                >= rule.days_in_status =>
+                        {
+                            let from = story.fields.get(name);
+                            if let Some(to) = escalated(custom_fields, name, from) {
+                                changes.push(RuleChange::StoryEscalated {
+                                    story_id: *story_id,
+                                    name: name.clone(),
+                                    from: from.cloned(),
+                                    to,
+                                })
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    changes
+}
+
+/// The value of an enum field one step towards its first, the highest, value.
+/// No value is raised to the last one. Returns `None` for the highest value.
+fn escalated(custom_fields: &[CustomField], name: &str, value: Option<&String>) -> Option<String> {
+    let FieldKind::Enum { values } = &custom_fields.iter().find(|f| f.name == name)?.kind else {
+        return None;
+    };
+    let index = match value {
+        Some(value) => values.iter().position(|v| v == value)?,
+        None => values.len(),
+    };
+    values.get(index.checked_sub(1)?).cloned()
+}
+
+/// Applies the changes of the rules in one write and returns them.
+pub fn apply(
+    db: &JiraDatabase,
+    rules: &[Rule],
+    custom_fields: &[CustomField],
+    now: DateTime<Utc>,
+) -> Result<Vec<RuleChange>> {
+    let changes = evaluate(rules, custom_fields, &db.read_db()?, now);
+    if changes.is_empty() {
+        return Ok(changes);
+    }
+
+    db.transaction(|db| {
+        for change in &changes {
+            match change {
+                RuleChange::EpicStatus { epic_id, status } => {
+                    db.update_epic_status(*epic_id, status.clone())?
+                }
+                RuleChange::StoryStatus { story_id, status } => {
+                    db.update_story_status(*story_id, status.clone())?
+                }
+                RuleChange::StoryField {
+                    story_id,
+                    name,
+                    value,
+                } => db.set_story_field(*story_id, name, Some(value.clone()))?,
+                RuleChange::StoryEscalated {
+                    story_id, name, to, ..
+                } => db.set_story_field(*story_id, name, Some(to.clone()))?,
+            }
+        }
+        Ok(changes)
+    })
+}
+
+/// Describes the escalations among the changes, e.g. `E1-S2 Pay: priority
+/// low → medium`.
+pub fn escalations(changes: &[RuleChange], db_state: &DBState) -> Vec<String> {
+    changes
+        .iter()
+        .filter_map(|change| {
+            let RuleChange::StoryEscalated {
+                story_id,
+                name,
+                from,
+                to,
+            } = change
+            else {
+                return None;
+            };
+            let label = db_state
+                .story_epic(*story_id)
+                .map_or(story_id.to_string(), |epic_id| {
+                    db_state.story_label(epic_id, *story_id)
+                });
+            let story_name = db_state.stories.get(story_id).map_or("", |s| &s.name);
+            Some(format!(
+                "{label} {story_name}: {name} {} → {to}",
+                from.as_deref().unwrap_or("none")
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::models::{Epic, Story};
+    use chrono::Duration;
+
+    #[test]
+    fn evaluate_should_change_items_long_enough_in_status() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "custom_fields": [{ "name": "health", "type": "string" }],
+                "rules": [
+                    { "items": "stories", "status": "InProgress", "days_in_status": 14,
+                      "set_field": { "name": "health", "value": "stale" } },
+                    { "items": "epics", "status": "Resolved", "days_in_status": 30,
+                      "set_status": "Closed" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let now = Utc::now();
+        let mut db_state = DBState::new();
+        for (id, status, days) in [(1, Status::Resolved, 31), (2, Status::Resolved, 3)] {
+            let mut epic = Epic::new("".to_owned(), "".to_owned());
+            epic.status = status;
+            epic.status_changed_at = now - Duration::days(days);
+            db_state.epics.insert(EpicId(id), epic);
+        }
+        for (id, status, days) in [
+            (3, Status::InProgress, 20),
+            (4, Status::InProgress, 2),
+            (5, Status::Open, 20),
+        ] {
+            let mut story = Story::new("".to_owned(), "".to_owned());
+            story.status = status;
+            story.status_changed_at = now - Duration::days(days);
+            db_state.stories.insert(StoryId(id), story);
+        }
+
+        let changes = evaluate(&config.rules, &config.custom_fields, &db_state, now);
+        assert_eq!(
+            changes,
+            vec![
+                RuleChange::StoryField {
+                    story_id: StoryId(3),
+                    name: "health".to_owned(),
+                    value: "stale".to_owned()
+                },
+                RuleChange::EpicStatus {
+                    epic_id: EpicId(1),
+                    status: Status::Closed
+                },
+            ]
+        );
+
+        db_state
+            .stories
+            .get_mut(&StoryId(3))
+            .unwrap()
+            .fields
+            .insert("health".to_owned(), "stale".to_owned());
+        db_state.epics.get_mut(&EpicId(1)).unwrap().status = Status::Closed;
+        assert!(evaluate(&config.rules, &config.custom_fields, &db_state, now).is_empty());
+    }
+
+    #[test]
+    fn evaluate_should_escalate_stories_unchanged_long_enough() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "custom_fields": [
+                    { "name": "priority", "type": "enum", "values": ["high", "medium", "low"] }
+                ],
+                "rules": [
+                    { "items": "stories", "status": "Open", "days_in_status": 30,
+                      "escalate": "priority" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let now = Utc::now();
+        let mut db_state = DBState::new();
+        db_state
+            .epics
+            .insert(EpicId(1), Epic::new("".to_owned(), "".to_owned()));
+        for (id, priority, days_changed) in [
+            (2, None, 40),
+            (3, Some("low"), 40),
+            (4, Some("low"), 5),
+            (5, Some("high"), 40),
+        ] {
+            let mut story = Story::new(format!("story {id}"), "".to_owned());
+            story.status_changed_at = now - Duration::days(40);
+            story.updated_at = now - Duration::days(days_changed);
+            if let Some(priority) = priority {
+                story
+                    .fields
+                    .insert("priority".to_owned(), priority.to_owned());
+            }
+            db_state.stories.insert(StoryId(id), story);
+            db_state
+                .epics
+                .get_mut(&EpicId(1))
+                .unwrap()
+                .stories
+                .push(StoryId(id));
+        }
+
+        let changes = evaluate(&config.rules, &config.custom_fields, &db_state, now);
+        assert_eq!(
+            changes,
+            vec![
+                RuleChange::StoryEscalated {
+                    story_id: StoryId(2),
+                    name: "priority".to_owned(),
+                    from: None,
+                    to: "low".to_owned()
+                },
+                RuleChange::StoryEscalated {
+                    story_id: StoryId(3),
+                    name: "priority".to_owned(),
+                    from: Some("low".to_owned()),
+                    to: "medium".to_owned()
+                },
+            ]
+        );
+        assert_eq!(
+            escalations(&changes, &db_state),
+            vec![
+                format!(
+                    "{} story 2: priority none → low",
+                    db_state.story_label(EpicId(1), StoryId(2))
+                ),
+                format!(
+                    "{} story 3: priority low → medium",
+                    db_state.story_label(EpicId(1), StoryId(3))
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt::Display;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use anyhow::Result;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+static EXIT_HOOKS: Mutex<Vec<Box<dyn Fn() + Send>>> = Mutex::new(Vec::new());
+
+/// Returned by prompts that were interrupted with Ctrl-C.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Handles SIGINT, SIGTERM and SIGHUP on a background thread.
+///
+/// SIGINT behaves like pressing Ctrl-C at a prompt, SIGTERM and SIGHUP exit
+/// right away. Exiting runs the exit hooks and waits for a running database
+/// write to finish so the file is never left half written.
+#[cfg(unix)]
+pub fn install() -> Result<()> {
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+    use signal_hook::iterator::Signals;
+
+    let mut signals = Signals::new([SIGINT, SIGTERM, SIGHUP])?;
+
+    std::thread::spawn(move || {
+        for signal in signals.forever() {
+            if signal == SIGINT {
+                interrupt();
+                println!();
+                println!("cancelled, press enter to continue (Ctrl-C again to quit)");
+            } else {
+                exit(128 + signal);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Ctrl-C is read as a key event by the terminal input on other platforms.
+#[cfg(not(unix))]
+pub fn install() -> Result<()> {
+    Ok(())
+}
+
+/// Marks the current prompt as cancelled. A second interrupt before the prompt
+/// was answered exits the process.
+pub fn interrupt() {
+    if INTERRUPTED.swap(true, Ordering::SeqCst) {
+        exit(130);
+    }
+}
+
+/// Runs `hook` before the process exits on a signal, e.g. to write changes
+/// that are still pending.
+pub fn on_exit(hook: impl Fn() + Send + 'static) {
+    exit_hooks().push(Box::new(hook));
+}
+
+fn exit_hooks() -> MutexGuard<'static, Vec<Box<dyn Fn() + Send>>> {
+    EXIT_HOOKS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn run_exit_hooks() {
+    for hook in exit_hooks().iter() {
+        hook();
+    }
+}
+
+fn exit(code: i32) -> ! {
+    // the hooks write through the write guard themselves
+    run_exit_hooks();
+    let _guard = write_guard();
+    crate::io_utils::restore_terminal();
+    process::exit(code)
+}
+
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Returns whether Ctrl-C was pressed since the last call and resets the flag.
+pub fn take_interrupt() -> bool {
+    INTERRUPTED.swap(false, Ordering::SeqCst)
+}
+
+/// Returns `value` unless the prompt that produced it was interrupted.
+pub fn check_interrupt<T>(value: T) -> Result<T> {
+    if take_interrupt() {
+        Err(Cancelled.into())
+    } else {
+        Ok(value)
+    }
+}
+
+/// Held while writing the database so that signals can't terminate the process mid-write.
+pub fn write_guard() -> MutexGuard<'static, ()> {
+    WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_interrupt_should_cancel_once() {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+
+        let result = check_interrupt(1);
+        assert!(result.unwrap_err().is::<Cancelled>());
+        assert_eq!(check_interrupt(1).unwrap(), 1);
+    }
+
+    #[test]
+    fn exit_hooks_should_write_debounced_changes() {
+        use crate::db::{read_db_file, JiraDatabase};
+        use crate::models::Epic;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("board.json");
+        let path = path.to_str().unwrap();
+        let db = Arc::new(
+            JiraDatabase::new(path)
+                .unwrap()
+                .with_debounce(Duration::from_secs(3600)),
+        );
+        JiraDatabase::flush_on_exit(&db);
+        db.create_epic(Epic::new("pending".to_owned(), "".to_owned()))
+            .unwrap();
+        assert!(read_db_file(path).unwrap().epics.is_empty());
+
+        // exiting on a signal skips dropping the database
+        run_exit_hooks();
+        assert_eq!(read_db_file(path).unwrap().epics.len(), 1);
+    }
+}
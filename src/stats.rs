@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs;
+
+use chrono::{DateTime, Local};
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::models::{DBState, EpicId, Status};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EpicSize {
+    pub id: EpicId,
+    pub name: String,
+    pub stories: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BoardStats {
+    pub epics: usize,
+    pub stories: usize,
+    pub last_item_id: u32,
+    pub file_size: Option<u64>,
+    pub last_modified: Option<DateTime<Local>>,
+    pub largest_epics: Vec<EpicSize>,
+    pub epic_status: BTreeMap<Status, usize>,
+    pub story_status: BTreeMap<Status, usize>,
+}
+
+impl BoardStats {
+    /// Collects the stats of a board, with the size and age of its file at
+    /// `path` if it has one.
+    pub fn collect(db_state: &DBState, path: Option<&str>) -> Self {
+        let metadata = path.and_then(|path| fs::metadata(path).ok());
+
+        let largest_epics = db_state
+            .epics
+            .iter()
+            .map(|(id, epic)| EpicSize {
+                id: *id,
+                name: epic.name.clone(),
+                stories: epic.stories.len(),
+            })
+            .sorted_by_key(|e| (std::cmp::Reverse(e.stories), e.id))
+            .take(5)
+            .collect();
+
+        Self {
+            epics: db_state.epics.len(),
+            stories: db_state.stories.len(),
+            last_item_id: db_state.last_item_id,
+            file_size: metadata.as_ref().map(|m| m.len()),
+            last_modified: metadata
+                .and_then(|m| m.modified().ok())
+                .map(DateTime::<Local>::from),
+            largest_epics,
+            epic_status: db_state
+                .epics
+                .values()
+                .map(|e| e.status.clone())
+                .counts()
+                .into_iter()
+                .collect(),
+            story_status: db_state
+                .stories
+                .values()
+                .map(|s| s.status.clone())
+                .counts()
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+impl Display for BoardStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "epics:          {}", self.epics)?;
+        writeln!(f, "stories:        {}", self.stories)?;
+        writeln!(f, "last item id:   {}", self.last_item_id)?;
+        if let Some(size) = self.file_size {
+            writeln!(f, "file size:      {size} bytes")?;
+        }
+        if let Some(modified) = self.last_modified {
+            writeln!(
+                f,
+                "last modified:  {}",
+                modified.format("%Y-%m-%d %H:%M:%S")
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "largest epics:")?;
+        for epic in &self.largest_epics {
+            writeln!(
+                f,
+                "  {:>6}  {} ({} stories)",
+                epic.id, epic.name, epic.stories
+            )?;
+        }
+
+        for (title, distribution) in [
+            ("epic status:", &self.epic_status),
+            ("story status:", &self.story_status),
+        ] {
+            writeln!(f)?;
+            writeln!(f, "{title}")?;
+            for (status, count) in distribution {
+                writeln!(f, "  {:<14}{count}", status.to_string())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_utils::MockDB, JiraDatabase};
+    use crate::models::{Epic, Story};
+
+    #[test]
+    fn collect_should_count_items_and_statuses() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let small = db
+            .create_epic(Epic::new("small".to_owned(), "".to_owned()))
+            .unwrap();
+        let large = db
+            .create_epic(Epic::new("large".to_owned(), "".to_owned()))
+            .unwrap();
+        db.create_story(Story::new("".to_owned(), "".to_owned()), small)
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_owned(), "".to_owned()), large)
+            .unwrap();
+        db.create_story(Story::new("".to_owned(), "".to_owned()), large)
+            .unwrap();
+        db.update_story_status(story_id, Status::Closed).unwrap();
+
+        let stats = BoardStats::collect(&db.read_db().unwrap(), None);
+
+        assert_eq!(stats.epics, 2);
+        assert_eq!(stats.stories, 3);
+        assert_eq!(stats.file_size, None);
+        assert_eq!(
+            stats.largest_epics,
+            vec![
+                EpicSize {
+                    id: large,
+                    name: "large".to_owned(),
+                    stories: 2
+                },
+                EpicSize {
+                    id: small,
+                    name: "small".to_owned(),
+                    stories: 1
+                },
+            ]
+        );
+        assert_eq!(stats.epic_status, BTreeMap::from([(Status::Open, 2)]));
+        assert_eq!(
+            stats.story_status,
+            BTreeMap::from([(Status::Open, 2), (Status::Closed, 1)])
+        );
+        assert!(stats.to_string().contains("stories:        3"));
+    }
+}
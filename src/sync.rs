@@ -0,0 +1,1265 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use sha2::{Digest, Sha256};
+
+use crate::config::RemoteConfig;
+use crate::db::{exclusive_access, read_db_file, write_db_file, JiraDatabase};
+use crate::diff::BoardDiff;
+use crate::models::{
+    Activity, ActivityEvent, DBState, EpicId, IdScheme, RecurringStory, Sprint, StoryId,
+};
+use crate::ui::get_elapsed_string;
+
+/// A single file on a remote storage holding a copy of the database.
+pub trait Remote: Send {
+    /// Returns the remote contents, or `None` if nothing was stored yet.
+    fn fetch(&self) -> Result<Option<Vec<u8>>>;
+    fn store(&self, contents: &[u8]) -> Result<()>;
+}
+
+pub fn open_remote(config: &RemoteConfig) -> Result<Box<dyn Remote>> {
+    match config {
+        RemoteConfig::Webdav {
+            url,
+            username,
+            password,
+        } => Ok(Box::new(WebDavRemote {
+            url: url.clone(),
+            username: username.clone(),
+            password: password
+                .clone()
+                .or_else(|| std::env::var("SCRUMTASK_WEBDAV_PASSWORD").ok()),
+        })),
+        RemoteConfig::S3 {
+            endpoint,
+            bucket,
+            key,
+            region,
+            access_key,
+            secret_key,
+        } => {
+            let credential = |value: &Option<String>, var: &str| {
+                value
+                    .clone()
+                    .or_else(|| std::env::var(var).ok())
+                    .ok_or_else(|| anyhow!(format!("missing S3 credentials, set {var}")))
+            };
+            Ok(Box::new(S3Remote {
+                endpoint: endpoint.trim_end_matches('/').to_owned(),
+                bucket: bucket.clone(),
+                key: key.trim_start_matches('/').to_owned(),
+                region: region.clone(),
+                access_key: credential(access_key, "AWS_ACCESS_KEY_ID")?,
+                secret_key: credential(secret_key, "AWS_SECRET_ACCESS_KEY")?,
+            }))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncOutcome {
+    UpToDate,
+    Pushed,
+    Pulled,
+    /// Both sides changed since the last sync; the newer one was kept and the
+    /// other one saved next to the database file.
+    Conflict {
+        kept: &'static str,
+        conflict_copy: String,
+    },
+}
+
+impl Display for SyncOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UpToDate => write!(f, "already up to date"),
+            Self::Pushed => write!(f, "pushed local changes"),
+            Self::Pulled => write!(f, "pulled remote changes"),
+            Self::Conflict {
+                kept,
+                conflict_copy,
+            } => write!(
+                f,
+                "both copies changed, kept the {kept} one and saved the other to {conflict_copy}"
+            ),
+        }
+    }
+}
+
+fn base_path(db_path: &str) -> String {
+    format!("{db_path}.sync-base")
+}
+
+/// Whether the database file was synced with a remote copy before.
+pub fn has_base(db_path: &str) -> bool {
+    Path::new(&base_path(db_path)).exists()
+}
+
+/// Brings the database file and the remote copy in line. The contents of the
+/// last sync are kept in a `.sync-base` file next to the database to tell which
+/// side changed; if both did, the last write wins.
+pub fn sync(db_path: &str, remote: &dyn Remote, now: DateTime<Utc>) -> Result<SyncOutcome> {
+    let base_path = base_path(db_path);
+    let base = fs::read(&base_path).ok();
+    let local = if Path::new(db_path).exists() {
+        Some(fs::read(db_path).with_context(|| format!("failed to read {db_path}"))?)
+    } else {
+        None
+    };
+    let remote_contents = remote.fetch()?;
+
+    let outcome = match (local, remote_contents) {
+        (None, None) => {
+            return Err(anyhow!(format!(
+                "nothing to sync: {db_path} does not exist"
+            )))
+        }
+        (Some(local), None) => {
+            remote.store(&local)?;
+            fs::write(&base_path, &local)?;
+            SyncOutcome::Pushed
+        }
+        (None, Some(remote_contents)) => {
+            pull(db_path, &base_path, &remote_contents)?;
+            SyncOutcome::Pulled
+        }
+        (Some(local), Some(remote_contents)) if local == remote_contents => {
+            fs::write(&base_path, &local)?;
+            SyncOutcome::UpToDate
+        }
+        (Some(local), Some(remote_contents)) if base.as_ref() == Some(&remote_contents) => {
+            remote.store(&local)?;
+            fs::write(&base_path, &local)?;
+            SyncOutcome::Pushed
+        }
+        (Some(local), Some(remote_contents)) if base.as_ref() == Some(&local) => {
+            pull(db_path, &base_path, &remote_contents)?;
+            SyncOutcome::Pulled
+        }
+        (Some(local), Some(remote_contents)) => {
+            let conflict_copy = format!("{db_path}.conflict-{}", now.format("%Y%m%dT%H%M%S"));
+            if last_modified(&remote_contents)? > last_modified(&local)? {
+                fs::write(&conflict_copy, &local)?;
+                pull(db_path, &base_path, &remote_contents)?;
+                SyncOutcome::Conflict {
+                    kept: "remote",
+                    conflict_copy,
+                }
+            } else {
+                fs::write(&conflict_copy, &remote_contents)?;
+                remote.store(&local)?;
+                fs::write(&base_path, &local)?;
+                SyncOutcome::Conflict {
+                    kept: "local",
+                    conflict_copy,
+                }
+            }
+        }
+    };
+
+    Ok(outcome)
+}
+
+fn pull(db_path: &str, base_path: &str, contents: &[u8]) -> Result<()> {
+    last_modified(contents).context("remote copy is not a valid database")?;
+    let _guard = crate::signals::write_guard();
+    fs::write(db_path, contents).with_context(|| format!("failed to write {db_path}"))?;
+    fs::write(base_path, contents)?;
+    Ok(())
+}
+
+fn last_modified(contents: &[u8]) -> Result<Option<DateTime<Utc>>> {
+    let db_state: DBState = serde_json::from_slice(contents)?;
+    Ok(db_state.last_modified())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncStatus {
+    NotSynced,
+    Syncing,
+    Synced(DateTime<Utc>),
+    /// The last attempt failed, e.g. because the remote could not be reached.
+    Offline(String),
+}
+
+impl SyncStatus {
+    pub fn line(&self, now: DateTime<Utc>) -> String {
+        match self {
+            Self::NotSynced => "not synced yet".to_owned(),
+            Self::Syncing => "syncing...".to_owned(),
+            Self::Synced(at) => format!("synced {} ago", get_elapsed_string(*at, now)),
+            Self::Offline(error) => format!("offline: {error}"),
+        }
+    }
+}
+
+/// Syncs the database with the remote on a background thread every `interval`
+/// and whenever a sync is requested. The thread stops when the worker is dropped.
+pub struct SyncWorker {
+    status: Arc<Mutex<SyncStatus>>,
+    requests: Sender<()>,
+}
+
+impl SyncWorker {
+    pub fn start(db: Arc<JiraDatabase>, remote: Box<dyn Remote>, interval: Duration) -> Self {
+        let status = Arc::new(Mutex::new(SyncStatus::NotSynced));
+        let (requests, pending) = mpsc::channel();
+
+        let shared_status = Arc::clone(&status);
+        thread::spawn(move || {
+            let mut failures = 0;
+            loop {
+                let set_status =
+                    |new| *shared_status.lock().unwrap_or_else(|e| e.into_inner()) = new;
+                set_status(SyncStatus::Syncing);
+                set_status(match sync_now(&db, remote.as_ref()) {
+                    Ok(_) => {
+                        failures = 0;
+                        SyncStatus::Synced(Utc::now())
+                    }
+                    Err(e) => {
+                        failures += 1;
+                        SyncStatus::Offline(e.to_string())
+                    }
+                });
+                // the status line and the synced changes are shown right away
+                crate::io_utils::request_redraw();
+
+                match pending.recv_timeout(retry_delay(failures, interval)) {
+                    Ok(()) | Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+
+        Self { status, requests }
+    }
+
+    pub fn status(&self) -> SyncStatus {
+        self.status
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Asks the worker to sync right away instead of at the next interval.
+    pub fn request_sync(&self) {
+        let _ = self.requests.send(());
+    }
+}
+
+const FIRST_RETRY: Duration = Duration::from_secs(5);
+
+/// Returns how long to wait before the next sync. After failures, e.g. while
+/// offline or rate limited, the wait starts short and doubles up to `interval`.
+pub fn retry_delay(failures: u32, interval: Duration) -> Duration {
+    if failures == 0 {
+        return interval;
+    }
+    FIRST_RETRY
+        .saturating_mul(2u32.saturating_pow(failures - 1))
+        .min(interval)
+}
+
+/// Returns the changes made to the database file since the last sync, or
+/// `None` if it was never synced.
+pub fn unsynced_changes(db_path: &str) -> Result<Option<BoardDiff>> {
+    if !has_base(db_path) {
+        return Ok(None);
+    }
+    let base_path = base_path(db_path);
+    Ok(Some(BoardDiff::collect(
+        &read_db_file(&base_path)?,
+        &read_db_file(db_path)?,
+    )))
+}
+
+/// Returns the copies saved next to the database file when both sides had
+/// changed, oldest first.
+pub fn conflict_copies(db_path: &str) -> Vec<PathBuf> {
+    let path = Path::new(db_path);
+    let Some(prefix) = path
+        .file_name()
+        .map(|name| format!("{}.conflict-", name.to_string_lossy()))
+    else {
+        return vec![];
+    };
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let mut copies = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| {
+                    path.file_name()
+                        .is_some_and(|name| name.to_string_lossy().starts_with(&prefix))
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    copies.sort();
+    copies
+}
+
+fn sync_now(db: &JiraDatabase, remote: &dyn Remote) -> Result<SyncOutcome> {
+    let db_path = db
+        .location()
+        .ok_or_else(|| anyhow!("the database is not stored in a file"))?;
+    // changes made meanwhile would overwrite a pulled copy or miss the push
+    let _access = exclusive_access();
+    db.flush()?;
+    sync(db_path, remote, Utc::now())
+}
+
+/// Waits for one peer on `addr` and merges databases with it. A bare port
+/// listens on localhost only.
+pub fn listen(db_path: &str, addr: &str, secret: &str) -> Result<DBState> {
+    let addr = match addr.parse::<u16>() {
+        Ok(port) => format!("127.0.0.1:{port}"),
+        Err(_) => addr.to_owned(),
+    };
+    let listener =
+        TcpListener::bind(&addr).with_context(|| format!("failed to listen on {addr}"))?;
+    println!("waiting for a peer on {}", listener.local_addr()?);
+    let (stream, peer) = listener.accept()?;
+    println!("connected to {peer}");
+    exchange(db_path, stream, secret)
+}
+
+pub fn connect(db_path: &str, addr: &str, secret: &str) -> Result<DBState> {
+    let stream =
+        TcpStream::connect(addr).with_context(|| format!("failed to connect to {addr}"))?;
+    exchange(db_path, stream, secret)
+}
+
+/// Longest line accepted from a peer before it has proven it knows the secret.
+const MAX_CHALLENGE_LINE: u64 = 256;
+
+/// Returns a hex string of 128 bits from the operating system's random source.
+fn nonce() -> Result<String> {
+    let mut bytes = [0; 16];
+    getrandom::fill(&mut bytes).map_err(|e| anyhow!(format!("failed to create a nonce: {e}")))?;
+    Ok(hex(&bytes))
+}
+
+/// Reads a line of the handshake, refusing longer ones than any valid message.
+fn read_challenge_line(reader: &mut impl BufRead) -> Result<String> {
+    let mut line = String::new();
+    reader
+        .by_ref()
+        .take(MAX_CHALLENGE_LINE)
+        .read_line(&mut line)?;
+    if !line.ends_with('\n') {
+        return Err(anyhow!("peer sent an invalid handshake"));
+    }
+    Ok(line.trim_end().to_owned())
+}
+
+/// Proves to the peer that this side knows the shared secret and checks the
+/// peer's proof, before any board data is sent. Each side sends a nonce, then
+/// the HMAC of its own nonce and the peer's one.
+fn authenticate(stream: &TcpStream, reader: &mut impl BufRead, secret: &str) -> Result<()> {
+    let mut writer = stream;
+    let ours = nonce()?;
+    writeln!(writer, "{ours}")?;
+    let theirs = read_challenge_line(reader)?;
+    let theirs = theirs.as_str();
+    if theirs.is_empty() || theirs == ours {
+        return Err(anyhow!("peer did not send a valid challenge"));
+    }
+
+    let proof = |from: &str, to: &str| {
+        hex(&hmac_sha256(
+            secret.as_bytes(),
+            format!("{from}:{to}").as_bytes(),
+        ))
+    };
+    writeln!(writer, "{}", proof(&ours, theirs))?;
+    let answer = read_challenge_line(reader)?;
+    let expected = proof(theirs, &ours);
+    // compare in constant time so the proof can't be guessed byte by byte
+    let answer = answer.as_bytes();
+    let matches = answer.len() == expected.len()
+        && answer
+            .iter()
+            .zip(expected.as_bytes())
+            .fold(0, |diff, (x, y)| diff | (x ^ y))
+            == 0;
+    if !matches {
+        return Err(anyhow!("peer does not know the shared secret"));
+    }
+    Ok(())
+}
+
+/// Authenticates the peer, then sends the local database to it and receives
+/// the peer's one as a single line of JSON each. Both sides then store the
+/// same merged state. The boards are sent unencrypted.
+fn exchange(db_path: &str, stream: TcpStream, secret: &str) -> Result<DBState> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    authenticate(&stream, &mut reader, secret)?;
+
+    let local = JiraDatabase::new(db_path)?.read_db()?;
+    let mut payload = serde_json::to_vec(&local)?;
+    payload.push(b'\n');
+
+    // write concurrently so large databases can't fill both socket buffers
+    let mut writer = stream.try_clone()?;
+    let sender = thread::spawn(move || -> std::io::Result<()> {
+        writer.write_all(&payload)?;
+        writer.shutdown(Shutdown::Write)
+    });
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    sender
+        .join()
+        .map_err(|_| anyhow!("failed to send the database"))??;
+
+    let peer: DBState =
+        serde_json::from_str(&line).context("peer did not send a valid database")?;
+    let merged = merge(&local, &peer);
+    write_db_file(db_path, &merged)?;
+    Ok(merged)
+}
+
+/// The kind of an item id and the activity entry of its creation, if it was
+/// logged. Copies of the same item have the same origin.
+type Origin<'a> = (Option<&'a Activity>, bool);
+
+fn origins(db_state: &DBState) -> HashMap<u32, Origin<'_>> {
+    let mut origins: HashMap<u32, Origin> = db_state
+        .epics
+        .keys()
+        .map(|id| (id.0, (None, false)))
+        .chain(db_state.stories.keys().map(|id| (id.0, (None, true))))
+        .collect();
+    // deleted items are included, their ids must not be confused either
+    for entry in db_state
+        .activity
+        .iter()
+        .filter(|e| e.event == ActivityEvent::Created)
+    {
+        let item = match (entry.story_id, entry.epic_id) {
+            (Some(story_id), _) => (story_id.0, true),
+            (None, Some(epic_id)) => (epic_id.0, false),
+            (None, None) => continue,
+        };
+        origins.insert(item.0, (Some(entry), item.1));
+    }
+    origins
+}
+
+/// Gives items that were created separately on both sides under the same id
+/// a new id above both sides' last one. Of the two, the one created later is
+/// renumbered.
+fn separate_collisions(a: &DBState, b: &DBState) -> (DBState, DBState) {
+    let (origins_a, origins_b) = (origins(a), origins(b));
+    let mut next_id = a.last_item_id.max(b.last_item_id);
+    let (mut ids_a, mut ids_b) = (HashMap::new(), HashMap::new());
+    for id in origins_a.keys().copied().sorted() {
+        let (Some(origin_a), Some(origin_b)) = (origins_a.get(&id), origins_b.get(&id)) else {
+            continue;
+        };
+        if origin_a == origin_b {
+            continue;
+        }
+        next_id += 1;
+        if origin_a > origin_b {
+            ids_a.insert(id, next_id);
+        } else {
+            ids_b.insert(id, next_id);
+        }
+    }
+
+    let (mut a, mut b) = (a.clone(), b.clone());
+    a.renumber(&ids_a);
+    b.renumber(&ids_b);
+    (a, b)
+}
+
+/// Merges two copies of a board that started out from the same file. Items
+/// present in both keep the copy with the higher version, then the later
+/// update; items deleted on either side stay deleted. Items created on both
+/// sides under the same id are both kept, see `separate_collisions`. The
+/// result does not depend on the order of the arguments.
+pub fn merge(a: &DBState, b: &DBState) -> DBState {
+    let (a, b) = separate_collisions(a, b);
+    let (a, b) = (&a, &b);
+    let mut activity = a.activity.clone();
+    activity.extend(
+        b.activity
+            .iter()
+            .filter(|e| !a.activity.contains(e))
+            .cloned(),
+    );
+    activity.sort();
+
+    let deleted: HashSet<u32> = activity
+        .iter()
+        .filter(|e| e.event == ActivityEvent::Deleted)
+        .filter_map(|e| e.story_id.map(|id| id.0).or(e.epic_id.map(|id| id.0)))
+        .collect();
+
+    // a story belongs to the epic that lists it on the side whose copy wins,
+    // so a story moved on one side isn't listed under both epics
+    let mut stories = a.stories.clone();
+    let mut owners: HashMap<StoryId, EpicId> = a
+        .stories
+        .keys()
+        .filter_map(|id| Some((*id, a.story_epic(*id)?)))
+        .collect();
+    for (id, story) in &b.stories {
+        let newer = stories.get(id).is_none_or(|s| {
+            (story.version, story.updated_at, story) > (s.version, s.updated_at, s)
+        });
+        if newer {
+            stories.insert(*id, story.clone());
+            match b.story_epic(*id) {
+                Some(epic_id) => owners.insert(*id, epic_id),
+                None => owners.remove(id),
+            };
+        }
+    }
+    stories.retain(|id, _| !deleted.contains(&id.0));
+
+    let mut epics = a.epics.clone();
+    for (id, epic) in &b.epics {
+        match epics.get_mut(id) {
+            Some(e) => {
+                let mut story_ids = e.stories.clone();
+                story_ids.extend(&epic.stories);
+                let last_story_number = e.last_story_number.max(epic.last_story_number);
+                let mut notes = e.notes.clone();
+                notes.extend(epic.notes.iter().filter(|n| !e.notes.contains(n)).cloned());
+                notes.sort();
+                if (epic.version, epic.updated_at, epic) > (e.version, e.updated_at, &*e) {
+                    *e = epic.clone();
+                }
+                e.stories = story_ids;
+                e.last_story_number = last_story_number;
+                // notes are append-only, so both sides' entries are kept
+                e.notes = notes;
+            }
+            None => {
+                epics.insert(*id, epic.clone());
+            }
+        }
+    }
+    epics.retain(|id, _| !deleted.contains(&id.0));
+    for (epic_id, epic) in epics.iter_mut() {
+        epic.stories.sort_unstable();
+        epic.stories.dedup();
+        epic.stories
+            .retain(|id| stories.contains_key(id) && owners.get(id) == Some(epic_id));
+    }
+    let referenced: HashSet<StoryId> = epics.values().flat_map(|e| e.stories.clone()).collect();
+    stories.retain(|id, _| referenced.contains(id));
+
+    let mut recurring: HashMap<(String, EpicId, u32), RecurringStory> = HashMap::new();
+    for r in a.recurring_stories.iter().chain(&b.recurring_stories) {
+        let key = (r.name.clone(), r.epic_id, r.interval_days);
+        match recurring.get_mut(&key) {
+            Some(existing) if existing.last_created_at >= r.last_created_at => {}
+            Some(existing) => *existing = r.clone(),
+            None => {
+                recurring.insert(key, r.clone());
+            }
+        }
+    }
+    let mut recurring_stories: Vec<_> = recurring
+        .into_values()
+        .filter(|r| epics.contains_key(&r.epic_id))
+        .collect();
+    recurring_stories.sort_by(|x, y| (x.epic_id, &x.name).cmp(&(y.epic_id, &y.name)));
+
+    // a sprint is known by when it was started and its name, so sprints
+    // started on both sides since the last merge are all kept
+    let mut sprints = a.sprints.clone();
+    for sprint in &b.sprints {
+        match sprints
+            .iter_mut()
+            .find(|s| (s.started_at, &s.name) == (sprint.started_at, &sprint.name))
+        {
+            Some(existing) => *existing = merge_sprint(existing, sprint),
+            None => sprints.push(sprint.clone()),
+        }
+    }
+    sprints.sort_by_key(|s| s.started_at);
+    for sprint in sprints.iter_mut() {
+        sprint.stories.retain(|id| stories.contains_key(id));
+    }
+
+    DBState {
+        last_item_id: a.last_item_id.max(b.last_item_id),
+        epics,
+        stories,
+        recurring_stories,
+        id_scheme: if a.id_scheme == IdScheme::PerEpic || b.id_scheme == IdScheme::PerEpic {
+            IdScheme::PerEpic
+        } else {
+            IdScheme::Global
+        },
+        activity,
+        sprints,
+    }
+}
+
+/// Merges two copies of the same sprint: stories planned on either side are
+/// kept, a sprint closed on either side is closed, and a retrospective taken
+/// on one side wins over an empty one.
+fn merge_sprint(a: &Sprint, b: &Sprint) -> Sprint {
+    let mut sprint = a.max(b).clone();
+    sprint.stories = a.stories.iter().chain(&b.stories).copied().collect();
+    sprint.stories.sort_unstable();
+    sprint.stories.dedup();
+    sprint.closed = a.closed || b.closed;
+    sprint.retrospective = match (a.retrospective.is_empty(), b.retrospective.is_empty()) {
+        (true, _) => b.retrospective.clone(),
+        (_, true) => a.retrospective.clone(),
+        _ => a.retrospective.clone().max(b.retrospective.clone()),
+    };
+    sprint
+}
+
+fn http_error(e: ureq::Error, method: &str, url: &str) -> anyhow::Error {
+    anyhow!(format!("{method} {url} failed: {e}"))
+}
+
+struct WebDavRemote {
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl WebDavRemote {
+    fn authorization(&self) -> Option<String> {
+        let username = self.username.as_deref()?;
+        let credentials = format!("{username}:{}", self.password.as_deref().unwrap_or(""));
+        Some(format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode(credentials)
+        ))
+    }
+}
+
+impl Remote for WebDavRemote {
+    fn fetch(&self) -> Result<Option<Vec<u8>>> {
+        let mut request = ureq::get(&self.url);
+        if let Some(auth) = self.authorization() {
+            request = request.header("Authorization", auth);
+        }
+        match request.call() {
+            Ok(mut response) => Ok(Some(response.body_mut().read_to_vec()?)),
+            Err(ureq::Error::StatusCode(404)) => Ok(None),
+            Err(e) => Err(http_error(e, "GET", &self.url)),
+        }
+    }
+
+    fn store(&self, contents: &[u8]) -> Result<()> {
+        let mut request = ureq::put(&self.url);
+        if let Some(auth) = self.authorization() {
+            request = request.header("Authorization", auth);
+        }
+        request
+            .send(contents)
+            .map_err(|e| http_error(e, "PUT", &self.url))?;
+        Ok(())
+    }
+}
+
+/// An object in an S3-compatible bucket, addressed path-style and signed with
+/// AWS Signature Version 4.
+struct S3Remote {
+    endpoint: String,
+    bucket: String,
+    key: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+}
+
+impl S3Remote {
+    fn path(&self) -> String {
+        let key = self
+            .key
+            .split('/')
+            .map(uri_encode)
+            .collect::<Vec<_>>()
+            .join("/");
+        format!("/{}/{key}", uri_encode(&self.bucket))
+    }
+
+    /// Returns the url and the headers of a signed request.
+    fn sign(
+        &self,
+        method: &str,
+        body: &[u8],
+        now: DateTime<Utc>,
+    ) -> (String, Vec<(String, String)>) {
+        let host = self
+            .endpoint
+            .split("://")
+            .last()
+            .unwrap_or_default()
+            .split('/')
+            .next()
+            .unwrap_or_default();
+        let path = self.path();
+        let payload_hash = hex(&Sha256::digest(body));
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let scope = format!("{date}/{}/s3/aws4_request", self.region);
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{method}\n{path}\n\nhost:{host}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:{amz_date}\n\n{signed_headers}\n{payload_hash}"
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex(&Sha256::digest(canonical_request))
+        );
+
+        let key = [date.as_str(), &self.region, "s3", "aws4_request"]
+            .iter()
+            .fold(
+                format!("AWS4{}", self.secret_key).into_bytes(),
+                |key, part| hmac_sha256(&key, part.as_bytes()).to_vec(),
+            );
+        let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        let headers = vec![
+            ("x-amz-content-sha256".to_owned(), payload_hash),
+            ("x-amz-date".to_owned(), amz_date),
+            (
+                "Authorization".to_owned(),
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+                    self.access_key
+                ),
+            ),
+        ];
+        (format!("{}{path}", self.endpoint), headers)
+    }
+}
+
+impl Remote for S3Remote {
+    fn fetch(&self) -> Result<Option<Vec<u8>>> {
+        let (url, headers) = self.sign("GET", &[], Utc::now());
+        let request = headers
+            .into_iter()
+            .fold(ureq::get(&url), |request, (name, value)| {
+                request.header(name, value)
+            });
+        match request.call() {
+            Ok(mut response) => Ok(Some(response.body_mut().read_to_vec()?)),
+            Err(ureq::Error::StatusCode(404)) => Ok(None),
+            Err(e) => Err(http_error(e, "GET", &url)),
+        }
+    }
+
+    fn store(&self, contents: &[u8]) -> Result<()> {
+        let (url, headers) = self.sign("PUT", contents, Utc::now());
+        let request = headers
+            .into_iter()
+            .fold(ureq::put(&url), |request, (name, value)| {
+                request.header(name, value)
+            });
+        request
+            .send(contents)
+            .map_err(|e| http_error(e, "PUT", &url))?;
+        Ok(())
+    }
+}
+
+fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::models::{Activity, Epic, Status, Story};
+
+    struct MemoryRemote {
+        contents: RefCell<Option<Vec<u8>>>,
+    }
+
+    impl Remote for MemoryRemote {
+        fn fetch(&self) -> Result<Option<Vec<u8>>> {
+            Ok(self.contents.borrow().clone())
+        }
+
+        fn store(&self, contents: &[u8]) -> Result<()> {
+            *self.contents.borrow_mut() = Some(contents.to_vec());
+            Ok(())
+        }
+    }
+
+    /// A remote that is still readable after handing it to a worker.
+    struct SharedRemote(Arc<Mutex<Option<Vec<u8>>>>);
+
+    impl Remote for SharedRemote {
+        fn fetch(&self) -> Result<Option<Vec<u8>>> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        fn store(&self, contents: &[u8]) -> Result<()> {
+            *self.0.lock().unwrap() = Some(contents.to_vec());
+            Ok(())
+        }
+    }
+
+    struct UnreachableRemote;
+
+    impl Remote for UnreachableRemote {
+        fn fetch(&self) -> Result<Option<Vec<u8>>> {
+            Err(anyhow!("connection refused"))
+        }
+
+        fn store(&self, _: &[u8]) -> Result<()> {
+            Err(anyhow!("connection refused"))
+        }
+    }
+
+    fn wait_until(condition: impl Fn() -> bool) {
+        for _ in 0..500 {
+            if condition() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("condition not met in time");
+    }
+
+    fn db_with_epic(name: &str, updated_at: DateTime<Utc>) -> DBState {
+        let mut db_state = DBState::new();
+        let mut epic = Epic::new(name.to_owned(), "".to_owned());
+        epic.updated_at = updated_at;
+        db_state.epics.insert(EpicId(1), epic);
+        db_state.last_item_id = 1;
+        db_state
+    }
+
+    fn serialized(db_state: &DBState) -> Vec<u8> {
+        serde_json::to_vec(db_state).unwrap()
+    }
+
+    #[test]
+    fn sync_worker_should_sync_in_the_background_and_on_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        let db = Arc::new(
+            JiraDatabase::new(path.to_str().unwrap())
+                .unwrap()
+                .with_debounce(Duration::from_secs(60)),
+        );
+        let remote = Arc::new(Mutex::new(None));
+
+        let worker = SyncWorker::start(
+            Arc::clone(&db),
+            Box::new(SharedRemote(Arc::clone(&remote))),
+            Duration::MAX,
+        );
+        wait_until(|| matches!(worker.status(), SyncStatus::Synced(_)));
+        assert_eq!(
+            remote.lock().unwrap().clone(),
+            Some(fs::read(&path).unwrap())
+        );
+
+        // unsaved changes are written before syncing
+        db.create_epic(Epic::new("Background".to_owned(), "".to_owned()))
+            .unwrap();
+        worker.request_sync();
+        wait_until(|| {
+            remote
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|contents| String::from_utf8_lossy(contents).contains("Background"))
+        });
+        assert!(!db.has_unsaved_changes());
+    }
+
+    #[test]
+    fn sync_worker_should_report_unreachable_remote() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        let db = Arc::new(JiraDatabase::new(path.to_str().unwrap()).unwrap());
+
+        let worker = SyncWorker::start(db, Box::new(UnreachableRemote), Duration::MAX);
+        wait_until(|| matches!(worker.status(), SyncStatus::Offline(_)));
+        assert_eq!(
+            worker.status().line(Utc::now()),
+            "offline: connection refused"
+        );
+
+        let now = Utc::now();
+        assert_eq!(
+            SyncStatus::Synced(now - chrono::Duration::minutes(2)).line(now),
+            "synced 2m ago"
+        );
+    }
+
+    #[test]
+    fn sync_should_push_pull_and_keep_conflict_copies() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        let path = path.to_str().unwrap();
+        let remote = MemoryRemote {
+            contents: RefCell::new(None),
+        };
+        let now = Utc::now();
+
+        write_db_file(path, &db_with_epic("first", now)).unwrap();
+        assert_eq!(sync(path, &remote, now).unwrap(), SyncOutcome::Pushed);
+        assert_eq!(sync(path, &remote, now).unwrap(), SyncOutcome::UpToDate);
+
+        let newer = serialized(&db_with_epic("from elsewhere", now));
+        remote.store(&newer).unwrap();
+        assert_eq!(sync(path, &remote, now).unwrap(), SyncOutcome::Pulled);
+        assert_eq!(fs::read(path).unwrap(), newer);
+
+        write_db_file(path, &db_with_epic("local", now)).unwrap();
+        assert_eq!(sync(path, &remote, now).unwrap(), SyncOutcome::Pushed);
+
+        let later = now + chrono::Duration::minutes(5);
+        write_db_file(path, &db_with_epic("older local edit", now)).unwrap();
+        let remote_edit = serialized(&db_with_epic("newer remote edit", later));
+        remote.store(&remote_edit).unwrap();
+
+        let outcome = sync(path, &remote, later).unwrap();
+        let SyncOutcome::Conflict {
+            kept,
+            conflict_copy,
+        } = outcome
+        else {
+            panic!("expected a conflict, got {outcome:?}");
+        };
+        assert_eq!(kept, "remote");
+        assert_eq!(fs::read(path).unwrap(), remote_edit);
+        let saved: DBState = serde_json::from_slice(&fs::read(&conflict_copy).unwrap()).unwrap();
+        assert_eq!(saved.epics[&EpicId(1)].name, "older local edit");
+        assert_eq!(conflict_copies(path), vec![PathBuf::from(conflict_copy)]);
+    }
+
+    #[test]
+    fn unsynced_changes_should_diff_against_the_last_sync() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        let path = path.to_str().unwrap();
+        let remote = MemoryRemote {
+            contents: RefCell::new(None),
+        };
+        let now = Utc::now();
+
+        write_db_file(path, &db_with_epic("first", now)).unwrap();
+        assert_eq!(unsynced_changes(path).unwrap(), None);
+
+        sync(path, &remote, now).unwrap();
+        assert!(unsynced_changes(path).unwrap().unwrap().is_empty());
+
+        write_db_file(path, &db_with_epic("renamed offline", now)).unwrap();
+        assert_eq!(
+            unsynced_changes(path).unwrap().unwrap().to_string(),
+            "epics:\n  ~ 1 renamed offline: name \"first\" → \"renamed offline\"\n"
+        );
+        assert!(conflict_copies(path).is_empty());
+    }
+
+    #[test]
+    fn retry_delay_should_back_off_up_to_the_interval() {
+        let interval = Duration::from_secs(60);
+        assert_eq!(retry_delay(0, interval), interval);
+        assert_eq!(retry_delay(1, interval), Duration::from_secs(5));
+        assert_eq!(retry_delay(3, interval), Duration::from_secs(20));
+        assert_eq!(retry_delay(5, interval), interval);
+        assert_eq!(
+            retry_delay(100, Duration::from_secs(3600)),
+            Duration::from_secs(3600)
+        );
+    }
+
+    #[test]
+    fn merge_should_keep_newer_versions_and_deletions() {
+        let db = JiraDatabase {
+            database: Box::new(crate::db::test_utils::MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("epic".to_owned(), "".to_owned()))
+            .unwrap();
+        let kept = db
+            .create_story(Story::new("kept".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let removed = db
+            .create_story(Story::new("removed".to_owned(), "".to_owned()), epic_id)
+            .unwrap();
+        let base = db.read_db().unwrap();
+
+        db.update_story_status(kept, Status::Closed).unwrap();
+        let ours = db.read_db().unwrap();
+
+        let mut theirs = base.clone();
+        let mut story = theirs.stories[&removed].clone();
+        story.name = "added elsewhere".to_owned();
+        theirs.stories.insert(StoryId(10), story);
+        theirs
+            .epics
+            .get_mut(&epic_id)
+            .unwrap()
+            .stories
+            .push(StoryId(10));
+        theirs.stories.remove(&removed);
+        theirs.activity.push(Activity::new(
+            Utc::now(),
+            epic_id,
+            Some(removed),
+            "removed",
+            ActivityEvent::Deleted,
+        ));
+        theirs.last_item_id = 10;
+
+        let merged = merge(&ours, &theirs);
+
+        assert_eq!(merged, merge(&theirs, &ours));
+        assert_eq!(merged.stories[&kept].status, Status::Closed);
+        assert_eq!(merged.stories[&StoryId(10)].name, "added elsewhere");
+        assert!(!merged.stories.contains_key(&removed));
+        assert_eq!(merged.epics[&epic_id].stories, vec![kept, StoryId(10)]);
+        assert_eq!(merged.last_item_id, 10);
+    }
+
+    #[test]
+    fn merge_should_list_a_moved_story_under_its_new_epic_only() {
+        let db = JiraDatabase {
+            database: Box::new(crate::db::test_utils::MockDB::new()),
+        };
+        let [from, to] = ["from", "to"].map(|name| {
+            db.create_epic(Epic::new(name.to_owned(), "".to_owned()))
+                .unwrap()
+        });
+        let story_id = db
+            .create_story(Story::new("moved".to_owned(), "".to_owned()), from)
+            .unwrap();
+        let theirs = db.read_db().unwrap();
+
+        db.move_story(story_id, to).unwrap();
+        let ours = db.read_db().unwrap();
+
+        let merged = merge(&ours, &theirs);
+
+        assert_eq!(merged, merge(&theirs, &ours));
+        assert!(merged.epics[&from].stories.is_empty());
+        assert_eq!(merged.epics[&to].stories, vec![story_id]);
+    }
+
+    #[test]
+    fn merge_should_keep_items_created_on_both_sides_under_the_same_id() {
+        use crate::db::test_utils::MockDB;
+
+        let ours = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = ours
+            .create_epic(Epic::new("epic".to_owned(), "".to_owned()))
+            .unwrap();
+        let theirs = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        theirs.database.write_db(&ours.read_db().unwrap()).unwrap();
+
+        for name in ["ours", "also ours"] {
+            ours.create_story(Story::new(name.to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+        }
+        for name in ["theirs", "deleted"] {
+            theirs
+                .create_story(Story::new(name.to_owned(), "".to_owned()), epic_id)
+                .unwrap();
+        }
+        theirs.delete_story(epic_id, StoryId(3)).unwrap();
+        let (ours, theirs) = (ours.read_db().unwrap(), theirs.read_db().unwrap());
+
+        let merged = merge(&ours, &theirs);
+
+        assert_eq!(merged, merge(&theirs, &ours));
+        let mut names = merged
+            .stories
+            .values()
+            .map(|s| s.name.as_str())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["also ours", "ours", "theirs"]);
+        assert_eq!(merged.epics[&epic_id].stories.len(), 3);
+        assert_eq!(merged.last_item_id, 5);
+    }
+
+    #[test]
+    fn merge_should_combine_sprints_changed_on_both_sides() {
+        use crate::db::test_utils::MockDB;
+        use crate::models::Retrospective;
+
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        let epic_id = db
+            .create_epic(Epic::new("epic".to_owned(), "".to_owned()))
+            .unwrap();
+        let story_ids = ["first", "second"].map(|name| {
+            db.create_story(Story::new(name.to_owned(), "".to_owned()), epic_id)
+                .unwrap()
+        });
+        let mut base = db.read_db().unwrap();
+        base.sprints.push(Sprint::new("Sprint 1".to_owned(), 10));
+
+        let mut ours = base.clone();
+        ours.sprints[0].stories.push(story_ids[0]);
+        ours.sprints[0].closed = true;
+        ours.sprints.push(Sprint::new("Sprint 2".to_owned(), 10));
+        let mut theirs = base;
+        theirs.sprints[0].stories.push(story_ids[1]);
+        theirs.sprints[0].retrospective = Retrospective {
+            went_well: vec!["pairing".to_owned()],
+            ..Default::default()
+        };
+
+        let merged = merge(&ours, &theirs);
+
+        assert_eq!(merged, merge(&theirs, &ours));
+        assert_eq!(merged.sprints.len(), 2);
+        let sprint = &merged.sprints[0];
+        assert_eq!(sprint.stories, story_ids.to_vec());
+        assert!(sprint.closed);
+        assert_eq!(sprint.retrospective.went_well, vec!["pairing".to_owned()]);
+        assert_eq!(merged.sprints[1].name, "Sprint 2");
+    }
+
+    #[test]
+    fn read_challenge_line_should_refuse_overlong_lines() {
+        let challenge = nonce().unwrap();
+        assert_eq!(challenge.len(), 32);
+        assert_ne!(challenge, nonce().unwrap());
+
+        let mut reader = std::io::Cursor::new(format!("{challenge}\n"));
+        assert_eq!(read_challenge_line(&mut reader).unwrap(), challenge);
+        let mut reader = std::io::Cursor::new("a".repeat(10_000));
+        assert!(read_challenge_line(&mut reader).is_err());
+    }
+
+    #[test]
+    fn merge_should_keep_sprints_started_on_both_sides() {
+        use crate::models::Sprint;
+
+        let base = DBState::new();
+        let started = Utc::now();
+        let sprint = |name: &str, minutes: i64| Sprint {
+            started_at: started + chrono::Duration::minutes(minutes),
+            ..Sprint::new(name.to_owned(), 10)
+        };
+        let mut ours = base.clone();
+        ours.sprints.push(sprint("X", 1));
+        let mut theirs = base;
+        theirs.sprints.push(sprint("Y", 0));
+        theirs.sprints.push(sprint("Z", 2));
+
+        let merged = merge(&ours, &theirs);
+
+        assert_eq!(merged, merge(&theirs, &ours));
+        let names = merged
+            .sprints
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["Y", "X", "Z"]);
+    }
+
+    #[test]
+    fn exchange_should_require_the_shared_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name).to_str().unwrap().to_owned();
+        let (local, peer) = (path("local.json"), path("peer.json"));
+        JiraDatabase::new(&local)
+            .unwrap()
+            .create_epic(Epic::new("local".to_owned(), "".to_owned()))
+            .unwrap();
+        JiraDatabase::new(&peer).unwrap();
+
+        let run = |peer_secret: &'static str| {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap().to_string();
+            let peer = peer.clone();
+            let connected = thread::spawn(move || {
+                connect(&peer, &addr, peer_secret).map_err(|e| e.to_string())
+            });
+            let (stream, _) = listener.accept().unwrap();
+            let listened = exchange(&local, stream, "secret").map_err(|e| e.to_string());
+            (listened, connected.join().unwrap())
+        };
+
+        let (listened, connected) = run("guess");
+        assert_eq!(
+            listened.unwrap_err(),
+            "peer does not know the shared secret"
+        );
+        assert!(connected.is_err());
+        assert!(read_db_file(&peer).unwrap().epics.is_empty());
+
+        let (listened, connected) = run("secret");
+        assert_eq!(listened.unwrap(), connected.unwrap());
+        assert_eq!(read_db_file(&peer).unwrap().epics.len(), 1);
+    }
+
+    #[test]
+    fn hmac_should_match_rfc_4231_test_vector() {
+        assert_eq!(
+            hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use anyhow::{anyhow, Result};
+
+use crate::models::EpicId;
+
+/// Returns true if the input looks like an id, e.g. `12` or `#12`.
+pub fn is_id(input: &str) -> bool {
+    let digits = input.trim().trim_start_matches('#');
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Parses an item id. Ids start at 1 and may be prefixed with `#`.
+pub fn parse_id(input: &str) -> Result<u32> {
+    let text = input.trim();
+    if !is_id(text) {
+        return Err(anyhow!("'{text}' is not a valid id"));
+    }
+
+    match text.trim_start_matches('#').parse::<u32>() {
+        Ok(0) | Err(_) => Err(anyhow!("id {text} is out of range (1-{})", u32::MAX)),
+        Ok(id) => Ok(id),
+    }
+}
+
+/// Parses a 1-based position in a list with `len` entries and returns the 0-based index.
+pub fn parse_index(input: &str, len: usize) -> Result<usize> {
+    let text = input.trim();
+    if !is_id(text) {
+        return Err(anyhow!("'{text}' is not a valid number"));
+    }
+
+    match text.trim_start_matches('#').parse::<usize>() {
+        Ok(n) if (1..=len).contains(&n) => Ok(n - 1),
+        _ if len == 0 => Err(anyhow!("number {text} is out of range (list is empty)")),
+        _ => Err(anyhow!("number {text} is out of range (1-{len})")),
+    }
+}
+
+/// Parses an epic label like `E3` and returns the id part.
+pub fn parse_epic_label(input: &str) -> Option<&str> {
+    input
+        .trim()
+        .strip_prefix(['E', 'e'])
+        .filter(|id| is_id(id) && !id.starts_with('#'))
+}
+
+/// Parses a story number like `S2` or `E3-S2` into the optional epic id and the number.
+pub fn parse_story_number(input: &str) -> Option<(Option<EpicId>, u32)> {
+    let text = input.trim();
+    let (epic, story) = match text.split_once('-') {
+        Some((epic, story)) => (Some(parse_epic_label(epic)?), story),
+        None => (None, text),
+    };
+    let number = story
+        .strip_prefix(['S', 's'])
+        .filter(|n| is_id(n) && !n.starts_with('#'))?;
+
+    let epic_id = match epic {
+        Some(id) => Some(id.parse().ok()?),
+        None => None,
+    };
+
+    Some((epic_id, number.parse().ok().filter(|n| *n > 0)?))
+}
+
+/// Trims a command argument and removes matching surrounding quotes.
+pub fn parse_argument(input: &str) -> Option<&str> {
+    let text = input.trim();
+    let text = ['"', '\'']
+        .iter()
+        .find_map(|q| {
+            text.strip_prefix(*q)
+                .and_then(|t| t.strip_suffix(*q))
+                .filter(|_| text.len() > 1)
+        })
+        .unwrap_or(text);
+
+    Some(text).filter(|t| !t.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_id_should_accept_plain_and_prefixed_ids() {
+        assert_eq!(parse_id("12").unwrap(), 12);
+        assert_eq!(parse_id(" #7 ").unwrap(), 7);
+        assert_eq!(parse_id("007").unwrap(), 7);
+    }
+
+    #[test]
+    fn parse_id_should_report_invalid_and_out_of_range_ids() {
+        assert_eq!(
+            parse_id("abc").unwrap_err().to_string(),
+            "'abc' is not a valid id"
+        );
+        assert_eq!(
+            parse_id("-1").unwrap_err().to_string(),
+            "'-1' is not a valid id"
+        );
+        assert_eq!(
+            parse_id("#").unwrap_err().to_string(),
+            "'#' is not a valid id"
+        );
+        assert_eq!(
+            parse_id("").unwrap_err().to_string(),
+            "'' is not a valid id"
+        );
+        assert_eq!(
+            parse_id("0").unwrap_err().to_string(),
+            "id 0 is out of range (1-4294967295)"
+        );
+        assert_eq!(
+            parse_id("99999999999").unwrap_err().to_string(),
+            "id 99999999999 is out of range (1-4294967295)"
+        );
+    }
+
+    #[test]
+    fn parse_index_should_check_bounds() {
+        assert_eq!(parse_index("1", 2).unwrap(), 0);
+        assert_eq!(parse_index("2", 2).unwrap(), 1);
+        assert_eq!(
+            parse_index("3", 2).unwrap_err().to_string(),
+            "number 3 is out of range (1-2)"
+        );
+        assert_eq!(
+            parse_index("0", 2).unwrap_err().to_string(),
+            "number 0 is out of range (1-2)"
+        );
+        assert_eq!(
+            parse_index("1", 0).unwrap_err().to_string(),
+            "number 1 is out of range (list is empty)"
+        );
+        assert!(parse_index("x", 2).is_err());
+    }
+
+    #[test]
+    fn parse_labels_should_accept_epic_and_story_numbers() {
+        assert_eq!(parse_epic_label("E3"), Some("3"));
+        assert_eq!(parse_epic_label("e12 "), Some("12"));
+        assert_eq!(parse_epic_label("E"), None);
+        assert_eq!(parse_epic_label("E#3"), None);
+        assert_eq!(parse_epic_label("3"), None);
+
+        assert_eq!(parse_story_number("S2"), Some((None, 2)));
+        assert_eq!(parse_story_number("e3-s2"), Some((Some(EpicId(3)), 2)));
+        assert_eq!(parse_story_number("S0"), None);
+        assert_eq!(parse_story_number("3-S2"), None);
+        assert_eq!(parse_story_number("E3-2"), None);
+        assert_eq!(parse_story_number("save"), None);
+    }
+
+    #[test]
+    fn parse_argument_should_trim_and_unquote() {
+        assert_eq!(parse_argument("  3 "), Some("3"));
+        assert_eq!(parse_argument("\"two words\""), Some("two words"));
+        assert_eq!(parse_argument("'x'"), Some("x"));
+        assert_eq!(parse_argument("\"unterminated"), Some("\"unterminated"));
+        assert_eq!(parse_argument("\""), Some("\""));
+        assert_eq!(parse_argument("\"\""), None);
+        assert_eq!(parse_argument("   "), None);
+    }
+}
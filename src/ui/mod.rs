@@ -1,5 +1,9 @@
+mod input_parser;
 mod pages;
 mod prompts;
+mod status_picker;
+mod widget;
 
 pub use pages::*;
 pub use prompts::*;
+pub use widget::BoardWidget;
@@ -0,0 +1,177 @@
+#[derive(Debug, PartialEq, Eq)]
+pub struct Command<'a> {
+    pub key: &'a str,
+    pub name: &'static str,
+}
+
+impl<'a> Command<'a> {
+    pub fn new(key: &'a str, name: &'static str) -> Self {
+        Self { key, name }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CommandMatch<'a> {
+    Found {
+        command: &'a Command<'a>,
+        argument: Option<&'a str>,
+    },
+    Ambiguous(Vec<&'a Command<'a>>),
+    Unknown(Option<&'a Command<'a>>),
+}
+
+impl CommandMatch<'_> {
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            Self::Found { .. } | Self::Unknown(None) => None,
+            Self::Unknown(Some(command)) => Some(format!("did you mean '{}'?", command.name)),
+            Self::Ambiguous(commands) => Some(format!(
+                "did you mean {}?",
+                commands
+                    .iter()
+                    .map(|c| format!("'{}'", c.name))
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            )),
+        }
+    }
+}
+
+use crate::ui::input_parser::parse_argument;
+
+pub fn match_command<'a>(input: &'a str, commands: &'a [Command<'a>]) -> CommandMatch<'a> {
+    let (word, argument) = match input.split_once(' ') {
+        Some((word, rest)) => (word, parse_argument(rest)),
+        None => (input, None),
+    };
+
+    if word.is_empty() {
+        return CommandMatch::Unknown(None);
+    }
+
+    if let Some(command) = commands.iter().find(|c| c.key == word) {
+        return CommandMatch::Found { command, argument };
+    }
+
+    let word = word.to_lowercase();
+
+    if let Some(command) = commands.iter().find(|c| c.name == word) {
+        return CommandMatch::Found { command, argument };
+    }
+
+    let candidates = commands
+        .iter()
+        .filter(|c| c.name.starts_with(&word))
+        .collect::<Vec<_>>();
+
+    match candidates.len() {
+        0 => CommandMatch::Unknown(
+            commands
+                .iter()
+                .map(|c| (edit_distance(&word, c.name), c))
+                .filter(|(distance, _)| *distance <= 2)
+                .min_by_key(|(distance, _)| *distance)
+                .map(|(_, c)| c),
+        ),
+        1 => CommandMatch::Found {
+            command: candidates[0],
+            argument,
+        },
+        _ => CommandMatch::Ambiguous(candidates),
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static COMMANDS: &[Command<'static>] = &[
+        Command {
+            key: "c",
+            name: "create",
+        },
+        Command {
+            key: "d",
+            name: "delete",
+        },
+        Command {
+            key: "D",
+            name: "describe",
+        },
+        Command {
+            key: "q",
+            name: "quit",
+        },
+    ];
+
+    #[test]
+    fn match_command_should_match_keys_names_and_prefixes() {
+        let found = |name: &str, argument: Option<&str>| match match_command(name, COMMANDS) {
+            CommandMatch::Found {
+                command,
+                argument: a,
+            } => {
+                assert_eq!(a, argument);
+                command.name
+            }
+            m => panic!("unexpected match for '{name}': {m:?}"),
+        };
+
+        assert_eq!(found("c", None), "create");
+        assert_eq!(found("D", None), "describe");
+        assert_eq!(found("cr", None), "create");
+        assert_eq!(found("CREATE", None), "create");
+        assert_eq!(found("Q", None), "quit");
+        assert_eq!(found("del 3", Some("3")), "delete");
+        assert_eq!(found("d  3 ", Some("3")), "delete");
+        assert_eq!(found("d \"3\"", Some("3")), "delete");
+    }
+
+    #[test]
+    fn match_command_should_report_ambiguous_and_unknown_input() {
+        assert_eq!(
+            match_command("de", COMMANDS),
+            CommandMatch::Ambiguous(vec![&COMMANDS[1], &COMMANDS[2]])
+        );
+        assert_eq!(
+            match_command("craete", COMMANDS),
+            CommandMatch::Unknown(Some(&COMMANDS[0]))
+        );
+        assert_eq!(
+            match_command("j983f2j", COMMANDS),
+            CommandMatch::Unknown(None)
+        );
+        assert_eq!(match_command("q\n", COMMANDS), CommandMatch::Unknown(None));
+        assert_eq!(match_command("", COMMANDS), CommandMatch::Unknown(None));
+    }
+
+    #[test]
+    fn hint_should_suggest_commands() {
+        assert_eq!(
+            match_command("de", COMMANDS).hint(),
+            Some("did you mean 'delete' or 'describe'?".to_owned())
+        );
+        assert_eq!(
+            match_command("qiut", COMMANDS).hint(),
+            Some("did you mean 'quit'?".to_owned())
+        );
+        assert_eq!(match_command("c", COMMANDS).hint(), None);
+        assert_eq!(match_command("xyzxyz", COMMANDS).hint(), None);
+    }
+}
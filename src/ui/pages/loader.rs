@@ -0,0 +1,135 @@
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::db::JiraDatabase;
+use crate::io_utils::request_redraw;
+use crate::models::DBState;
+
+/// How long drawing waits for a fetch before showing the loading line, so fast
+/// backends draw without flicker.
+const LOADING_DELAY: Duration = Duration::from_millis(100);
+
+/// Data fetched after the loading line was shown is only drawn this soon after;
+/// older data may be stale by then and is fetched again.
+const FRESH_FOR: Duration = Duration::from_secs(1);
+
+enum LoadState<T> {
+    Idle,
+    Pending { loading_shown: bool },
+    Ready { data: Result<T>, at: Instant },
+}
+
+/// Fetches the data a page draws on a background thread, so a slow backend
+/// shows a loading line instead of blocking the loop inside `draw_page`.
+pub struct Loader<T> {
+    state: Arc<(Mutex<LoadState<T>>, Condvar)>,
+}
+
+impl<T> Default for Loader<T> {
+    fn default() -> Self {
+        Self {
+            state: Arc::new((Mutex::new(LoadState::Idle), Condvar::new())),
+        }
+    }
+}
+
+impl<T: Send + 'static> Loader<T> {
+    /// Returns the fetched data, starting `fetch` unless a fetch is under way.
+    /// Returns `None` if the data isn't there yet; a redraw is requested once
+    /// it is.
+    pub fn load(&self, fetch: impl FnOnce() -> Result<T> + Send + 'static) -> Option<Result<T>> {
+        let (lock, ready) = &*self.state;
+        let mut state = lock.lock().expect("loader lock poisoned");
+        if matches!(&*state, LoadState::Ready { at, .. } if at.elapsed() > FRESH_FOR) {
+            *state = LoadState::Idle;
+        }
+        if let LoadState::Idle = *state {
+            *state = LoadState::Pending {
+                loading_shown: false,
+            };
+            let shared = Arc::clone(&self.state);
+            thread::spawn(move || {
+                let data = fetch();
+                let (lock, ready) = &*shared;
+                let mut state = lock.lock().expect("loader lock poisoned");
+                if let LoadState::Pending {
+                    loading_shown: true,
+                } = *state
+                {
+                    request_redraw();
+                }
+                *state = LoadState::Ready {
+                    data,
+                    at: Instant::now(),
+                };
+                ready.notify_all();
+            });
+        }
+
+        let (mut state, _) = ready
+            .wait_timeout_while(state, LOADING_DELAY, |state| {
+                matches!(state, LoadState::Pending { .. })
+            })
+            .expect("loader lock poisoned");
+        if let LoadState::Pending { loading_shown } = &mut *state {
+            *loading_shown = true;
+            return None;
+        }
+        match std::mem::replace(&mut *state, LoadState::Idle) {
+            LoadState::Ready { data, .. } => Some(data),
+            LoadState::Pending { .. } | LoadState::Idle => None,
+        }
+    }
+}
+
+impl Loader<DBState> {
+    /// Loads the board of `db`.
+    pub fn board(&self, db: &Arc<JiraDatabase>) -> Option<Result<DBState>> {
+        let db = Arc::clone(db);
+        self.load(move || db.read_db())
+    }
+}
+
+pub fn print_loading() {
+    println!("loading...");
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+
+    #[test]
+    fn load_should_show_loading_until_a_slow_fetch_finished() {
+        let loader = Loader::default();
+        assert_eq!(loader.load(|| Ok(1)).unwrap().unwrap(), 1);
+
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let (release, released) = mpsc::channel::<()>();
+        let counted = Arc::clone(&fetches);
+        assert!(loader
+            .load(move || {
+                counted.fetch_add(1, Ordering::SeqCst);
+                released.recv().unwrap();
+                Ok(2)
+            })
+            .is_none());
+        // a draw while the fetch is under way doesn't start another one
+        assert!(loader.load(|| Ok(3)).is_none());
+
+        release.send(()).unwrap();
+        let data = loop {
+            if let Some(data) = loader.load(|| Ok(4)) {
+                break data;
+            }
+        };
+        assert_eq!(data.unwrap(), 2);
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert_eq!(loader.load(|| Ok(5)).unwrap().unwrap(), 5);
+    }
+}
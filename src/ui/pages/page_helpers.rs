@@ -1,5 +1,23 @@
+use std::cell::RefCell;
+use std::io::{self, IsTerminal, Write};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, Utc};
+use crossterm::cursor;
+use crossterm::style::{Attribute, Attributes, ContentStyle, StyledContent, Stylize};
+use crossterm::terminal::{Clear, ClearType};
 use ellipse::Ellipse;
 
+use super::Shortcut;
+use crate::config::{Color, Health, HighlightRule, InputFeedback};
+use crate::io_utils::CLICK_PREFIX;
+use crate::models::{Activity, ActivityEvent, Note, Status, StoryId};
+
+static MESSAGE_SHOWN: AtomicBool = AtomicBool::new(false);
+
 pub fn get_column_string(text: &str, width: usize) -> String {
     match width {
         0 => return String::new(),
@@ -26,9 +44,345 @@ pub fn get_column_string(text: &str, width: usize) -> String {
     text.truncate_ellipse(width - 3).to_string()
 }
 
+/// Prints why input was rejected and signals it as configured. Without a
+/// message only the bell or flash is given.
+pub fn reject_input(message: Option<&str>, feedback: InputFeedback) {
+    let mut stdout = io::stdout();
+    match feedback {
+        InputFeedback::None => {}
+        InputFeedback::Bell => print!("\x07"),
+        InputFeedback::Flash if stdout.is_terminal() => {
+            let text = message.unwrap_or("invalid input");
+            print!("{}", text.reverse());
+            let _ = stdout.flush();
+            thread::sleep(Duration::from_millis(150));
+            print!("\r{}", Clear(ClearType::CurrentLine));
+        }
+        InputFeedback::Flash => {}
+    }
+    match message {
+        Some(message) => {
+            println!("{message}");
+            MESSAGE_SHOWN.store(true, Ordering::SeqCst);
+        }
+        None => {
+            let _ = stdout.flush();
+        }
+    }
+}
+
+/// Returns whether a message was printed since the last call, which clearing
+/// the screen before the next page would hide.
+pub fn take_message_shown() -> bool {
+    MESSAGE_SHOWN.swap(false, Ordering::SeqCst)
+}
+
+/// Centers a page title in a line of dashes as wide as the list rows.
+pub fn get_title_string(title: &str) -> String {
+    let title = format!(" {title} ");
+    let width = 65usize.saturating_sub(title.len());
+    format!(
+        "{}{title}{}",
+        "-".repeat(width / 2),
+        "-".repeat(width - width / 2)
+    )
+}
+
+/// Formats the second line of a row in the detailed list view, aligned below
+/// the name column. Items without a description get no second line.
+pub fn get_description_row(description: &str) -> Option<String> {
+    let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+    if description.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "{}| {}",
+        " ".repeat(12),
+        get_column_string(&description, 52).trim_end()
+    ))
+}
+
+/// Formats a status with the time spent in it, e.g. "IN PROGRESS (4d)".
+/// Items without a recorded status change only show the status.
+pub fn get_status_string(status: &Status, since: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    if since == DateTime::<Utc>::default() {
+        return status.to_string();
+    }
+
+    format!("{status} ({})", get_elapsed_string(since, now))
+}
+
+/// Formats the time between two points in the largest whole unit, e.g. "4d", "5h" or "3m".
+pub fn get_elapsed_string(since: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let elapsed = now.signed_duration_since(since);
+    if elapsed.num_days() > 0 {
+        format!("{}d", elapsed.num_days())
+    } else if elapsed.num_hours() > 0 {
+        format!("{}h", elapsed.num_hours())
+    } else {
+        format!("{}m", elapsed.num_minutes().max(0))
+    }
+}
+
+/// Formats the statuses a story went through with the time spent in each, e.g.
+/// `OPEN 3d → IN PROGRESS 5d → RESOLVED`. Returns `None` if no status change
+/// was recorded.
+pub fn get_status_history(activity: &[Activity], story_id: StoryId) -> Option<String> {
+    let mut events = activity
+        .iter()
+        .filter(|a| a.story_id == Some(story_id))
+        .collect::<Vec<_>>();
+    events.sort_by_key(|a| a.at);
+
+    let mut since = events
+        .iter()
+        .find(|a| a.event == ActivityEvent::Created)
+        .map(|a| a.at);
+    let mut steps = vec![];
+    let mut current = None;
+    for event in events {
+        let ActivityEvent::StatusChanged { from, to } = &event.event else {
+            continue;
+        };
+        steps.push(match since {
+            Some(since) => format!("{from} {}", get_elapsed_string(since, event.at)),
+            None => from.to_string(),
+        });
+        since = Some(event.at);
+        current = Some(to);
+    }
+
+    Some(format!("{} → {}", steps.join(" → "), current?))
+}
+
+/// Applies the style of the first matching highlight rule to a list row.
+pub fn get_highlighted_row(
+    row: String,
+    rules: &[HighlightRule],
+    status: &Status,
+    name: &str,
+    status_changed_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> StyledContent<String> {
+    let Some(rule) = rules
+        .iter()
+        .find(|r| r.matches(status, name, status_changed_at, now))
+    else {
+        return ContentStyle::new().apply(row);
+    };
+
+    let mut attributes = Attributes::default();
+    if rule.bold {
+        attributes.set(Attribute::Bold);
+    }
+    if rule.blink {
+        attributes.set(Attribute::SlowBlink);
+    }
+
+    ContentStyle {
+        foreground_color: rule.foreground.map(to_terminal_color),
+        background_color: rule.background.map(to_terminal_color),
+        underline_color: None,
+        attributes,
+    }
+    .apply(row)
+}
+
+/// Shows the selected row of a list in inverse video.
+pub fn get_selectable_row(row: StyledContent<String>, selected: bool) -> StyledContent<String> {
+    if selected {
+        row.reverse()
+    } else {
+        row
+    }
+}
+
+/// Maps the list selection keys to a direction, `true` meaning down.
+pub fn selection_direction(input: &str) -> Option<bool> {
+    match input {
+        "j" => Some(true),
+        "k" => Some(false),
+        _ => None,
+    }
+}
+
+/// Maps the status keys to a step through the workflow, `true` meaning forward.
+pub fn status_step(input: &str) -> Option<bool> {
+    match input {
+        ">" => Some(true),
+        "<" => Some(false),
+        _ => None,
+    }
+}
+
+/// Returns the status one step forward or back from `status`, rejecting the
+/// input at either end of the workflow.
+pub fn stepped_status(status: &Status, forward: bool, feedback: InputFeedback) -> Option<Status> {
+    let stepped = if forward {
+        status.advanced()
+    } else {
+        status.reverted()
+    };
+    if stepped.is_none() {
+        reject_input(
+            Some(&format!(
+                "status is already {}",
+                status.to_string().to_lowercase()
+            )),
+            feedback,
+        );
+    }
+    stepped
+}
+
+/// Moves a selection within `len` rows. Without a selection, moving down starts
+/// at the first row and moving up at the last one.
+pub fn moved_selection(selected: Option<usize>, len: usize, down: bool) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+
+    Some(match (selected, down) {
+        (None, true) => 0,
+        (None, false) => len - 1,
+        (Some(index), true) => (index + 1).min(len - 1),
+        (Some(index), false) => index.saturating_sub(1).min(len - 1),
+    })
+}
+
+/// Returns how many list rows fit on the terminal next to `reserved` other
+/// lines, or `None` if the output is not a terminal and lists are shown in full.
+pub fn list_height(reserved: usize) -> Option<usize> {
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    let (_, rows) = crossterm::terminal::size().ok()?;
+    Some(
+        usize::from(rows)
+            .saturating_sub(reserved)
+            .max(MIN_LIST_HEIGHT),
+    )
+}
+
+const MIN_LIST_HEIGHT: usize = 5;
+
+/// Returns the rows of a list of `len` rows that are formatted and shown,
+/// keeping the selected row in the middle of the window where possible.
+pub fn visible_rows(len: usize, selected: Option<usize>, height: Option<usize>) -> Range<usize> {
+    let Some(height) = height.filter(|height| *height < len) else {
+        return 0..len;
+    };
+
+    let start = selected
+        .map_or(0, |index| index.saturating_sub(height / 2))
+        .min(len - height);
+    start..start + height
+}
+
+/// Prints how many rows of a list are not shown above or below the window.
+pub fn print_hidden_rows(count: usize, position: &str) {
+    if count > 0 {
+        println!("{}", format!("({count} more {position})").dark_grey());
+    }
+}
+
+/// Formats a note as its local date and time followed by its text, with the
+/// following lines of the text indented below the first one.
+pub fn get_note_lines(note: &Note) -> Vec<String> {
+    let at = note
+        .at
+        .with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string();
+    note.text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let prefix = if index == 0 { at.as_str() } else { "" };
+            format!("{prefix:<16} | {line}")
+        })
+        .collect()
+}
+
+/// Returns the screen line of a click reported by the input layer.
+pub fn parse_click(input: &str) -> Option<u16> {
+    input.strip_prefix(CLICK_PREFIX)?.parse().ok()
+}
+
+/// Screen lines of the rows of a list, recorded while drawing so clicks can be
+/// mapped back to rows.
+#[derive(Default)]
+pub struct RowPositions(RefCell<Vec<(u16, usize)>>);
+
+impl RowPositions {
+    pub fn clear(&self) {
+        self.0.borrow_mut().clear();
+    }
+
+    /// Records the line of the cursor as the line of row `index`. Returns
+    /// `false` if the terminal did not report the cursor position, so callers
+    /// can stop asking instead of waiting on every row.
+    pub fn record(&self, index: usize) -> bool {
+        let _ = io::stdout().flush();
+        match cursor::position() {
+            Ok((_, line)) => {
+                self.insert(line, index);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn insert(&self, line: u16, index: usize) {
+        self.0.borrow_mut().push((line, index));
+    }
+
+    pub fn index_at(&self, line: u16) -> Option<usize> {
+        self.0
+            .borrow()
+            .iter()
+            .find(|(l, _)| *l == line)
+            .map(|(_, index)| *index)
+    }
+}
+
+/// Returns a colored dot for the health of an epic, or a blank for none.
+pub fn get_health_dot(health: Option<Health>) -> StyledContent<&'static str> {
+    match health {
+        None => " ".stylize(),
+        Some(Health::Good) => "●".green(),
+        Some(Health::Warning) => "●".yellow(),
+        Some(Health::Critical) => "●".red(),
+    }
+}
+
+fn to_terminal_color(color: Color) -> crossterm::style::Color {
+    match color {
+        Color::Black => crossterm::style::Color::Black,
+        Color::Red => crossterm::style::Color::Red,
+        Color::Green => crossterm::style::Color::Green,
+        Color::Yellow => crossterm::style::Color::Yellow,
+        Color::Blue => crossterm::style::Color::Blue,
+        Color::Magenta => crossterm::style::Color::Magenta,
+        Color::Cyan => crossterm::style::Color::Cyan,
+        Color::White => crossterm::style::Color::White,
+        Color::Grey => crossterm::style::Color::Grey,
+    }
+}
+
+pub fn get_footer_string(shortcuts: &[Shortcut]) -> String {
+    shortcuts
+        .iter()
+        .map(|s| format!("[{}] {}", s.key, s.description))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::EpicId;
 
     #[test]
     fn test_get_column_string() {
@@ -64,4 +418,197 @@ mod tests {
         assert_eq!(get_column_string(text3, width), "testme".to_owned());
         assert_eq!(get_column_string(text4, width), "tes...".to_owned());
     }
+
+    #[test]
+    fn test_get_status_string() {
+        let now = Utc::now();
+
+        assert_eq!(
+            get_status_string(&Status::InProgress, now - chrono::Duration::days(4), now),
+            "IN PROGRESS (4d)"
+        );
+        assert_eq!(
+            get_status_string(&Status::Open, now - chrono::Duration::hours(5), now),
+            "OPEN (5h)"
+        );
+        assert_eq!(get_status_string(&Status::Open, now, now), "OPEN (0m)");
+        assert_eq!(
+            get_status_string(&Status::Open, DateTime::<Utc>::default(), now),
+            "OPEN"
+        );
+    }
+
+    #[test]
+    fn test_get_highlighted_row() {
+        let now = Utc::now();
+        let rules = [
+            HighlightRule {
+                status: Some(Status::Closed),
+                foreground: Some(Color::Grey),
+                ..Default::default()
+            },
+            HighlightRule {
+                bold: true,
+                ..Default::default()
+            },
+        ];
+
+        let row = get_highlighted_row("row".to_owned(), &rules, &Status::Closed, "", now, now);
+        assert_eq!(
+            row.style().foreground_color,
+            Some(crossterm::style::Color::Grey)
+        );
+        assert!(!row.style().attributes.has(Attribute::Bold));
+
+        let row = get_highlighted_row("row".to_owned(), &rules, &Status::Open, "", now, now);
+        assert_eq!(row.style().foreground_color, None);
+        assert!(row.style().attributes.has(Attribute::Bold));
+
+        let row = get_highlighted_row("row".to_owned(), &[], &Status::Open, "", now, now);
+        assert_eq!(row.to_string(), "row");
+    }
+
+    #[test]
+    fn test_get_title_string() {
+        assert_eq!(
+            get_title_string("EPICS"),
+            "----------------------------- EPICS -----------------------------"
+        );
+        assert_eq!(get_title_string("EPICS (3 left)").len(), 65);
+    }
+
+    #[test]
+    fn test_moved_selection() {
+        assert_eq!(moved_selection(None, 0, true), None);
+        assert_eq!(moved_selection(None, 3, true), Some(0));
+        assert_eq!(moved_selection(None, 3, false), Some(2));
+        assert_eq!(moved_selection(Some(1), 3, true), Some(2));
+        assert_eq!(moved_selection(Some(2), 3, true), Some(2));
+        assert_eq!(moved_selection(Some(0), 3, false), Some(0));
+        // the list shrank since the selection was made
+        assert_eq!(moved_selection(Some(5), 3, false), Some(2));
+    }
+
+    #[test]
+    fn test_stepped_status() {
+        assert_eq!(status_step(">"), Some(true));
+        assert_eq!(status_step(">>"), None);
+        assert_eq!(
+            stepped_status(&Status::Open, true, InputFeedback::None),
+            Some(Status::InProgress)
+        );
+        assert_eq!(
+            stepped_status(&Status::Closed, false, InputFeedback::None),
+            Some(Status::Resolved)
+        );
+        assert_eq!(
+            stepped_status(&Status::Closed, true, InputFeedback::None),
+            None
+        );
+        assert_eq!(
+            stepped_status(&Status::Open, false, InputFeedback::None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_get_status_history() {
+        let start = Utc::now();
+        let changed = |days, from, to| {
+            Activity::new(
+                start + chrono::Duration::days(days),
+                EpicId(1),
+                Some(StoryId(2)),
+                "",
+                ActivityEvent::StatusChanged { from, to },
+            )
+        };
+        let mut activity = vec![
+            changed(8, Status::InProgress, Status::Resolved),
+            Activity::new(
+                start,
+                EpicId(1),
+                Some(StoryId(2)),
+                "",
+                ActivityEvent::Created,
+            ),
+            changed(3, Status::Open, Status::InProgress),
+            changed(1, Status::Open, Status::Closed),
+        ];
+        activity[3].story_id = Some(StoryId(5));
+
+        assert_eq!(
+            get_status_history(&activity, StoryId(2)).unwrap(),
+            "OPEN 3d → IN PROGRESS 5d → RESOLVED"
+        );
+        assert_eq!(
+            get_status_history(&activity[3..], StoryId(5)).unwrap(),
+            "OPEN → Closed"
+        );
+        assert_eq!(get_status_history(&activity, StoryId(9)), None);
+    }
+
+    #[test]
+    fn test_visible_rows() {
+        assert_eq!(visible_rows(10, Some(9), None), 0..10);
+        assert_eq!(visible_rows(10, Some(9), Some(10)), 0..10);
+        assert_eq!(visible_rows(100, None, Some(10)), 0..10);
+        assert_eq!(visible_rows(100, Some(3), Some(10)), 0..10);
+        assert_eq!(visible_rows(100, Some(50), Some(10)), 45..55);
+        assert_eq!(visible_rows(100, Some(99), Some(10)), 90..100);
+        assert_eq!(visible_rows(0, None, Some(10)), 0..0);
+    }
+
+    #[test]
+    fn test_get_note_lines() {
+        let note = Note {
+            at: Utc::now(),
+            text: "decided to\nship on friday".to_owned(),
+        };
+        let lines = get_note_lines(&note);
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" | decided to"));
+        assert_eq!(lines[1], "                 | ship on friday");
+    }
+
+    #[test]
+    fn test_row_positions() {
+        assert_eq!(parse_click("click:7"), Some(7));
+        assert_eq!(parse_click("click:"), None);
+        assert_eq!(parse_click("7"), None);
+
+        let rows = RowPositions::default();
+        rows.insert(4, 0);
+        rows.insert(6, 1);
+        assert_eq!(rows.index_at(6), Some(1));
+        assert_eq!(rows.index_at(5), None);
+
+        rows.clear();
+        assert_eq!(rows.index_at(4), None);
+    }
+
+    #[test]
+    fn test_get_description_row() {
+        assert_eq!(get_description_row(" \n "), None);
+        assert_eq!(
+            get_description_row("first line\nsecond line").unwrap(),
+            "            | first line second line"
+        );
+    }
+
+    #[test]
+    fn test_get_footer_string() {
+        assert_eq!(get_footer_string(&[]), "".to_owned());
+
+        let shortcuts = [
+            Shortcut::new("q", "quit"),
+            Shortcut::new("c", "create epic"),
+        ];
+
+        assert_eq!(
+            get_footer_string(&shortcuts),
+            "[q] quit | [c] create epic".to_owned()
+        );
+    }
 }
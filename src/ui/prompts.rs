@@ -1,50 +1,386 @@
+use anyhow::Result;
+
+use super::input_parser::parse_index;
+use super::status_picker::pick_status;
 use crate::{
+    config::{CustomField, Template, Templates},
     io_utils::get_user_input,
-    models::{Epic, Status, Story},
+    models::{Blocker, Epic, Retrospective, Status, Story, StoryId},
+    signals::check_interrupt,
 };
 
 static DELIMITER: &str = "----------------------------";
 
-pub struct Prompts {
-    pub create_epic: Box<dyn Fn() -> Epic>,
-    pub create_story: Box<dyn Fn() -> Story>,
-    pub delete_epic: Box<dyn Fn() -> bool>,
-    pub delete_story: Box<dyn Fn() -> bool>,
-    pub update_status: Box<dyn Fn() -> Option<Status>>,
+pub trait PromptProvider {
+    fn create_epic(&self) -> Result<Epic>;
+    fn create_story(&self) -> Result<Story>;
+    fn create_stories(&self) -> Result<Vec<Story>>;
+    /// Asks a yes/no question before a change is made.
+    fn confirm(&self, question: &str) -> Result<bool>;
+    fn update_status(&self) -> Result<Option<Status>>;
+    fn create_close_out_report(&self) -> Result<bool>;
+    fn add_note(&self) -> Result<String>;
+    fn block_story(&self) -> Result<Option<Blocker>>;
+    fn merge_story(&self) -> Result<Option<StoryId>>;
+    fn retrospective(&self) -> Result<Retrospective>;
+    /// Returns the entered value, empty to keep the current one or `-` to clear it.
+    fn custom_field(&self, field: &CustomField, current: Option<&str>) -> Result<String>;
+    fn confirm_exit(&self) -> Result<bool>;
+    /// Returns the key to replay a recorded macro with, empty to discard it.
+    fn macro_key(&self) -> Result<String>;
+}
+
+pub struct InteractivePrompts {
+    pub templates: Templates,
+}
+
+impl PromptProvider for InteractivePrompts {
+    fn create_epic(&self) -> Result<Epic> {
+        check_interrupt(create_epic_prompt(&self.templates.epics))
+    }
+
+    fn create_story(&self) -> Result<Story> {
+        check_interrupt(create_story_prompt(&self.templates.stories))
+    }
+
+    fn create_stories(&self) -> Result<Vec<Story>> {
+        check_interrupt(create_stories_prompt())
+    }
+
+    fn confirm(&self, question: &str) -> Result<bool> {
+        check_interrupt(confirm_prompt(question))
+    }
+
+    fn update_status(&self) -> Result<Option<Status>> {
+        check_interrupt(update_status_prompt())
+    }
+
+    fn create_close_out_report(&self) -> Result<bool> {
+        check_interrupt(create_close_out_report_prompt())
+    }
+
+    fn add_note(&self) -> Result<String> {
+        check_interrupt(add_note_prompt())
+    }
+
+    fn block_story(&self) -> Result<Option<Blocker>> {
+        check_interrupt(block_story_prompt())
+    }
+
+    fn merge_story(&self) -> Result<Option<StoryId>> {
+        check_interrupt(merge_story_prompt())
+    }
+
+    fn retrospective(&self) -> Result<Retrospective> {
+        check_interrupt(retrospective_prompt())
+    }
+
+    fn custom_field(&self, field: &CustomField, current: Option<&str>) -> Result<String> {
+        check_interrupt(custom_field_prompt(field, current))
+    }
+
+    fn confirm_exit(&self) -> Result<bool> {
+        check_interrupt(confirm_exit_prompt())
+    }
+
+    fn macro_key(&self) -> Result<String> {
+        check_interrupt(macro_key_prompt())
+    }
 }
 
-impl Prompts {
-    pub fn new() -> Self {
-        Self {
-            create_epic: Box::new(create_epic_prompt),
-            create_story: Box::new(create_story_prompt),
-            delete_epic: Box::new(delete_epic_prompt),
-            delete_story: Box::new(delete_story_prompt),
-            update_status: Box::new(update_status_prompt),
+pub use scripted::{ScriptedPrompts, ScriptedResponse};
+
+mod scripted {
+    use std::{cell::RefCell, collections::VecDeque};
+
+    use anyhow::{anyhow, Result};
+
+    use super::{parse_story_id, PromptProvider};
+    use crate::{
+        config::CustomField,
+        models::{Blocker, Epic, Retrospective, Status, Story, StoryId},
+    };
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum ScriptedResponse {
+        Epic(Epic),
+        Story(Story),
+        Stories(Vec<Story>),
+        Confirm(bool),
+        Status(Option<Status>),
+        Text(String),
+    }
+
+    /// Answers prompts from a predefined queue of responses instead of stdin.
+    #[derive(Default)]
+    pub struct ScriptedPrompts {
+        responses: RefCell<VecDeque<ScriptedResponse>>,
+    }
+
+    impl ScriptedPrompts {
+        pub fn new(responses: impl IntoIterator<Item = ScriptedResponse>) -> Self {
+            Self {
+                responses: RefCell::new(responses.into_iter().collect()),
+            }
+        }
+
+        fn next(&self, prompt: &str) -> Result<ScriptedResponse> {
+            self.responses
+                .borrow_mut()
+                .pop_front()
+                .ok_or_else(|| anyhow!("no scripted response left for prompt: {prompt}"))
+        }
+    }
+
+    impl PromptProvider for ScriptedPrompts {
+        fn create_epic(&self) -> Result<Epic> {
+            match self.next("create epic")? {
+                ScriptedResponse::Epic(epic) => Ok(epic),
+                r => Err(anyhow!(
+                    "unexpected scripted response for create epic: {r:?}"
+                )),
+            }
+        }
+
+        fn create_story(&self) -> Result<Story> {
+            match self.next("create story")? {
+                ScriptedResponse::Story(story) => Ok(story),
+                r => Err(anyhow!(
+                    "unexpected scripted response for create story: {r:?}"
+                )),
+            }
+        }
+
+        fn create_stories(&self) -> Result<Vec<Story>> {
+            match self.next("create stories")? {
+                ScriptedResponse::Stories(stories) => Ok(stories),
+                r => Err(anyhow!(
+                    "unexpected scripted response for create stories: {r:?}"
+                )),
+            }
+        }
+
+        fn confirm(&self, question: &str) -> Result<bool> {
+            match self.next(question)? {
+                ScriptedResponse::Confirm(decision) => Ok(decision),
+                r => Err(anyhow!(
+                    "unexpected scripted response for {question}: {r:?}"
+                )),
+            }
+        }
+
+        fn update_status(&self) -> Result<Option<Status>> {
+            match self.next("update status")? {
+                ScriptedResponse::Status(status) => Ok(status),
+                r => Err(anyhow!(
+                    "unexpected scripted response for update status: {r:?}"
+                )),
+            }
+        }
+
+        fn create_close_out_report(&self) -> Result<bool> {
+            match self.next("create close-out report")? {
+                ScriptedResponse::Confirm(decision) => Ok(decision),
+                r => Err(anyhow!(
+                    "unexpected scripted response for create close-out report: {r:?}"
+                )),
+            }
+        }
+
+        fn add_note(&self) -> Result<String> {
+            match self.next("add note")? {
+                ScriptedResponse::Text(text) => Ok(text),
+                r => Err(anyhow!("unexpected scripted response for add note: {r:?}")),
+            }
+        }
+
+        fn block_story(&self) -> Result<Option<Blocker>> {
+            match self.next("block story")? {
+                ScriptedResponse::Text(text) => Ok(text.parse().ok()),
+                r => Err(anyhow!(
+                    "unexpected scripted response for block story: {r:?}"
+                )),
+            }
+        }
+
+        fn merge_story(&self) -> Result<Option<StoryId>> {
+            match self.next("merge story")? {
+                ScriptedResponse::Text(text) => Ok(parse_story_id(&text)),
+                r => Err(anyhow!(
+                    "unexpected scripted response for merge story: {r:?}"
+                )),
+            }
+        }
+
+        fn custom_field(&self, field: &CustomField, _: Option<&str>) -> Result<String> {
+            match self.next("custom field")? {
+                ScriptedResponse::Text(text) => Ok(text),
+                r => Err(anyhow!(
+                    "unexpected scripted response for custom field {}: {r:?}",
+                    field.name
+                )),
+            }
+        }
+
+        /// Takes one text response per section, with one entry per line.
+        fn retrospective(&self) -> Result<Retrospective> {
+            let section = || match self.next("retrospective")? {
+                ScriptedResponse::Text(text) => Ok(text.lines().map(str::to_owned).collect()),
+                r => Err(anyhow!(
+                    "unexpected scripted response for retrospective: {r:?}"
+                )),
+            };
+            Ok(Retrospective {
+                went_well: section()?,
+                to_improve: section()?,
+                actions: section()?,
+            })
+        }
+
+        fn confirm_exit(&self) -> Result<bool> {
+            match self.next("confirm exit")? {
+                ScriptedResponse::Confirm(decision) => Ok(decision),
+                r => Err(anyhow!(
+                    "unexpected scripted response for confirm exit: {r:?}"
+                )),
+            }
+        }
+
+        fn macro_key(&self) -> Result<String> {
+            match self.next("macro key")? {
+                ScriptedResponse::Text(key) => Ok(key),
+                r => Err(anyhow!("unexpected scripted response for macro key: {r:?}")),
+            }
         }
     }
 }
 
-fn create_epic_prompt() -> Epic {
+fn create_epic_prompt(templates: &[Template]) -> Epic {
     println!("{DELIMITER}");
-    println!("Epic Name:");
-    let name = get_user_input();
-    println!("Epic Description:");
-    let description = get_user_input();
+    let (name, description) = name_and_description_prompt("Epic", templates);
     Epic::new(name, description)
 }
 
-fn create_story_prompt() -> Story {
+fn create_story_prompt(templates: &[Template]) -> Story {
     println!("{DELIMITER}");
-    println!("Story Name:");
-    let name = get_user_input();
-    println!("Story Description:");
-    let description = get_user_input();
+    let (name, description) = name_and_description_prompt("Story", templates);
     Story::new(name, description)
 }
 
-fn delete_epic_prompt() -> bool {
-    static QUESTION: &str = "Are you sure you want to delete this epic? All stories in this epic will also be deleted [Y/n]:";
+fn name_and_description_prompt(kind: &str, templates: &[Template]) -> (String, String) {
+    let Some(template) = template_prompt(templates) else {
+        println!("{kind} Name:");
+        let name = get_user_input();
+        println!("{kind} Description:");
+        let description = get_user_input();
+        return (name, description);
+    };
+
+    println!("{kind} Name (empty for '{}'):", template.name);
+    let name = Some(get_user_input())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| template.name.clone());
+    println!("{kind} Description (empty for the template):");
+    println!("{}", template.description);
+    let description = Some(get_user_input())
+        .filter(|description| !description.is_empty())
+        .unwrap_or_else(|| template.description.clone());
+    (name, description)
+}
+
+fn template_prompt(templates: &[Template]) -> Option<&Template> {
+    if templates.is_empty() {
+        return None;
+    }
+
+    println!("Template (empty for none):");
+    for (i, template) in templates.iter().enumerate() {
+        println!("  {}) {}", i + 1, template.name);
+    }
+
+    let input = get_user_input();
+    if input.is_empty() {
+        return None;
+    }
+    match parse_index(&input, templates.len()) {
+        Ok(index) => Some(&templates[index]),
+        Err(e) => {
+            println!("{e}, using no template");
+            None
+        }
+    }
+}
+
+fn create_stories_prompt() -> Vec<Story> {
+    println!("{DELIMITER}");
+    println!("Story Names (one per line, empty line to finish):");
+    std::iter::from_fn(|| Some(get_user_input()).filter(|name| !name.is_empty()))
+        .map(|name| Story::new(name, String::new()))
+        .collect()
+}
+
+fn add_note_prompt() -> String {
+    println!("{DELIMITER}");
+    println!("Note (empty line to finish):");
+    std::iter::from_fn(|| Some(get_user_input()).filter(|line| !line.is_empty()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn retrospective_prompt() -> Retrospective {
+    let section = |title: &str| {
+        println!("{DELIMITER}");
+        println!("{title} (one per line, empty line to finish):");
+        std::iter::from_fn(|| Some(get_user_input()).filter(|line| !line.is_empty()))
+            .collect::<Vec<_>>()
+    };
+    Retrospective {
+        went_well: section("What went well"),
+        to_improve: section("What to improve"),
+        actions: section("Actions"),
+    }
+}
+
+fn custom_field_prompt(field: &CustomField, current: Option<&str>) -> String {
+    println!("{DELIMITER}");
+    println!(
+        "{} ({}) [{}], - to clear:",
+        field.name,
+        field.hint(),
+        current.unwrap_or("")
+    );
+    get_user_input()
+}
+
+fn block_story_prompt() -> Option<Blocker> {
+    println!("{DELIMITER}");
+    println!("Blocked by (story id or reason, empty to unblock):");
+    get_user_input().parse().ok()
+}
+
+fn merge_story_prompt() -> Option<StoryId> {
+    println!("{DELIMITER}");
+    println!("Duplicate story to merge into this one (story id, empty to cancel):");
+    parse_story_id(&get_user_input())
+}
+
+/// Parses a story id, optionally written as `:id:`.
+fn parse_story_id(input: &str) -> Option<StoryId> {
+    let input = input.trim();
+    let id = input.strip_prefix(':').and_then(|s| s.strip_suffix(':'));
+    id.unwrap_or(input).parse().ok()
+}
+
+fn confirm_prompt(question: &str) -> bool {
+    println!("{DELIMITER}");
+    println!("{question}");
+    let decision = matches!(get_user_input().as_str(), "y" | "Y");
+    println!();
+    decision
+}
+
+fn create_close_out_report_prompt() -> bool {
+    static QUESTION: &str = "The epic is closed. Do you want to create a close-out report? [Y/n]:";
     println!("{DELIMITER}");
     println!("{QUESTION}");
     let decision = matches!(get_user_input().as_str(), "y" | "Y");
@@ -52,8 +388,8 @@ fn delete_epic_prompt() -> bool {
     decision
 }
 
-fn delete_story_prompt() -> bool {
-    static QUESTION: &str = "Are you sure you want to delete this story? [Y/n]:";
+fn confirm_exit_prompt() -> bool {
+    static QUESTION: &str = "There are unsaved changes. Are you sure you want to exit? [Y/n]:";
     println!("{DELIMITER}");
     println!("{QUESTION}");
     let decision = matches!(get_user_input().as_str(), "y" | "Y");
@@ -61,15 +397,40 @@ fn delete_story_prompt() -> bool {
     decision
 }
 
+fn macro_key_prompt() -> String {
+    println!("{DELIMITER}");
+    println!("Key to replay the macro with (empty to discard it):");
+    get_user_input().trim().to_owned()
+}
+
 fn update_status_prompt() -> Option<Status> {
-    static QUESTION: &str = "New Status (1 - OPEN, 2 - IN-PROGRESS, 3 - RESOLVED, 4 - CLOSED):";
+    static QUESTION: &str =
+        "New Status (type to filter, up/down to choose, or 1/open, 2/ip/in-progress, 3/r/resolved, 4/c/closed):";
     println!("{DELIMITER}");
     println!("{QUESTION}");
-    match get_user_input().as_str() {
-        "1" => Some(Status::Open),
-        "2" => Some(Status::InProgress),
-        "3" => Some(Status::Resolved),
-        "4" => Some(Status::Closed),
-        _ => None,
+    pick_status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripted_prompts_should_answer_in_order() {
+        let prompts = ScriptedPrompts::new([
+            ScriptedResponse::Confirm(true),
+            ScriptedResponse::Status(Some(Status::Closed)),
+        ]);
+
+        assert!(prompts.confirm("delete story").unwrap());
+        assert_eq!(prompts.update_status().unwrap(), Some(Status::Closed));
+        assert!(prompts.confirm("delete epic").is_err());
+    }
+
+    #[test]
+    fn scripted_prompts_should_reject_mismatched_responses() {
+        let prompts = ScriptedPrompts::new([ScriptedResponse::Confirm(true)]);
+
+        assert!(prompts.create_epic().is_err());
     }
 }
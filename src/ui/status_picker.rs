@@ -0,0 +1,184 @@
+use std::io::{self, IsTerminal, Write};
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    style::Stylize,
+    terminal::{self, Clear, ClearType},
+};
+
+use crate::{io_utils::get_user_input, models::Status, signals};
+
+/// Filters the statuses by what was typed so far and keeps one of the matches
+/// highlighted, which Enter picks.
+#[derive(Debug, Default)]
+pub struct StatusPicker {
+    query: String,
+    highlighted: usize,
+}
+
+impl StatusPicker {
+    /// Statuses whose name contains the query, or that the query names by
+    /// number or abbreviation, in workflow order.
+    pub fn matches(&self) -> Vec<Status> {
+        let query = self.query.trim().to_lowercase();
+        let alias = query.parse::<Status>().ok();
+        Status::ALL
+            .into_iter()
+            .filter(|status| {
+                status.to_string().to_lowercase().contains(&query) || alias.as_ref() == Some(status)
+            })
+            .collect()
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.highlighted = 0;
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.highlighted = 0;
+    }
+
+    /// Moves the highlight through the matches, wrapping around at either end.
+    pub fn cycle(&mut self, down: bool) {
+        let len = self.matches().len();
+        if len == 0 {
+            return;
+        }
+        self.highlighted = if down {
+            (self.highlighted + 1) % len
+        } else {
+            (self.highlighted + len - 1) % len
+        };
+    }
+
+    pub fn pick(&self) -> Option<Status> {
+        self.matches().get(self.highlighted).cloned()
+    }
+
+    fn line(&self) -> String {
+        let matches = self
+            .matches()
+            .iter()
+            .enumerate()
+            .map(|(index, status)| {
+                if index == self.highlighted {
+                    status.to_string().reverse().to_string()
+                } else {
+                    status.to_string()
+                }
+            })
+            .collect::<Vec<_>>();
+        let matches = if matches.is_empty() {
+            "no matching status".dark_grey().to_string()
+        } else {
+            matches.join("  ")
+        };
+        format!("> {:<12} {matches}", self.query)
+    }
+}
+
+/// Asks for a status. On a terminal the statuses are filtered while typing;
+/// otherwise a line is read and parsed like the `--status` option.
+pub fn pick_status() -> Option<Status> {
+    if signals::is_interrupted() || !io::stdin().is_terminal() {
+        return get_user_input().parse().ok();
+    }
+
+    terminal::enable_raw_mode().ok()?;
+    let picked = read_pick();
+    let _ = terminal::disable_raw_mode();
+    println!();
+
+    picked.ok().flatten()
+}
+
+fn read_pick() -> io::Result<Option<Status>> {
+    let mut stdout = io::stdout();
+    let mut picker = StatusPicker::default();
+
+    loop {
+        write!(
+            stdout,
+            "\r{}{}",
+            Clear(ClearType::CurrentLine),
+            picker.line()
+        )?;
+        stdout.flush()?;
+
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind,
+            ..
+        }) = event::read()?
+        else {
+            continue;
+        };
+
+        // Windows reports both key presses and releases.
+        if kind == KeyEventKind::Release {
+            continue;
+        }
+
+        match code {
+            KeyCode::Enter => return Ok(picker.pick()),
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                signals::interrupt();
+                return Ok(None);
+            }
+            KeyCode::Down | KeyCode::Tab => picker.cycle(true),
+            KeyCode::Up | KeyCode::BackTab => picker.cycle(false),
+            KeyCode::Char(c) => picker.push(c),
+            KeyCode::Backspace => picker.pop(),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(query: &str) -> StatusPicker {
+        let mut picker = StatusPicker::default();
+        query.chars().for_each(|c| picker.push(c));
+        picker
+    }
+
+    #[test]
+    fn matches_should_filter_by_name_and_alias() {
+        assert_eq!(typed("").matches(), Status::ALL.to_vec());
+        assert_eq!(typed("reso").matches(), vec![Status::Resolved]);
+        assert_eq!(
+            typed("o").matches(),
+            vec![
+                Status::Open,
+                Status::InProgress,
+                Status::Resolved,
+                Status::Closed
+            ]
+        );
+        assert_eq!(typed("ip").matches(), vec![Status::InProgress]);
+        assert_eq!(typed("4").matches(), vec![Status::Closed]);
+        assert_eq!(typed("xyz").pick(), None);
+    }
+
+    #[test]
+    fn cycle_should_wrap_around_the_matches() {
+        let mut picker = typed("s");
+        assert_eq!(
+            picker.matches(),
+            vec![Status::InProgress, Status::Resolved, Status::Closed]
+        );
+        picker.cycle(false);
+        assert_eq!(picker.pick(), Some(Status::Closed));
+        picker.cycle(true);
+        assert_eq!(picker.pick(), Some(Status::InProgress));
+
+        picker.pop();
+        assert_eq!(picker.pick(), Some(Status::Open));
+    }
+}
@@ -0,0 +1,103 @@
+use itertools::Itertools;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Widget};
+
+use crate::models::DBState;
+
+/// The epics of a board with their stories, for other ratatui applications to
+/// embed. Build it from the latest state on every frame to show a live view.
+pub struct BoardWidget<'a> {
+    db_state: &'a DBState,
+}
+
+impl<'a> BoardWidget<'a> {
+    pub fn new(db_state: &'a DBState) -> Self {
+        Self { db_state }
+    }
+
+    fn lines(&self) -> Vec<Line<'a>> {
+        let db_state = self.db_state;
+        let mut lines = vec![];
+        for (epic_id, epic) in db_state.epics.iter().sorted_by_key(|(id, _)| **id) {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{} {}", db_state.epic_label(*epic_id), epic.name),
+                    Style::new().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!(" {}", epic.status)),
+            ]));
+
+            for story_id in epic.stories.iter().sorted() {
+                let Some(story) = db_state.stories.get(story_id) else {
+                    continue;
+                };
+                let mut spans = vec![Span::raw(format!(
+                    "  {} {} {}",
+                    db_state.story_label(*epic_id, *story_id),
+                    story.name,
+                    story.status
+                ))];
+                if let Some(blocker) = &story.blocker {
+                    spans.push(Span::styled(
+                        format!(" {}", db_state.blocker_label(blocker)),
+                        Style::new().fg(Color::Red),
+                    ));
+                }
+                lines.push(Line::from(spans));
+            }
+        }
+        lines
+    }
+}
+
+impl Widget for BoardWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.lines()).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Blocker, Epic, EpicId, Story, StoryId};
+
+    #[test]
+    fn render_should_draw_epics_with_their_stories() {
+        let mut db_state = DBState::new();
+        let mut epic = Epic::new("Checkout".to_owned(), "".to_owned());
+        epic.stories = vec![StoryId(3), StoryId(2)];
+        db_state.epics.insert(EpicId(1), epic);
+        db_state
+            .stories
+            .insert(StoryId(2), Story::new("Cart".to_owned(), "".to_owned()));
+        let mut story = Story::new("Pay".to_owned(), "".to_owned());
+        story.blocker = Some(Blocker::Reason("legal".to_owned()));
+        db_state.stories.insert(StoryId(3), story);
+
+        let area = Rect::new(0, 0, 30, 4);
+        let mut buf = Buffer::empty(area);
+        BoardWidget::new(&db_state).render(area, &mut buf);
+
+        let rows = (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![
+                "1 Checkout OPEN               ",
+                "  2 Cart OPEN                 ",
+                "  3 Pay OPEN blocked: legal   ",
+                "                              ",
+            ]
+        );
+        assert!(buf[(0, 0)].modifier.contains(Modifier::BOLD));
+        assert_eq!(buf[(13, 2)].fg, Color::Red);
+    }
+}
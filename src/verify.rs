@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use serde_json::Value;
+
+use crate::models::{Blocker, DBState, EpicId, StoryId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    /// Content that is dropped when the file is loaded and saved again.
+    Lossy,
+    /// Content that is kept but written differently when the file is saved again.
+    NonCanonical,
+    /// A field missing from the file, e.g. one added after it was written, that
+    /// is filled in with its default.
+    Defaulted,
+    InvalidReference,
+}
+
+impl IssueKind {
+    /// Whether the data is lost or broken, rather than only written differently.
+    pub fn is_problem(self) -> bool {
+        matches!(self, Self::Lossy | Self::InvalidReference)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+impl Issue {
+    fn new(kind: IssueKind, message: String) -> Self {
+        Self { kind, message }
+    }
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            IssueKind::Lossy => "lossy",
+            IssueKind::NonCanonical => "non-canonical",
+            IssueKind::Defaulted => "info",
+            IssueKind::InvalidReference => "invalid reference",
+        };
+        write!(f, "{kind}: {}", self.message)
+    }
+}
+
+/// Loads the contents of a database file, saves it again in memory and reports
+/// everything the round trip would change, plus references to missing items.
+pub fn verify(contents: &str) -> Result<Vec<Issue>> {
+    let raw: Value = serde_json::from_str(contents).map_err(|e| anyhow!("invalid JSON: {e}"))?;
+    let db_state: DBState =
+        serde_json::from_value(raw.clone()).map_err(|e| anyhow!("invalid database: {e}"))?;
+    let saved = serde_json::to_value(&db_state)?;
+
+    let mut issues = vec![];
+    compare(&raw, &saved, "", &mut issues);
+    check_references(&db_state, &mut issues);
+    Ok(issues)
+}
+
+fn compare(raw: &Value, saved: &Value, path: &str, issues: &mut Vec<Issue>) {
+    let child = |key: &dyn Display| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+
+    match (raw, saved) {
+        (Value::Object(raw), Value::Object(saved)) => {
+            for (key, value) in raw {
+                match saved.get(key) {
+                    Some(saved) => compare(value, saved, &child(key), issues),
+                    None => issues.push(Issue::new(
+                        IssueKind::Lossy,
+                        format!("unknown field {}", child(key)),
+                    )),
+                }
+            }
+            for key in saved.keys().filter(|key| !raw.contains_key(*key)) {
+                issues.push(Issue::new(
+                    IssueKind::Defaulted,
+                    format!("missing field {} is filled in with a default", child(key)),
+                ));
+            }
+        }
+        (Value::Array(raw), Value::Array(saved)) if raw.len() == saved.len() => {
+            for (index, (raw, saved)) in raw.iter().zip(saved).enumerate() {
+                compare(raw, saved, &child(&index), issues);
+            }
+        }
+        (raw, saved) if raw != saved => issues.push(Issue::new(
+            IssueKind::NonCanonical,
+            format!("{path} is saved as {saved} instead of {raw}"),
+        )),
+        _ => {}
+    }
+}
+
+fn check_references(db_state: &DBState, issues: &mut Vec<Issue>) {
+    let mut invalid =
+        |message: String| issues.push(Issue::new(IssueKind::InvalidReference, message));
+
+    let mut owners: HashMap<StoryId, Vec<EpicId>> = HashMap::new();
+    for (epic_id, epic) in db_state.epics.iter().sorted_by_key(|(id, _)| **id) {
+        for story_id in &epic.stories {
+            owners.entry(*story_id).or_default().push(*epic_id);
+            if !db_state.stories.contains_key(story_id) {
+                invalid(format!("epic {epic_id} lists missing story {story_id}"));
+            }
+        }
+    }
+
+    for story_id in db_state.stories.keys().sorted() {
+        match owners.get(story_id).map(Vec::len) {
+            None => invalid(format!("story {story_id} is not in any epic")),
+            Some(1) => {}
+            Some(_) => invalid(format!(
+                "story {story_id} is listed by several epics: {:?}",
+                owners[story_id]
+            )),
+        }
+        if let Some(Blocker::Story(blocking_id)) = db_state.stories[story_id].blocker {
+            if !db_state.stories.contains_key(&blocking_id) {
+                invalid(format!(
+                    "story {story_id} is blocked by missing story {blocking_id}"
+                ));
+            }
+        }
+        if db_state.epics.contains_key(&EpicId(story_id.0)) {
+            invalid(format!("id {story_id} is used by an epic and a story"));
+        }
+    }
+
+    let max_id = db_state
+        .epics
+        .keys()
+        .map(|id| id.0)
+        .chain(db_state.stories.keys().map(|id| id.0))
+        .max()
+        .unwrap_or(0);
+    if max_id > db_state.last_item_id {
+        invalid(format!(
+            "last_item_id {} is lower than the highest id {max_id}",
+            db_state.last_item_id
+        ));
+    }
+
+    for sprint in &db_state.sprints {
+        for story_id in &sprint.stories {
+            if !db_state.stories.contains_key(story_id) {
+                invalid(format!(
+                    "sprint '{}' contains missing story {story_id}",
+                    sprint.name
+                ));
+            }
+        }
+    }
+
+    for recurring in &db_state.recurring_stories {
+        if !db_state.epics.contains_key(&recurring.epic_id) {
+            invalid(format!(
+                "recurring story '{}' belongs to missing epic {}",
+                recurring.name, recurring.epic_id
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{test_utils::MockDB, JiraDatabase};
+
+    #[test]
+    fn verify_should_accept_a_saved_database() {
+        let db = JiraDatabase {
+            database: Box::new(MockDB::new()),
+        };
+        crate::demo::seed(&db).unwrap();
+        let contents = serde_json::to_string_pretty(&db.read_db().unwrap()).unwrap();
+
+        assert_eq!(verify(&contents).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn verify_should_report_lossy_content_and_invalid_references() {
+        let contents = r#"{
+            "last_item_id": 1,
+            "epics": {
+                "1": { "name": "", "description": "", "status": "Open", "stories": [2], "color": "red" }
+            },
+            "stories": {
+                "3": { "name": "", "description": "", "status": "Open" }
+            }
+        }"#;
+
+        let issues = verify(contents).unwrap();
+        let messages = |kind| {
+            issues
+                .iter()
+                .filter(|i| i.kind == kind)
+                .map(|i| i.message.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            messages(IssueKind::Lossy),
+            vec!["unknown field epics.1.color"]
+        );
+        assert!(messages(IssueKind::Defaulted)
+            .contains(&"missing field epics.1.pinned is filled in with a default"));
+        assert!(messages(IssueKind::NonCanonical).is_empty());
+        assert_eq!(
+            messages(IssueKind::InvalidReference),
+            vec![
+                "epic 1 lists missing story 2",
+                "story 3 is not in any epic",
+                "last_item_id 1 is lower than the highest id 3"
+            ]
+        );
+        assert!(verify("{").is_err());
+        assert!(verify("{}").is_err());
+    }
+}
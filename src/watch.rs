@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::db::{read_db_file, write_db_file};
+use crate::diff::{BoardDiff, ItemChange};
+use crate::models::{DBState, StoryId};
+
+/// The watched stories as they were when the board was last closed are kept
+/// next to the database file.
+fn seen_path(db_path: &str) -> String {
+    format!("{db_path}.watched")
+}
+
+/// The watched stories of the board with the epics they belong to.
+fn watched_subset(db_state: &DBState) -> DBState {
+    let mut subset = db_state.clone();
+    subset.stories.retain(|_, story| story.watched);
+    subset.epics.retain(|_, epic| {
+        epic.stories.retain(|id| subset.stories.contains_key(id));
+        !epic.stories.is_empty()
+    });
+    subset
+}
+
+/// Returns what changed on the watched stories since they were last seen,
+/// whether by an edit from another backend or a sync. Nothing is reported
+/// before the board was seen once.
+pub fn watched_changes(db_path: &str, db_state: &DBState) -> Result<BoardDiff> {
+    let path = seen_path(db_path);
+    if !Path::new(&path).exists() {
+        return Ok(BoardDiff::default());
+    }
+
+    let diff = BoardDiff::collect(&read_db_file(&path)?, &watched_subset(db_state));
+    Ok(BoardDiff {
+        epics: vec![],
+        stories: diff
+            .stories
+            .into_iter()
+            .filter(|item| match item.change {
+                ItemChange::Changed { .. } => true,
+                // watching a story isn't news, and unwatching one only is when it was deleted
+                ItemChange::Added => false,
+                ItemChange::Removed => !db_state.stories.contains_key(&StoryId(item.id)),
+            })
+            .collect(),
+    })
+}
+
+/// Records the watched stories as seen, so only later changes are reported.
+pub fn mark_seen(db_path: &str, db_state: &DBState) -> Result<()> {
+    write_db_file(&seen_path(db_path), &watched_subset(db_state))
+}
+
+/// Gives the seen stories the ids `compact` gave them. Stories that were
+/// deleted since are forgotten, as their ids now belong to other items.
+pub fn renumber_seen(db_path: &str, ids: &BTreeMap<u32, u32>) -> Result<()> {
+    let path = seen_path(db_path);
+    if !Path::new(&path).exists() {
+        return Ok(());
+    }
+
+    let mut seen = read_db_file(&path)?;
+    seen.stories.retain(|id, _| ids.contains_key(&id.0));
+    seen.epics.retain(|id, epic| {
+        epic.stories.retain(|id| ids.contains_key(&id.0));
+        ids.contains_key(&id.0) && !epic.stories.is_empty()
+    });
+    seen.renumber(&ids.iter().map(|(old, new)| (*old, *new)).collect());
+    write_db_file(&path, &seen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::FieldChange;
+    use crate::models::{Epic, EpicId, Status, Story};
+
+    #[test]
+    fn watched_changes_should_report_changes_since_last_seen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        let path = path.to_str().unwrap();
+
+        let mut db_state = DBState::new();
+        let mut epic = Epic::new("Checkout".to_owned(), "".to_owned());
+        epic.stories = vec![StoryId(2), StoryId(3), StoryId(4)];
+        db_state.epics.insert(EpicId(1), epic);
+        for (id, name) in [(2, "Pay"), (3, "Refund"), (4, "Coupons")] {
+            let mut story = Story::new(name.to_owned(), "".to_owned());
+            story.watched = id != 4;
+            db_state.stories.insert(StoryId(id), story);
+        }
+        assert!(watched_changes(path, &db_state).unwrap().is_empty());
+        mark_seen(path, &db_state).unwrap();
+
+        db_state.stories.get_mut(&StoryId(2)).unwrap().status = Status::Resolved;
+        db_state.stories.remove(&StoryId(3));
+        db_state.epics.get_mut(&EpicId(1)).unwrap().stories = vec![StoryId(2), StoryId(4)];
+        db_state.stories.get_mut(&StoryId(4)).unwrap().name = "Vouchers".to_owned();
+        let changes = watched_changes(path, &db_state).unwrap();
+        assert!(changes.epics.is_empty());
+        assert_eq!(
+            changes
+                .stories
+                .iter()
+                .map(|s| (s.id, s.change.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    2,
+                    ItemChange::Changed {
+                        fields: vec![FieldChange {
+                            field: "status",
+                            from: "OPEN".to_owned(),
+                            to: "RESOLVED".to_owned(),
+                        }]
+                    }
+                ),
+                (3, ItemChange::Removed),
+            ]
+        );
+
+        mark_seen(path, &db_state).unwrap();
+        db_state.stories.get_mut(&StoryId(2)).unwrap().watched = false;
+        db_state.stories.get_mut(&StoryId(4)).unwrap().watched = true;
+        assert!(watched_changes(path, &db_state).unwrap().is_empty());
+    }
+
+    #[test]
+    fn renumber_seen_should_follow_compacted_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        let path = path.to_str().unwrap();
+
+        let mut db_state = DBState::new();
+        let mut epic = Epic::new("Checkout".to_owned(), "".to_owned());
+        epic.stories = vec![StoryId(4), StoryId(5)];
+        db_state.epics.insert(EpicId(3), epic);
+        for (id, name) in [(4, "Pay"), (5, "Refund")] {
+            let mut story = Story::new(name.to_owned(), "".to_owned());
+            story.watched = true;
+            db_state.stories.insert(StoryId(id), story);
+        }
+        mark_seen(path, &db_state).unwrap();
+
+        // story 4 was deleted before the board was compacted
+        renumber_seen(path, &BTreeMap::from([(3, 1), (5, 2)])).unwrap();
+
+        let seen = read_db_file(&seen_path(path)).unwrap();
+        assert_eq!(seen.epics[&EpicId(1)].stories, vec![StoryId(2)]);
+        assert_eq!(seen.stories[&StoryId(2)].name, "Refund");
+        assert_eq!(seen.stories.len(), 1);
+    }
+}
@@ -0,0 +1,198 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use itertools::Itertools;
+
+use crate::db::read_db_file;
+use crate::models::DBState;
+
+/// Seconds after which the browser reloads the page to show recent changes.
+const REFRESH_SECS: u32 = 60;
+
+/// How long a client may take to send its request or read the page. Requests
+/// are answered one at a time, so a stalled client must not hold up the rest.
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serves the board on `addr` as a read-only web page until interrupted. The
+/// database file is read again for every request.
+pub fn serve(db_path: &str, addr: &str) -> Result<()> {
+    let listener =
+        TcpListener::bind(addr).with_context(|| format!("failed to listen on {addr}"))?;
+    println!("serving {db_path} on http://{}", listener.local_addr()?);
+
+    for stream in listener.incoming() {
+        if let Err(e) = stream
+            .map_err(anyhow::Error::from)
+            .and_then(|stream| respond(db_path, stream, IO_TIMEOUT))
+        {
+            eprintln!("failed to answer request: {e}");
+        }
+    }
+    Ok(())
+}
+
+fn respond(db_path: &str, mut stream: TcpStream, timeout: Duration) -> Result<()> {
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // skip the headers, the page doesn't depend on them
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let (status, body) = match request_line.split_whitespace().take(2).collect_tuple() {
+        Some(("GET", "/")) => match read_db_file(db_path) {
+            Ok(db_state) => ("200 OK", render(&db_state)),
+            Err(e) => (
+                "500 Internal Server Error",
+                page("error", &format!("<p>{}</p>", escape(&e.to_string()))),
+            ),
+        },
+        _ => ("404 Not Found", page("not found", "<p>not found</p>")),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    Ok(())
+}
+
+/// Renders the epics of the board with their stories as an HTML page.
+pub fn render(db_state: &DBState) -> String {
+    let mut body = String::new();
+    if db_state.epics.is_empty() {
+        body.push_str("<p>no epics yet</p>");
+    }
+
+    for (epic_id, epic) in db_state.epics.iter().sorted_by_key(|(id, _)| **id) {
+        body.push_str(&format!(
+            "<h2>{} {} <small>{}</small></h2>",
+            escape(&db_state.epic_label(*epic_id)),
+            escape(&epic.name),
+            escape(&epic.status.to_string())
+        ));
+        if !epic.description.is_empty() {
+            body.push_str(&format!("<p>{}</p>", escape(&epic.description)));
+        }
+
+        let stories = epic
+            .stories
+            .iter()
+            .filter_map(|id| db_state.stories.get(id).map(|story| (*id, story)))
+            .collect::<Vec<_>>();
+        if stories.is_empty() {
+            continue;
+        }
+        body.push_str("<table><tr><th>id</th><th>name</th><th>status</th><th></th></tr>");
+        for (story_id, story) in stories {
+            let blocker = story
+                .blocker
+                .as_ref()
+                .map_or(String::new(), |b| db_state.blocker_label(b));
+            body.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"blocked\">{}</td></tr>",
+                escape(&db_state.story_label(*epic_id, story_id)),
+                escape(&story.name),
+                escape(&story.status.to_string()),
+                escape(&blocker)
+            ));
+        }
+        body.push_str("</table>");
+    }
+
+    page("board", &body)
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\">\
+         <meta http-equiv=\"refresh\" content=\"{REFRESH_SECS}\">\
+         <title>scrumtask - {title}</title>\
+         <style>body{{font-family:monospace;margin:2em}}table{{border-collapse:collapse}}\
+         td,th{{padding:2px 12px;text-align:left}}.blocked{{color:#c00}}</style>\
+         </head><body>{body}</body></html>"
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::thread;
+
+    use crate::db::write_db_file;
+    use crate::models::{Blocker, Epic, EpicId, Story, StoryId};
+
+    #[test]
+    fn render_should_list_epics_and_escaped_stories() {
+        let mut db_state = DBState::new();
+        let mut epic = Epic::new("Checkout".to_owned(), "".to_owned());
+        epic.stories = vec![StoryId(2)];
+        db_state.epics.insert(EpicId(1), epic);
+        let mut story = Story::new("<script>".to_owned(), "".to_owned());
+        story.blocker = Some(Blocker::Reason("legal".to_owned()));
+        db_state.stories.insert(StoryId(2), story);
+
+        let html = render(&db_state);
+        assert!(html.contains("<h2>1 Checkout <small>OPEN</small></h2>"));
+        assert!(html.contains("<td>&lt;script&gt;</td>"));
+        assert!(html.contains("blocked: legal"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn respond_should_serve_the_board_and_nothing_else() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("db.json");
+        let path = path.to_str().unwrap().to_owned();
+        let mut db_state = DBState::new();
+        db_state
+            .epics
+            .insert(EpicId(1), Epic::new("Served".to_owned(), "".to_owned()));
+        write_db_file(&path, &db_state).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            for stream in listener.incoming().take(2) {
+                respond(&path, stream.unwrap(), IO_TIMEOUT).unwrap();
+            }
+        });
+
+        let get = |target: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "GET {target} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let board = get("/");
+        assert!(board.starts_with("HTTP/1.1 200 OK"));
+        assert!(board.contains("Served"));
+        assert!(get("/db.json").starts_with("HTTP/1.1 404 Not Found"));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn respond_should_give_up_on_a_silent_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+
+        assert!(respond("INVALID_PATH", stream, Duration::from_millis(50)).is_err());
+    }
+}